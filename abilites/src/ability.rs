@@ -0,0 +1,149 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use components::{items::MetaData, status::StatusEffect};
+use std::fmt;
+
+/// Who an ability can be used on.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AbilityTarget {
+    /// An opponent.
+    Enemy,
+    /// An ally, Including the caster.
+    Ally,
+    /// The caster itself.
+    Caster,
+}
+
+/// What an ability does to its target.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AbilityEffect {
+    /// Deals damage. Abilities can't be evaded.
+    Damage(u32),
+    /// Restores health.
+    Heal(u32),
+    /// Applies a status effect.
+    Apply(StatusEffect),
+}
+
+/// An ability a character can use.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Ability {
+    name: &'static str,
+    description: &'static str,
+    target: AbilityTarget,
+    effect: AbilityEffect,
+    cooldown: u32,
+    cost: u32,
+    cast_time: u32,
+}
+
+impl fmt::Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Ability(name: {}, cooldown: {}, cost: {}, description: {})",
+            self.name, self.cooldown, self.cost, self.description
+        )
+    }
+}
+
+impl MetaData for Ability {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+}
+
+impl Ability {
+    /// Creates a new ability.
+    ///
+    /// ## Parameters
+    /// cooldown: [u32] How many turns the caster must wait before using this ability again.
+    pub const fn new(
+        name: &'static str,
+        description: &'static str,
+        target: AbilityTarget,
+        effect: AbilityEffect,
+        cooldown: u32,
+    ) -> Ability {
+        Ability {
+            name,
+            description,
+            target,
+            effect,
+            cooldown,
+            cost: 0,
+            cast_time: 0,
+        }
+    }
+
+    /// Set the mana this ability costs.
+    pub const fn with_cost(mut self, cost: u32) -> Ability {
+        self.cost = cost;
+        self
+    }
+
+    /// Set how long casting this ability takes in milliseconds.
+    ///
+    /// Abilities resolve instantly in turn based battles.
+    pub const fn with_cast_time(mut self, cast_time: u32) -> Ability {
+        self.cast_time = cast_time;
+        self
+    }
+
+    /// Who this ability can be used on.
+    pub fn target(&self) -> AbilityTarget {
+        self.target
+    }
+
+    /// What this ability does to its target.
+    pub fn effect(&self) -> AbilityEffect {
+        self.effect
+    }
+
+    /// The cooldown of this ability in turns.
+    pub fn cooldown(&self) -> u32 {
+        self.cooldown
+    }
+
+    /// The mana this ability costs.
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    /// How long casting this ability takes in milliseconds.
+    pub fn cast_time(&self) -> u32 {
+        self.cast_time
+    }
+}
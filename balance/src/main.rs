@@ -0,0 +1,283 @@
+//! A headless simulator that runs batches of battles to help tune the game's balance.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+mod report;
+
+use crate::report::{to_csv, to_json, Batch, Gear, Policy};
+use characters::CharacterRegistry;
+use std::{fs, io::Write};
+
+/// The characters that fight each other when no sides are given.
+const BUILTINS: [&str; 4] = ["Vamp", "Kain", "Susanoo", "Tyr"];
+
+const USAGE: &str = "\
+Usage: balance [OPTIONS]
+
+Runs batches of seeded battles for every combination of allies, enemies and gear.
+
+Options:
+    --allies <NAMES>     Comma separated character names of an allied side, Repeatable.
+                         Defaults to each builtin character alone.
+    --enemies <NAMES>    Comma separated character names of an enemy side, Repeatable.
+                         Defaults to each builtin character alone.
+    --gear <GEAR>        The allies' main hand weapon, i.e., `default`, `Bow` or `Bow:Exotic`.
+                         Repeatable, Defaults to `default`.
+    --ai <POLICY>        aggressive, defensive, healer, kiting or utility. Defaults to utility.
+    --seeds <COUNT>      The amount of battles per batch. Defaults to 100.
+    --seed <SEED>        The first seed. Defaults to 0.
+    --rounds <ROUNDS>    The amount of rounds before a battle ends in a draw.
+    --format <FORMAT>    csv or json. Defaults to csv.
+    --out <PATH>         Write to a file instead of stdout.
+    --list               List the character names that can fight.
+    -h, --help           Print this message.
+";
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct Args {
+    allies: Vec<Vec<String>>,
+    enemies: Vec<Vec<String>>,
+    gear: Vec<Gear>,
+    policy: Policy,
+    seeds: u64,
+    seed: u64,
+    rounds: Option<u32>,
+    format: Format,
+    out: Option<String>,
+    list: bool,
+    help: bool,
+}
+
+impl Args {
+    /// Parse the arguments, Fails if a side names a character that isn't in the registry.
+    fn parse<I: IntoIterator<Item = String>>(
+        args: I,
+        registry: &CharacterRegistry,
+    ) -> anyhow::Result<Args> {
+        let mut parsed = Args {
+            allies: Vec::new(),
+            enemies: Vec::new(),
+            gear: Vec::new(),
+            policy: Policy::Utility,
+            seeds: 100,
+            seed: 0,
+            rounds: None,
+            format: Format::Csv,
+            out: None,
+            list: false,
+            help: false,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow::anyhow!("{} expects a value", arg))
+            };
+            match arg.as_str() {
+                "--allies" => parsed.allies.push(side(&value()?)),
+                "--enemies" => parsed.enemies.push(side(&value()?)),
+                "--gear" => parsed.gear.push(Gear::parse(&value()?)?),
+                "--ai" => parsed.policy = Policy::parse(&value()?)?,
+                "--seeds" => parsed.seeds = value()?.parse()?,
+                "--seed" => parsed.seed = value()?.parse()?,
+                "--rounds" => parsed.rounds = Some(value()?.parse()?),
+                "--format" => {
+                    parsed.format = match value()?.as_str() {
+                        "csv" => Format::Csv,
+                        "json" => Format::Json,
+                        format => return Err(anyhow::anyhow!("Unknown format {}", format)),
+                    }
+                }
+                "--out" => parsed.out = Some(value()?),
+                "--list" => parsed.list = true,
+                "-h" | "--help" => parsed.help = true,
+                _ => return Err(anyhow::anyhow!("Unknown argument {}\n\n{}", arg, USAGE)),
+            }
+        }
+
+        let builtins = || BUILTINS.iter().map(|name| vec![name.to_string()]).collect();
+        if parsed.allies.is_empty() {
+            parsed.allies = builtins();
+        }
+        if parsed.enemies.is_empty() {
+            parsed.enemies = builtins();
+        }
+        if parsed.gear.is_empty() {
+            parsed.gear.push(Gear::Default);
+        }
+        for name in parsed.allies.iter().chain(parsed.enemies.iter()).flatten() {
+            if registry.get(name).is_none() {
+                return Err(anyhow::anyhow!(
+                    "Unknown character {}, See --list for the names that can fight",
+                    name
+                ));
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Every combination of allies, enemies and gear.
+    fn batches(&self) -> Vec<Batch> {
+        let mut batches = Vec::new();
+        for allies in self.allies.iter() {
+            for enemies in self.enemies.iter() {
+                for gear in self.gear.iter() {
+                    batches.push(Batch {
+                        allies: allies.clone(),
+                        enemies: enemies.clone(),
+                        gear: *gear,
+                        policy: self.policy,
+                        seeds: self.seed..self.seed.saturating_add(self.seeds),
+                        max_rounds: self.rounds,
+                    });
+                }
+            }
+        }
+        batches
+    }
+}
+
+fn side(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn main() -> anyhow::Result<()> {
+    let registry = CharacterRegistry::default();
+    let args = Args::parse(std::env::args().skip(1), &registry)?;
+    if args.help {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    if args.list {
+        for entry in registry.iter() {
+            println!("{}", entry.name());
+        }
+        return Ok(());
+    }
+
+    let mut reports = Vec::new();
+    for batch in args.batches() {
+        // Classes that can't wield a gear set's weapon sit that batch out, The names were checked already.
+        if let Err(err) = batch.spawn(&registry, batch.seeds.start) {
+            eprintln!(
+                "Skipping {} vs {} with {} gear, {}",
+                batch.allies.join("+"),
+                batch.enemies.join("+"),
+                batch.gear,
+                err
+            );
+            continue;
+        }
+        reports.push(batch.run(&registry)?);
+    }
+
+    let output = match args.format {
+        Format::Csv => to_csv(&reports),
+        Format::Json => to_json(&reports)?,
+    };
+    match args.out {
+        Some(path) => fs::write(path, output)?,
+        None => std::io::stdout().write_all(output.as_bytes())?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use components::WeaponType;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Args> {
+        Args::parse(
+            args.iter().map(|s| s.to_string()),
+            &CharacterRegistry::default(),
+        )
+    }
+
+    #[test]
+    fn test_args() {
+        let args = parse(&[]).unwrap();
+        assert_eq!(args.allies.len(), BUILTINS.len());
+        assert_eq!(args.gear, vec![Gear::Default]);
+        assert_eq!(args.batches().len(), BUILTINS.len() * BUILTINS.len());
+
+        let args = parse(&[
+            "--allies",
+            "Tyr, Vamp",
+            "--enemies",
+            "ghoul",
+            "--enemies",
+            "imp",
+            "--gear",
+            "bow",
+            "--gear",
+            "default",
+            "--ai",
+            "kiting",
+            "--seeds",
+            "10",
+            "--seed",
+            "5",
+            "--format",
+            "json",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.allies,
+            vec![vec!["Tyr".to_string(), "Vamp".to_string()]]
+        );
+        assert_eq!(args.policy, Policy::Kiting);
+        assert_eq!(args.format, Format::Json);
+        let batches = args.batches();
+        assert_eq!(batches.len(), 4);
+        assert_eq!(batches[0].gear, Gear::Weapon(WeaponType::Bow, None));
+        assert_eq!(batches[0].seeds, 5..15);
+
+        assert!(parse(&["--seeds"]).is_err());
+        assert!(parse(&["--seeds", "many"]).is_err());
+        assert!(parse(&["--format", "xml"]).is_err());
+        assert!(parse(&["--fast"]).is_err());
+        assert!(parse(&["--allies", "Tyr,Tyrr"]).is_err());
+        assert!(parse(&["--enemies", "gohul"]).is_err());
+    }
+}
@@ -0,0 +1,569 @@
+//! Running batches of simulated battles and collecting their statistics.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use characters::{Char, CharacterRegistry};
+use combat::{
+    ai::{Aggressive, Defensive, Healer, Kiting, Utility},
+    Battle, BattleEvent, BattleOutcome, Controller, DamageKind, Side,
+};
+use components::{EquipSlot, ItemRarity, MetaData, Weapon, WeaponType};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt, ops::Range};
+
+/// The key of damage dealt by basic attacks without a weapon.
+pub const UNARMED: &str = "Unarmed";
+
+/// The weapon equipped in the allies' main hands.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Gear {
+    /// The allies fight with the weapons they spawned with.
+    Default,
+    Weapon(WeaponType, Option<ItemRarity>),
+}
+
+impl Gear {
+    /// Parse a gear set, i.e., `default`, `Bow` or `Bow:Exotic`.
+    pub fn parse(s: &str) -> anyhow::Result<Gear> {
+        if s.eq_ignore_ascii_case("default") {
+            return Ok(Gear::Default);
+        }
+
+        let (weapon, rarity) = match s.split_once(':') {
+            Some((weapon, rarity)) => (weapon, Some(rarity)),
+            None => (s, None),
+        };
+        let weapon = *WeaponType::all()
+            .iter()
+            .find(|w| w.name().eq_ignore_ascii_case(weapon))
+            .ok_or_else(|| anyhow::anyhow!("Unknown weapon type {}", weapon))?;
+        let rarity = match rarity {
+            Some(rarity) => Some(
+                [ItemRarity::Rare, ItemRarity::Legendry, ItemRarity::Exotic]
+                    .iter()
+                    .copied()
+                    .find(|r| format!("{:?}", r).eq_ignore_ascii_case(rarity))
+                    .ok_or_else(|| anyhow::anyhow!("Unknown rarity {}", rarity))?,
+            ),
+            None => None,
+        };
+        Ok(Gear::Weapon(weapon, rarity))
+    }
+
+    /// Equip this gear set on a character rolling the weapon from the given generator.
+    ///
+    /// Fails if the character's class can't wield the weapon.
+    pub fn equip(&self, character: &mut dyn Char, rng: &mut StdRng) -> anyhow::Result<()> {
+        if let Gear::Weapon(weapon_type, rarity) = *self {
+            let mut weapon = Weapon::generate(weapon_type, rng);
+            if let Some(rarity) = rarity {
+                weapon = weapon.with_rarity(rarity);
+            }
+            character.equip(weapon, EquipSlot::MainHand)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Gear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Gear::Default => write!(f, "default"),
+            Gear::Weapon(weapon, None) => write!(f, "{}", weapon.name()),
+            Gear::Weapon(weapon, Some(rarity)) => write!(f, "{}:{:?}", weapon.name(), rarity),
+        }
+    }
+}
+
+/// The AI policy controlling both sides, See [`combat::ai`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Policy {
+    Aggressive,
+    Defensive,
+    Healer,
+    Kiting,
+    Utility,
+}
+
+impl Policy {
+    pub fn all() -> &'static [Policy] {
+        &[
+            Policy::Aggressive,
+            Policy::Defensive,
+            Policy::Healer,
+            Policy::Kiting,
+            Policy::Utility,
+        ]
+    }
+
+    /// Parse a policy by its name, i.e., `aggressive`.
+    pub fn parse(s: &str) -> anyhow::Result<Policy> {
+        Policy::all()
+            .iter()
+            .copied()
+            .find(|p| format!("{:?}", p).eq_ignore_ascii_case(s))
+            .ok_or_else(|| anyhow::anyhow!("Unknown AI policy {}", s))
+    }
+
+    pub fn controller(&self) -> Box<dyn Controller> {
+        match self {
+            Policy::Aggressive => Box::new(Aggressive),
+            Policy::Defensive => Box::new(Defensive::default()),
+            Policy::Healer => Box::new(Healer::default()),
+            Policy::Kiting => Box::new(Kiting),
+            Policy::Utility => Box::new(Utility::default()),
+        }
+    }
+}
+
+/// The damage a side dealt during a batch.
+#[derive(PartialEq, Clone, Debug, Default, Serialize)]
+pub struct Damage {
+    pub total: u64,
+    /// Damage of basic attacks by weapon type, [`UNARMED`] for attacks without a weapon.
+    pub by_weapon: BTreeMap<String, u64>,
+    /// Damage of basic attacks by weapon ammo type, [`UNARMED`] for attacks without a weapon.
+    pub by_ammo: BTreeMap<String, u64>,
+    /// Damage of abilities by ability name.
+    pub by_ability: BTreeMap<String, u64>,
+    /// Damage dealt by status effects the side applied.
+    pub over_time: u64,
+}
+
+impl Damage {
+    fn add(&mut self, kind: DamageKind, amount: u32) {
+        let amount = u64::from(amount);
+        self.total += amount;
+        let (weapon, ammo) = match kind {
+            DamageKind::Weapon(weapon, ammo) => (weapon.name(), ammo.name()),
+            DamageKind::Unarmed => (UNARMED, UNARMED),
+            DamageKind::Ability(ability) => {
+                *self.by_ability.entry(ability.to_string()).or_default() += amount;
+                return;
+            }
+        };
+        *self.by_weapon.entry(weapon.to_string()).or_default() += amount;
+        *self.by_ammo.entry(ammo.to_string()).or_default() += amount;
+    }
+}
+
+/// The characters of both sides of a battle.
+pub type Sides = (Vec<Box<dyn Char>>, Vec<Box<dyn Char>>);
+
+/// Roll the equipped weapons of a character again keeping their types.
+fn reroll(character: &mut dyn Char, rng: &mut StdRng) {
+    for slot in [EquipSlot::MainHand, EquipSlot::OffHand] {
+        if let Some(weapon) = character.equipment_mut().get_mut(slot) {
+            let mut rolled = Weapon::generate(weapon.weapon_type(), rng);
+            if let Some(durability) = weapon.durability() {
+                rolled = rolled.with_durability(durability.max());
+            }
+            *weapon = rolled;
+        }
+    }
+}
+
+/// A batch of battles between the same sides across a range of seeds.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Batch {
+    /// The registry names of the allies.
+    pub allies: Vec<String>,
+    /// The registry names of the enemies.
+    pub enemies: Vec<String>,
+    pub gear: Gear,
+    pub policy: Policy,
+    pub seeds: Range<u64>,
+    /// The amount of rounds before a battle ends in a draw, The battle's default if `None`.
+    pub max_rounds: Option<u32>,
+}
+
+/// The statistics of a batch.
+#[derive(PartialEq, Clone, Debug, Serialize)]
+pub struct BatchReport {
+    /// The allies joined by `+`.
+    pub allies: String,
+    /// The enemies joined by `+`.
+    pub enemies: String,
+    pub gear: String,
+    pub policy: String,
+    pub fights: u32,
+    pub victories: u32,
+    pub defeats: u32,
+    pub fled: u32,
+    pub draws: u32,
+    /// The ratio of battles won by the allies.
+    pub win_rate: f64,
+    /// The mean amount of rounds the allies took to win, `None` if they never won.
+    pub mean_time_to_kill: Option<f64>,
+    pub mean_turns: f64,
+    pub allies_damage: Damage,
+    pub enemies_damage: Damage,
+    /// The amount of won battles by the rounds they took.
+    pub time_to_kill: BTreeMap<u32, u32>,
+    /// The amount of battles by the rounds they lasted.
+    pub rounds: BTreeMap<u32, u32>,
+}
+
+impl Batch {
+    /// Spawn both sides for the battle of the given seed.
+    ///
+    /// The equipped weapons are rolled again from the seed, So every battle of a batch is reproducible.
+    /// Fails if a name isn't registered or the allies can't equip the gear.
+    pub fn spawn(&self, registry: &CharacterRegistry, seed: u64) -> anyhow::Result<Sides> {
+        let spawn = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| {
+                    registry
+                        .spawn(name)
+                        .ok_or_else(|| anyhow::anyhow!("Unknown character {}", name))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut allies = spawn(&self.allies)?;
+        let mut enemies = spawn(&self.enemies)?;
+        for character in allies.iter_mut().chain(enemies.iter_mut()) {
+            reroll(character.as_mut(), &mut rng);
+        }
+        for ally in allies.iter_mut() {
+            self.gear.equip(ally.as_mut(), &mut rng)?;
+        }
+        Ok((allies, enemies))
+    }
+
+    /// Run a battle for every seed and collect the statistics.
+    pub fn run(&self, registry: &CharacterRegistry) -> anyhow::Result<BatchReport> {
+        let mut report = BatchReport {
+            allies: self.allies.join("+"),
+            enemies: self.enemies.join("+"),
+            gear: self.gear.to_string(),
+            policy: format!("{:?}", self.policy),
+            fights: 0,
+            victories: 0,
+            defeats: 0,
+            fled: 0,
+            draws: 0,
+            win_rate: 0.0,
+            mean_time_to_kill: None,
+            mean_turns: 0.0,
+            allies_damage: Damage::default(),
+            enemies_damage: Damage::default(),
+            time_to_kill: BTreeMap::new(),
+            rounds: BTreeMap::new(),
+        };
+        let mut turns = 0u64;
+
+        for seed in self.seeds.clone() {
+            let (allies, enemies) = self.spawn(registry, seed)?;
+            let mut battle = Battle::new(allies, enemies, seed);
+            if let Some(rounds) = self.max_rounds {
+                battle.set_max_rounds(rounds);
+            }
+            let mut allies = self.policy.controller();
+            let mut enemies = self.policy.controller();
+
+            let outcome = loop {
+                if let Some(outcome) = battle.outcome() {
+                    break outcome;
+                }
+                let events = battle.next_turn(allies.as_mut(), enemies.as_mut())?;
+                report.record(&events);
+                turns += 1;
+            };
+
+            // The round counter passes the limit when a battle is drawn.
+            let rounds = battle.round().min(self.max_rounds.unwrap_or(u32::MAX));
+            report.fights += 1;
+            *report.rounds.entry(rounds).or_default() += 1;
+            match outcome {
+                BattleOutcome::Victory => {
+                    report.victories += 1;
+                    *report.time_to_kill.entry(rounds).or_default() += 1;
+                }
+                BattleOutcome::Defeat => report.defeats += 1,
+                BattleOutcome::Fled => report.fled += 1,
+                BattleOutcome::Draw => report.draws += 1,
+            }
+        }
+
+        if report.fights > 0 {
+            report.win_rate = f64::from(report.victories) / f64::from(report.fights);
+            report.mean_turns = turns as f64 / f64::from(report.fights);
+        }
+        if report.victories > 0 {
+            let rounds: u32 = report.time_to_kill.iter().map(|(r, n)| r * n).sum();
+            report.mean_time_to_kill = Some(f64::from(rounds) / f64::from(report.victories));
+        }
+        Ok(report)
+    }
+}
+
+impl BatchReport {
+    fn record(&mut self, events: &[BattleEvent]) {
+        for event in events {
+            match *event {
+                BattleEvent::Damage {
+                    source,
+                    amount,
+                    kind,
+                    ..
+                } => self.damage_mut(source.side).add(kind, amount),
+                BattleEvent::StatusDamage { target, amount, .. } => {
+                    let damage = self.damage_mut(target.side.opposite());
+                    damage.total += u64::from(amount);
+                    damage.over_time += u64::from(amount);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn damage_mut(&mut self, side: Side) -> &mut Damage {
+        match side {
+            Side::Allies => &mut self.allies_damage,
+            Side::Enemies => &mut self.enemies_damage,
+        }
+    }
+
+    /// The statistics as rows of `(metric, key, value)`.
+    ///
+    /// Keys are empty for metrics without one.
+    pub fn rows(&self) -> Vec<(&'static str, String, String)> {
+        let mut rows = vec![
+            ("fights", String::new(), self.fights.to_string()),
+            ("victories", String::new(), self.victories.to_string()),
+            ("defeats", String::new(), self.defeats.to_string()),
+            ("fled", String::new(), self.fled.to_string()),
+            ("draws", String::new(), self.draws.to_string()),
+            ("win_rate", String::new(), format!("{:.4}", self.win_rate)),
+            (
+                "mean_time_to_kill",
+                String::new(),
+                self.mean_time_to_kill
+                    .map_or_else(String::new, |ttk| format!("{:.2}", ttk)),
+            ),
+            (
+                "mean_turns",
+                String::new(),
+                format!("{:.2}", self.mean_turns),
+            ),
+        ];
+        for (side, damage) in [
+            ("allies", &self.allies_damage),
+            ("enemies", &self.enemies_damage),
+        ] {
+            rows.push(("damage_total", side.to_string(), damage.total.to_string()));
+            rows.push((
+                "damage_over_time",
+                side.to_string(),
+                damage.over_time.to_string(),
+            ));
+            for (weapon, amount) in damage.by_weapon.iter() {
+                rows.push((
+                    "damage_by_weapon",
+                    format!("{}/{}", side, weapon),
+                    amount.to_string(),
+                ));
+            }
+            for (ammo, amount) in damage.by_ammo.iter() {
+                rows.push((
+                    "damage_by_ammo",
+                    format!("{}/{}", side, ammo),
+                    amount.to_string(),
+                ));
+            }
+            for (ability, amount) in damage.by_ability.iter() {
+                rows.push((
+                    "damage_by_ability",
+                    format!("{}/{}", side, ability),
+                    amount.to_string(),
+                ));
+            }
+        }
+        for (rounds, fights) in self.time_to_kill.iter() {
+            rows.push(("time_to_kill", rounds.to_string(), fights.to_string()));
+        }
+        for (rounds, fights) in self.rounds.iter() {
+            rows.push(("rounds", rounds.to_string(), fights.to_string()));
+        }
+        rows
+    }
+}
+
+/// Write reports as JSON.
+pub fn to_json(reports: &[BatchReport]) -> anyhow::Result<String> {
+    Ok(serde_json::to_string_pretty(reports)?)
+}
+
+/// Write reports as CSV, One row per statistic.
+///
+/// The columns are `allies,enemies,gear,policy,metric,key,value`.
+pub fn to_csv(reports: &[BatchReport]) -> String {
+    let mut csv = String::from("allies,enemies,gear,policy,metric,key,value\n");
+    for report in reports {
+        for (metric, key, value) in report.rows() {
+            let fields = [
+                report.allies.as_str(),
+                report.enemies.as_str(),
+                report.gear.as_str(),
+                report.policy.as_str(),
+                metric,
+                key.as_str(),
+                value.as_str(),
+            ];
+            let fields: Vec<_> = fields.iter().map(|f| escape(f)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+    }
+    csv
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(allies: &[&str], enemies: &[&str], gear: Gear) -> Batch {
+        Batch {
+            allies: allies.iter().map(|s| s.to_string()).collect(),
+            enemies: enemies.iter().map(|s| s.to_string()).collect(),
+            gear,
+            policy: Policy::Aggressive,
+            seeds: 0..20,
+            max_rounds: None,
+        }
+    }
+
+    #[test]
+    fn test_batch() {
+        let registry = CharacterRegistry::default();
+        let report = batch(&["Tyr", "Vamp"], &["ghoul"], Gear::Default)
+            .run(&registry)
+            .unwrap();
+        assert_eq!(report.fights, 20);
+        assert_eq!(
+            report.victories + report.defeats + report.fled + report.draws,
+            20
+        );
+        assert_eq!(report.rounds.values().sum::<u32>(), 20);
+        assert_eq!(report.time_to_kill.values().sum::<u32>(), report.victories);
+        assert!(report.allies_damage.total > 0);
+        for damage in [&report.allies_damage, &report.enemies_damage] {
+            let attacks = damage.by_weapon.values().sum::<u64>();
+            let abilities = damage.by_ability.values().sum::<u64>();
+            assert_eq!(damage.by_ammo.values().sum::<u64>(), attacks);
+            assert_eq!(attacks + abilities + damage.over_time, damage.total);
+        }
+        // Abilities are reported on their own, Not as the attacker's weapon.
+        assert!(!report.allies_damage.by_ability.is_empty());
+        for damage in [&report.allies_damage, &report.enemies_damage] {
+            assert!(damage
+                .by_weapon
+                .keys()
+                .all(|weapon| weapon == UNARMED
+                    || WeaponType::all().iter().any(|w| w.name() == weapon)));
+        }
+
+        // Seeded, So the same batch has the same statistics.
+        let again = batch(&["Tyr", "Vamp"], &["ghoul"], Gear::Default)
+            .run(&registry)
+            .unwrap();
+        assert_eq!(report, again);
+    }
+
+    #[test]
+    fn test_gear() {
+        assert_eq!(Gear::parse("DEFAULT").unwrap(), Gear::Default);
+        assert_eq!(
+            Gear::parse("bow:exotic").unwrap(),
+            Gear::Weapon(WeaponType::Bow, Some(ItemRarity::Exotic))
+        );
+        assert!(Gear::parse("Sword").is_err());
+        assert!(Gear::parse("Bow:Common").is_err());
+        assert_eq!(Gear::parse("Mace:Rare").unwrap().to_string(), "Mace:Rare");
+
+        let registry = CharacterRegistry::default();
+        let gear = WeaponType::all()
+            .iter()
+            .map(|w| Gear::Weapon(*w, None))
+            .find(|gear| {
+                batch(&["Tyr"], &["Kain"], *gear)
+                    .spawn(&registry, 0)
+                    .is_ok()
+            })
+            .unwrap();
+        let report = batch(&["Tyr"], &["Kain"], gear).run(&registry).unwrap();
+        let Gear::Weapon(weapon, _) = gear else {
+            unreachable!()
+        };
+        assert_eq!(
+            report.allies_damage.by_weapon.keys().collect::<Vec<_>>(),
+            vec![weapon.name()]
+        );
+        assert!(batch(&["Tyr"], &["nobody"], Gear::Default)
+            .run(&registry)
+            .is_err());
+    }
+
+    #[test]
+    fn test_output() {
+        let registry = CharacterRegistry::default();
+        let mut draw = batch(&["Tyr"], &["Tyr"], Gear::Default);
+        draw.max_rounds = Some(1);
+        let report = draw.run(&registry).unwrap();
+        assert_eq!(report.draws, 20);
+        assert_eq!(report.rounds.keys().collect::<Vec<_>>(), vec![&1]);
+        assert_eq!(report.mean_time_to_kill, None);
+
+        let csv = to_csv(std::slice::from_ref(&report));
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("allies,enemies,gear,policy,metric,key,value")
+        );
+        assert_eq!(lines.next(), Some("Tyr,Tyr,default,Aggressive,fights,,20"));
+        assert!(lines.any(|l| l == "Tyr,Tyr,default,Aggressive,rounds,1,20"));
+        assert_eq!(escape("a,\"b\""), "\"a,\"\"b\"\"\"");
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&[report]).unwrap()).unwrap();
+        assert_eq!(json[0]["draws"], 20);
+        assert_eq!(json[0]["mean_time_to_kill"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,591 @@
+//! Achievements unlocked by gameplay events.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::character::{Char, MetaData};
+use crate::events::is_player;
+use crate::quests::Reward;
+use components::{
+    EventBus, GameEvent, Generator, Item, ItemRarity, Loot, WeaponAmmoType, WeaponType,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt};
+
+/// ## What unlocks an achievement.
+/// - [`Criteria::DamageDealt`]
+///     - Deal an amount of damage in total, With weapons of the ammo if given.
+/// - [`Criteria::Kills`]
+///     - Defeat an amount of characters, Of a kind if given.
+/// - [`Criteria::Revives`]
+///     - Be revived an amount of times.
+/// - [`Criteria::WeaponNames`]
+///     - Pick up a weapon of the rarity with every name of [`WeaponType::generate_name`].
+/// - [`Criteria::ReachLevel`]
+///     - Reach a level.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Criteria {
+    DamageDealt {
+        ammo: Option<WeaponAmmoType>,
+        amount: u64,
+    },
+    Kills {
+        target: Option<&'static str>,
+        count: u64,
+    },
+    Revives(u64),
+    WeaponNames(ItemRarity),
+    ReachLevel(u32),
+}
+
+impl Criteria {
+    /// The progress needed to unlock the achievement.
+    pub fn required(&self) -> u64 {
+        match self {
+            Criteria::DamageDealt { amount, .. } => *amount,
+            Criteria::Kills { count, .. } => *count,
+            Criteria::Revives(count) => *count,
+            Criteria::WeaponNames(_) => weapon_names().len() as u64,
+            Criteria::ReachLevel(level) => u64::from(*level),
+        }
+    }
+}
+
+/// Every weapon name of every weapon type.
+fn weapon_names() -> Vec<&'static str> {
+    let mut names: Vec<_> = WeaponType::all()
+        .iter()
+        .flat_map(|w| w.generate_name())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// An achievement, Its reward is claimed once unlocked.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Achievement {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    criteria: Criteria,
+    reward: Reward,
+}
+
+impl fmt::Display for Achievement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Achievement(name: {}, description: {})",
+            self.name, self.description
+        )
+    }
+}
+
+impl MetaData for Achievement {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+}
+
+impl Achievement {
+    pub const fn new(
+        id: &'static str,
+        name: &'static str,
+        description: &'static str,
+        criteria: Criteria,
+        reward: Reward,
+    ) -> Achievement {
+        Achievement {
+            id,
+            name,
+            description,
+            criteria,
+            reward,
+        }
+    }
+
+    /// Returns the achievement with the given id if any.
+    pub fn get(id: &str) -> Option<&'static Achievement> {
+        ACHIEVEMENTS.iter().find(|a| a.id == id)
+    }
+
+    /// Every achievement.
+    pub fn all() -> &'static [Achievement] {
+        &ACHIEVEMENTS
+    }
+
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn criteria(&self) -> Criteria {
+        self.criteria
+    }
+
+    pub fn reward(&self) -> Reward {
+        self.reward
+    }
+}
+
+static ACHIEVEMENTS: [Achievement; 6] = [
+    Achievement::new(
+        "first_blood",
+        "First Blood",
+        "Defeat an enemy.",
+        Criteria::Kills {
+            target: None,
+            count: 1,
+        },
+        Reward::new(50, 10),
+    ),
+    Achievement::new(
+        "slayer",
+        "Slayer",
+        "Defeat 100 enemies.",
+        Criteria::Kills {
+            target: None,
+            count: 100,
+        },
+        Reward::new(1000, 250).with_loot("demon"),
+    ),
+    Achievement::new(
+        "void_walker",
+        "Void Walker",
+        "Deal 10000 damage with void weapons.",
+        Criteria::DamageDealt {
+            ammo: Some(WeaponAmmoType::Void),
+            amount: 10_000,
+        },
+        Reward::new(1500, 500).with_loot("elemental"),
+    ),
+    Achievement::new(
+        "curator",
+        "Curator",
+        "Collect an exotic weapon of every name.",
+        Criteria::WeaponNames(ItemRarity::Exotic),
+        Reward::new(5000, 2000),
+    ),
+    Achievement::new(
+        "undying",
+        "Undying",
+        "Be revived 50 times.",
+        Criteria::Revives(50),
+        Reward::new(500, 100),
+    ),
+    Achievement::new(
+        "veteran",
+        "Veteran",
+        "Reach level 10.",
+        Criteria::ReachLevel(10),
+        Reward::new(0, 1000),
+    ),
+];
+
+/// The progress towards an achievement.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct AchievementProgress {
+    achievement: &'static Achievement,
+    count: u64,
+    names: Vec<&'static str>,
+    unlocked: bool,
+    claimed: bool,
+}
+
+impl AchievementProgress {
+    fn new(achievement: &'static Achievement) -> AchievementProgress {
+        AchievementProgress {
+            achievement,
+            count: 0,
+            names: Vec::new(),
+            unlocked: false,
+            claimed: false,
+        }
+    }
+
+    pub fn achievement(&self) -> &'static Achievement {
+        self.achievement
+    }
+
+    /// The progress so far, Capped at [`Criteria::required`].
+    pub fn count(&self) -> u64 {
+        self.count.min(self.achievement.criteria.required())
+    }
+
+    /// The weapon names collected for [`Criteria::WeaponNames`].
+    pub fn names(&self) -> &[&'static str] {
+        &self.names
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked
+    }
+
+    /// Whether the reward was claimed.
+    pub fn is_claimed(&self) -> bool {
+        self.claimed
+    }
+
+    /// Advance the progress by the event of the given players.
+    fn advance(&mut self, event: &GameEvent, players: &[String]) {
+        let player = |name: &str| is_player(players, name);
+        match (self.achievement.criteria, event) {
+            (
+                Criteria::DamageDealt { ammo, .. },
+                GameEvent::DamageDealt {
+                    source: Some(source),
+                    amount,
+                    ammo: used,
+                    ..
+                },
+            ) if player(source) && ammo.is_none_or(|ammo| *used == Some(ammo)) => {
+                self.count = self.count.saturating_add(u64::from(*amount));
+            }
+            (Criteria::Kills { target, .. }, GameEvent::Died { target: died })
+                if (players.is_empty() || !player(died)) && target.is_none_or(|t| t == *died) =>
+            {
+                self.count += 1;
+            }
+            (Criteria::Revives(_), GameEvent::Revived { target }) if player(target) => {
+                self.count += 1;
+            }
+            (
+                Criteria::WeaponNames(rarity),
+                GameEvent::ItemPickedUp {
+                    owner,
+                    item: Loot::Weapon(weapon),
+                },
+            ) if player(owner) && weapon.rarity() == rarity => {
+                let name = weapon.name();
+                if weapon_names().contains(&name) && !self.names.contains(&name) {
+                    self.names.push(name);
+                    self.count = self.names.len() as u64;
+                }
+            }
+            (Criteria::ReachLevel(_), GameEvent::LevelUp { target, level }) if player(target) => {
+                self.count = self.count.max(u64::from(*level));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The saved progress towards an achievement.
+#[derive(Serialize, Deserialize)]
+struct ProgressRecord {
+    id: String,
+    count: u64,
+    #[serde(default)]
+    names: Vec<String>,
+    unlocked: bool,
+    claimed: bool,
+}
+
+/// The saved state of achievements.
+#[derive(Serialize, Deserialize)]
+struct AchievementsRecord {
+    players: Vec<String>,
+    progress: Vec<ProgressRecord>,
+}
+
+/// The progress towards every achievement.
+///
+/// Progress is fed by gameplay events of the tracked players, Every character is a player if none are tracked.
+/// Kills count the deaths of characters that aren't players, Or every death if none are tracked.
+///
+/// ```
+/// use characters::Achievements;
+/// use components::GameEvent;
+///
+/// let mut achievements = Achievements::new();
+/// achievements.track("Vamp");
+///
+/// assert!(achievements.handle(&GameEvent::Died { target: "Vamp" }).is_empty());
+/// assert_eq!(achievements.handle(&GameEvent::Died { target: "ghoul" }), vec!["first_blood"]);
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "AchievementsRecord", into = "AchievementsRecord")]
+pub struct Achievements {
+    players: Vec<String>,
+    progress: Vec<AchievementProgress>,
+}
+
+impl Default for Achievements {
+    fn default() -> Self {
+        Achievements {
+            players: Vec::new(),
+            progress: Achievement::all()
+                .iter()
+                .map(AchievementProgress::new)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<AchievementsRecord> for Achievements {
+    type Error = anyhow::Error;
+
+    /// Achievements added since the progress was saved start with no progress.
+    fn try_from(record: AchievementsRecord) -> anyhow::Result<Achievements> {
+        let mut achievements = Achievements::new();
+        achievements.players = record.players;
+        let names = weapon_names();
+        for saved in record.progress {
+            let progress = achievements
+                .progress
+                .iter_mut()
+                .find(|p| p.achievement.id == saved.id)
+                .ok_or_else(|| anyhow::anyhow!("Unknown achievement {}", saved.id))?;
+            progress.count = saved.count;
+            progress.unlocked = saved.unlocked;
+            progress.claimed = saved.claimed;
+            for name in saved.names {
+                let name = names
+                    .iter()
+                    .find(|n| **n == name)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown weapon name {}", name))?;
+                progress.names.push(name);
+            }
+        }
+        Ok(achievements)
+    }
+}
+
+impl From<Achievements> for AchievementsRecord {
+    fn from(achievements: Achievements) -> AchievementsRecord {
+        AchievementsRecord {
+            players: achievements.players.clone(),
+            progress: achievements
+                .progress
+                .iter()
+                .map(|p| ProgressRecord {
+                    id: p.achievement.id.to_string(),
+                    count: p.count,
+                    names: p.names.iter().map(|n| n.to_string()).collect(),
+                    unlocked: p.unlocked,
+                    claimed: p.claimed,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Achievements {
+    /// Creates new achievements with no progress.
+    pub fn new() -> Achievements {
+        Self::default()
+    }
+
+    /// Track the events of the character with the given event name as a player's, See [`Char::event_name`].
+    pub fn track(&mut self, player: &str) {
+        if !self.players.iter().any(|p| p == player) {
+            self.players.push(player.to_string());
+        }
+    }
+
+    /// The event names of the tracked players.
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    /// The progress towards every achievement in the order of [`Achievement::all`].
+    pub fn progress(&self) -> &[AchievementProgress] {
+        &self.progress
+    }
+
+    /// Returns the progress towards the achievement with the given id if any.
+    pub fn get(&self, id: &str) -> Option<&AchievementProgress> {
+        self.progress.iter().find(|p| p.achievement.id == id)
+    }
+
+    /// The unlocked achievements.
+    pub fn unlocked(&self) -> impl Iterator<Item = &'static Achievement> + '_ {
+        self.progress
+            .iter()
+            .filter(|p| p.unlocked)
+            .map(|p| p.achievement)
+    }
+
+    /// Advance the progress towards the locked achievements by the event.
+    ///
+    /// ## Returns
+    /// The ids of the achievements the event unlocked.
+    pub fn handle(&mut self, event: &GameEvent) -> Vec<&'static str> {
+        let players = &self.players;
+        let mut unlocked = Vec::new();
+        for progress in self.progress.iter_mut().filter(|p| !p.unlocked) {
+            progress.advance(event, players);
+            if progress.count >= progress.achievement.criteria.required() {
+                progress.unlocked = true;
+                unlocked.push(progress.achievement.id);
+            }
+        }
+        unlocked
+    }
+
+    /// Claim the reward of an unlocked achievement, See [`Reward::grant`].
+    ///
+    /// ## Returns
+    /// The items rolled from the reward's loot table.
+    pub fn claim<R: Rng + ?Sized>(
+        &mut self,
+        id: &str,
+        character: &mut dyn Char,
+        events: &mut EventBus,
+        rng: &mut R,
+    ) -> anyhow::Result<Vec<Loot>> {
+        let progress = self
+            .progress
+            .iter_mut()
+            .find(|p| p.achievement.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown achievement {}", id))?;
+        if !progress.unlocked {
+            return Err(anyhow::anyhow!(
+                "Achievement {} isn't unlocked",
+                progress.achievement.name
+            ));
+        }
+        if progress.claimed {
+            return Err(anyhow::anyhow!(
+                "Achievement {} was already claimed",
+                progress.achievement.name
+            ));
+        }
+        let loot = progress.achievement.reward.grant(character, events, rng)?;
+        progress.claimed = true;
+        Ok(loot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CharFactory, Kain};
+    use components::Weapon;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn damage(source: &'static str, amount: u32, ammo: WeaponAmmoType) -> GameEvent {
+        GameEvent::DamageDealt {
+            source: Some(source),
+            target: "ghoul",
+            amount,
+            ammo: Some(ammo),
+        }
+    }
+
+    #[test]
+    fn test_counters() {
+        let mut achievements = Achievements::new();
+        achievements.track("Kain");
+
+        for _ in 0..9 {
+            achievements.handle(&damage("Kain", 1000, WeaponAmmoType::Void));
+        }
+        achievements.handle(&damage("ghoul", 5000, WeaponAmmoType::Void));
+        assert_eq!(achievements.get("void_walker").unwrap().count(), 9000);
+        assert_eq!(
+            achievements.handle(&damage("Kain", 1000, WeaponAmmoType::Void)),
+            vec!["void_walker"]
+        );
+
+        for _ in 0..49 {
+            achievements.handle(&GameEvent::Revived { target: "Kain" });
+            achievements.handle(&GameEvent::Revived { target: "ghoul" });
+        }
+        assert!(!achievements.get("undying").unwrap().is_unlocked());
+        achievements.handle(&GameEvent::Revived { target: "Kain" });
+        assert!(achievements.get("undying").unwrap().is_unlocked());
+        assert_eq!(achievements.unlocked().count(), 2);
+    }
+
+    #[test]
+    fn test_weapon_names() {
+        let mut achievements = Achievements::new();
+        let mut pick_up = |weapon: Weapon| {
+            achievements.handle(&GameEvent::ItemPickedUp {
+                owner: "Tyr",
+                item: Loot::Weapon(weapon),
+            })
+        };
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut unlocked = Vec::new();
+        while unlocked.is_empty() {
+            let weapon_type = WeaponType::all()[rng.gen_range(0..WeaponType::all().len())];
+            let weapon = Weapon::generate(weapon_type, &mut rng);
+            unlocked = pick_up(weapon.with_rarity(ItemRarity::Rare));
+            assert!(unlocked.is_empty());
+            unlocked = pick_up(weapon.with_rarity(ItemRarity::Exotic));
+        }
+        assert_eq!(unlocked, vec!["curator"]);
+        assert_eq!(
+            achievements.get("curator").unwrap().names().len(),
+            weapon_names().len()
+        );
+    }
+
+    #[test]
+    fn test_claim_and_save() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut kain = Kain::new();
+        let mut events = EventBus::new();
+        let mut achievements = Achievements::new();
+        assert!(achievements
+            .claim("first_blood", &mut kain, &mut events, &mut rng)
+            .is_err());
+
+        achievements.handle(&GameEvent::Died { target: "imp" });
+        achievements
+            .claim("first_blood", &mut kain, &mut events, &mut rng)
+            .unwrap();
+        assert_eq!(kain.wallet().gold(), 10);
+        assert!(achievements
+            .claim("first_blood", &mut kain, &mut events, &mut rng)
+            .is_err());
+
+        achievements.track("Kain");
+        achievements.handle(&damage("Kain", 30, WeaponAmmoType::Void));
+        achievements.handle(&GameEvent::ItemPickedUp {
+            owner: "Kain",
+            item: Loot::Weapon(
+                Weapon::generate(WeaponType::Rod, &mut rng).with_rarity(ItemRarity::Exotic),
+            ),
+        });
+        let json = serde_json::to_string(&achievements).unwrap();
+        let loaded: Achievements = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, achievements);
+        assert!(loaded.get("first_blood").unwrap().is_claimed());
+        assert_eq!(loaded.get("curator").unwrap().names().len(), 1);
+    }
+}
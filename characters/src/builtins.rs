@@ -29,18 +29,26 @@
 // OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use components::{Health, Inventory};
+use components::{EquipSlot, Equipment, Health, Inventory, Mana, Wallet, Weapon};
 
-pub(super) use crate::character::{BuiltinCharacter, Char, CharacterClass, MetaData};
-use crate::stats::Stats;
+pub(super) use crate::character::{BuiltinCharacter, Char, CharFactory, CharacterClass, MetaData};
+use crate::level::Level;
+use crate::stats::{Resistense, Stats};
+use crate::talents::Talents;
 
 macro impl_builtin_character($name:ident, $builtin:expr, $class:expr) {
     /// Builtin [$name] character.
     #[derive(Clone, Debug, PartialEq)]
     pub struct $name {
         health: Health,
+        mana: Mana,
         inventory: Inventory,
+        equipment: Equipment,
         stats: Stats,
+        resistances: Resistense,
+        level: Level,
+        wallet: Wallet,
+        talents: Talents,
         class: CharacterClass,
     }
 
@@ -56,34 +64,43 @@ macro impl_builtin_character($name:ident, $builtin:expr, $class:expr) {
         }
     }
 
-    impl Into<super::character::BuiltinCharacter> for $name {
-        fn into(self) -> super::character::BuiltinCharacter {
+    impl From<$name> for super::character::BuiltinCharacter {
+        fn from(_: $name) -> super::character::BuiltinCharacter {
             $builtin
         }
     }
 
-    impl super::Char for $name {
+    impl CharFactory for $name {
         fn new() -> Self {
-            let mut inventory = Inventory::default();
-            // Put default starter weapon in inventory.
-            inventory
-                .put_weapon(crate::components::Weapon::default())
-                .unwrap();
-
             // TODO: Each character should have different stats.
             // i,e., Vampire should have more movement speed and Assasin should have more attack speed.
-            Self::build(&&inventory, &Stats::default(), &Health::default())
+            let mut character =
+                Self::build(&Inventory::default(), &Stats::default(), &Health::default());
+
+            // Equip the class's starter weapon.
+            character
+                .equip(Weapon::new($class.starter_weapon()), EquipSlot::MainHand)
+                .unwrap();
+            character
         }
 
         fn build(inventory: &Inventory, stats: &Stats, health: &Health) -> Self {
             Self {
                 class: $class,
                 health: health.clone(),
+                mana: Mana::with_resource($class.resource(), None),
                 inventory: inventory.clone(),
+                equipment: Equipment::default(),
                 stats: stats.clone(),
+                resistances: Resistense::default(),
+                level: Level::default(),
+                wallet: Wallet::default(),
+                talents: Talents::new($class),
             }
         }
+    }
 
+    impl super::Char for $name {
         fn class(&self) -> &CharacterClass {
             &self.class
         }
@@ -92,17 +109,73 @@ macro impl_builtin_character($name:ident, $builtin:expr, $class:expr) {
             &self.stats
         }
 
+        fn resistances(&self) -> &Resistense {
+            &self.resistances
+        }
+
+        fn level(&self) -> &Level {
+            &self.level
+        }
+
+        fn level_mut(&mut self) -> &mut Level {
+            &mut self.level
+        }
+
+        fn wallet(&self) -> &Wallet {
+            &self.wallet
+        }
+
+        fn wallet_mut(&mut self) -> &mut Wallet {
+            &mut self.wallet
+        }
+
+        fn talents(&self) -> Option<&Talents> {
+            Some(&self.talents)
+        }
+
+        fn talents_mut(&mut self) -> Option<&mut Talents> {
+            Some(&mut self.talents)
+        }
+
         fn is_builtin(&self) -> bool {
             true
         }
 
+        fn kind(&self) -> Option<&'static str> {
+            Some($builtin.name())
+        }
+
         fn inventory(&self) -> &Inventory {
             &self.inventory
         }
 
+        fn inventory_mut(&mut self) -> &mut Inventory {
+            &mut self.inventory
+        }
+
+        fn equipment(&self) -> &Equipment {
+            &self.equipment
+        }
+
+        fn equipment_mut(&mut self) -> &mut Equipment {
+            &mut self.equipment
+        }
+
         fn health(&self) -> &Health {
             &self.health
         }
+
+        fn health_mut(&mut self) -> &mut Health {
+            &mut self.health
+        }
+
+        fn mana(&self) -> &Mana {
+            &self.mana
+        }
+
+        fn mana_mut(&mut self) -> &mut Mana {
+            &mut self.mana
+        }
     }
 }
 
@@ -115,6 +188,7 @@ impl_builtin_character!(Tyr, BuiltinCharacter::Tyr, CharacterClass::Warrior);
 mod tests {
 
     use super::*;
+    use components::WeaponType;
 
     fn print(data: &impl std::fmt::Display) {
         println!("{}", data);
@@ -127,7 +201,7 @@ mod tests {
         assert!(!vamp.inventory().is_full());
 
         for item in vamp.inventory().get_weapons() {
-            print(&item);
+            print(item.item());
         }
 
         assert_eq!(vamp.stats().movement_speed, 0);
@@ -147,4 +221,97 @@ mod tests {
         let into_builtin: BuiltinCharacter = kain.into();
         print(&into_builtin.description());
     }
+
+    #[test]
+    fn test_starter_weapon() {
+        let tyr = Tyr::new();
+        let weapon = tyr.weapon().unwrap();
+        assert_eq!(
+            weapon.weapon_type(),
+            CharacterClass::Warrior.starter_weapon()
+        );
+        assert_eq!(tyr.attack_damage(), weapon.damage());
+    }
+
+    #[test]
+    fn test_equip_restrictions() {
+        let mut kain = Kain::new();
+        assert!(kain
+            .equip(Weapon::new(WeaponType::Mace), EquipSlot::MainHand)
+            .is_err());
+
+        // The starter rod goes back to the inventory.
+        kain.equip(Weapon::new(WeaponType::Daggers), EquipSlot::MainHand)
+            .unwrap();
+        assert_eq!(kain.inventory().get_weapons().len(), 1);
+
+        kain.equip_from_inventory(0, EquipSlot::MainHand).unwrap();
+        assert_eq!(kain.weapon().unwrap().weapon_type(), WeaponType::Rod);
+        assert_eq!(
+            kain.inventory().weapon(0).unwrap().weapon_type(),
+            WeaponType::Daggers
+        );
+    }
+
+    #[test]
+    fn test_class_resources() {
+        use components::ResourceType;
+
+        let tyr = Tyr::new();
+        assert_eq!(tyr.mana().resource(), ResourceType::Rage);
+        assert_eq!(tyr.mana().current(), 0);
+        assert_eq!(Susanoo::new().mana().resource(), ResourceType::Energy);
+
+        for class in [
+            CharacterClass::Warrior,
+            CharacterClass::Warlock,
+            CharacterClass::Vampire,
+            CharacterClass::Assassin,
+        ] {
+            assert!(!class.description().is_empty());
+            assert!(class.can_wield(class.starter_weapon()));
+        }
+    }
+
+    #[test]
+    fn test_talents() {
+        let mut tyr = Tyr::new();
+        let damage = tyr.attack_damage();
+        assert!(tyr.learn_talent("iron_skin").is_err());
+
+        tyr.level_mut().gain(2800);
+        tyr.learn_talent("iron_skin").unwrap();
+        tyr.learn_talent("heavy_blows").unwrap();
+        tyr.learn_talent("battle_trance").unwrap();
+        tyr.learn_talent("whirlwind").unwrap();
+        assert_eq!(tyr.effective_stats().health, tyr.stats().health + 20);
+        assert_eq!(tyr.health().max(), 120);
+        assert_eq!(tyr.attack_damage(), damage * 115 / 100);
+        assert_eq!(tyr.abilities().last().unwrap().name(), "Whirlwind");
+
+        assert_eq!(tyr.respec(), 7);
+        assert_eq!(tyr.health().max(), 100);
+        assert_eq!(tyr.attack_damage(), damage);
+        assert_eq!(tyr.abilities(), CharacterClass::Warrior.abilities());
+    }
+
+    #[test]
+    fn test_encumbrance() {
+        let stats = Stats {
+            movement_speed: 40,
+            ..Stats::default()
+        };
+        let mut susanoo = Susanoo::build(
+            &Inventory::new().with_max_weight(10),
+            &stats,
+            &Health::default(),
+        );
+        assert_eq!(susanoo.effective_stats().movement_speed, 40);
+
+        susanoo
+            .inventory_mut()
+            .put_weapon(Weapon::new(WeaponType::Mace))
+            .unwrap();
+        assert_eq!(susanoo.effective_stats().movement_speed, 20);
+    }
 }
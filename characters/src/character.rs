@@ -28,8 +28,23 @@
 // OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::stats::Stats;
-pub(crate) use components::{health::Health, inventory::Inventory, items::MetaData};
+use crate::level::Level;
+use crate::stats::{Resistense, Stat, Stats};
+use crate::talents::{
+    Talent, Talents, ASSASSIN_TALENTS, VAMPIRE_TALENTS, WARLOCK_TALENTS, WARRIOR_TALENTS,
+};
+use abilites::{Ability, AbilityEffect, AbilityTarget};
+use components::status::{StatusEffect, StatusKind};
+pub(crate) use components::{
+    equipment::{EquipSlot, Equipment},
+    health::Health,
+    inventory::Inventory,
+    items::MetaData,
+    loot::LootTable,
+    mana::{Mana, ResourceType},
+    wallet::Wallet,
+    weapon::{Weapon, WeaponType},
+};
 
 /// Core character classes.
 ///
@@ -41,20 +56,15 @@ pub(crate) use components::{health::Health, inventory::Inventory, items::MetaDat
 /// let assassin = CharacterClass::Assassin;
 /// println!("{}", assassin.name());
 /// ```
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
 pub enum CharacterClass {
+    #[default]
     Warrior,
     Warlock,
     Vampire,
     Assassin,
 }
 
-impl Default for CharacterClass {
-    fn default() -> Self {
-        CharacterClass::Warrior
-    }
-}
-
 impl MetaData for CharacterClass {
     /// Returns the string version of the character type name.
     ///
@@ -72,10 +82,219 @@ impl MetaData for CharacterClass {
 
     /// Small description/lore about the character's background
     fn description(&self) -> &'static str {
-        todo!()
+        match self {
+            CharacterClass::Warrior => {
+                "Hardened by countless battles, Warriors wade into the front lines and grow angrier with every blow."
+            }
+            CharacterClass::Warlock => {
+                "Scholars of forbidden arts who bargained with the void for power over flesh and flame."
+            }
+            CharacterClass::Vampire => {
+                "Cursed nobles of the night who feed on the blood of the living to mend their own wounds."
+            }
+            CharacterClass::Assassin => {
+                "Blades for hire who strike from the shadows, Never where their victim is looking."
+            }
+        }
+    }
+}
+
+/// ## Passives every character of a class has.
+/// - [`ClassPassive::Fortitude`]
+///     - Reduces the damage taken from attacks by a percentage.
+/// - [`ClassPassive::Affliction`]
+///     - Status effects from abilities last longer by some turns.
+/// - [`ClassPassive::Lifesteal`]
+///     - Heals for a percentage of the damage dealt by attacks.
+/// - [`ClassPassive::Backstab`]
+///     - Attacks from behind, i.e., on a target facing someone else,
+///       have a chance percentage to critically hit for a damage multiplier percentage.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ClassPassive {
+    Fortitude(u32),
+    Affliction(u32),
+    Lifesteal(u32),
+    Backstab { chance: u32, multiplier: u32 },
+}
+
+impl MetaData for ClassPassive {
+    fn name(&self) -> &'static str {
+        match self {
+            ClassPassive::Fortitude(_) => "Fortitude",
+            ClassPassive::Affliction(_) => "Affliction",
+            ClassPassive::Lifesteal(_) => "Lifesteal",
+            ClassPassive::Backstab { .. } => "Backstab",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ClassPassive::Fortitude(_) => "Shrug off a part of every blow.",
+            ClassPassive::Affliction(_) => "Curses linger longer.",
+            ClassPassive::Lifesteal(_) => "Heal from the blood of the enemies hit.",
+            ClassPassive::Backstab { .. } => "Strikes from behind may critically hit.",
+        }
     }
 }
 
+impl CharacterClass {
+    /// The weapon types characters of this class are able to wield.
+    ///
+    /// The first weapon type is the class's starter weapon.
+    pub fn allowed_weapons(&self) -> &'static [WeaponType] {
+        match self {
+            CharacterClass::Warrior => &[WeaponType::Mace, WeaponType::Claw, WeaponType::Bow],
+            CharacterClass::Warlock => &[WeaponType::Rod, WeaponType::Daggers],
+            CharacterClass::Vampire => &[WeaponType::Claw, WeaponType::Daggers, WeaponType::Rod],
+            CharacterClass::Assassin => &[WeaponType::Daggers, WeaponType::Bow, WeaponType::Claw],
+        }
+    }
+
+    /// Whether characters of this class can wield the given weapon type.
+    pub fn can_wield(&self, weapon_type: WeaponType) -> bool {
+        self.allowed_weapons().contains(&weapon_type)
+    }
+
+    /// The weapon type characters of this class start with.
+    pub fn starter_weapon(&self) -> WeaponType {
+        self.allowed_weapons()[0]
+    }
+
+    /// The stat characters of this class rely on the most.
+    pub fn primary_stat(&self) -> Stat {
+        match self {
+            CharacterClass::Warrior => Stat::Health,
+            CharacterClass::Warlock => Stat::Mp5,
+            CharacterClass::Vampire => Stat::Hp5,
+            CharacterClass::Assassin => Stat::AttackSpeed,
+        }
+    }
+
+    /// The resource characters of this class pay abilities with.
+    pub fn resource(&self) -> ResourceType {
+        match self {
+            CharacterClass::Warrior => ResourceType::Rage,
+            CharacterClass::Warlock => ResourceType::Mana,
+            CharacterClass::Vampire => ResourceType::Blood,
+            CharacterClass::Assassin => ResourceType::Energy,
+        }
+    }
+
+    /// The passive applied to every character of this class.
+    pub fn passive(&self) -> ClassPassive {
+        match self {
+            CharacterClass::Warrior => ClassPassive::Fortitude(10),
+            CharacterClass::Warlock => ClassPassive::Affliction(1),
+            CharacterClass::Vampire => ClassPassive::Lifesteal(25),
+            CharacterClass::Assassin => ClassPassive::Backstab {
+                chance: 40,
+                multiplier: 200,
+            },
+        }
+    }
+
+    /// The abilities characters of this class are able to use.
+    pub fn abilities(&self) -> &'static [Ability] {
+        match self {
+            CharacterClass::Warrior => &WARRIOR_ABILITIES,
+            CharacterClass::Warlock => &WARLOCK_ABILITIES,
+            CharacterClass::Vampire => &VAMPIRE_ABILITIES,
+            CharacterClass::Assassin => &ASSASSIN_ABILITIES,
+        }
+    }
+
+    /// The talent tree characters of this class learn talents from.
+    pub fn talent_tree(&self) -> &'static [Talent] {
+        match self {
+            CharacterClass::Warrior => &WARRIOR_TALENTS,
+            CharacterClass::Warlock => &WARLOCK_TALENTS,
+            CharacterClass::Vampire => &VAMPIRE_TALENTS,
+            CharacterClass::Assassin => &ASSASSIN_TALENTS,
+        }
+    }
+}
+
+const WARRIOR_ABILITIES: [Ability; 2] = [
+    Ability::new(
+        "Cleave",
+        "A heavy swing that cuts through armor.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Damage(22),
+        2,
+    )
+    .with_cost(20),
+    Ability::new(
+        "Second Wind",
+        "Catch your breath and recover health.",
+        AbilityTarget::Caster,
+        AbilityEffect::Heal(25),
+        4,
+    )
+    .with_cost(15)
+    .with_cast_time(1000),
+];
+
+const WARLOCK_ABILITIES: [Ability; 2] = [
+    Ability::new(
+        "Void Bolt",
+        "A bolt of void energy that weakens its target.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Apply(StatusEffect::new(StatusKind::Weaken, 3, 30)),
+        3,
+    )
+    .with_cost(25)
+    .with_cast_time(1500),
+    Ability::new(
+        "Hellfire",
+        "Engulf the enemy in flames.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Apply(StatusEffect::new(StatusKind::Burn, 4, 6)),
+        3,
+    )
+    .with_cost(30)
+    .with_cast_time(2000),
+];
+
+const VAMPIRE_ABILITIES: [Ability; 2] = [
+    Ability::new(
+        "Blood Drain",
+        "Sink your fangs into the enemy.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Damage(18),
+        2,
+    )
+    .with_cost(20)
+    .with_cast_time(500),
+    Ability::new(
+        "Dark Mending",
+        "Mend the wounds of an ally with stolen blood.",
+        AbilityTarget::Ally,
+        AbilityEffect::Heal(20),
+        3,
+    )
+    .with_cost(25)
+    .with_cast_time(1500),
+];
+
+const ASSASSIN_ABILITIES: [Ability; 2] = [
+    Ability::new(
+        "Backstab",
+        "Strike from the shadows.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Damage(26),
+        3,
+    )
+    .with_cost(25),
+    Ability::new(
+        "Smoke Bomb",
+        "Blind the enemy leaving them unable to act.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Apply(StatusEffect::new(StatusKind::Stun, 1, 0)),
+        4,
+    )
+    .with_cost(20),
+];
+
 /// Builtin character enum.
 #[derive(PartialEq, Eq, Debug)]
 pub(crate) enum BuiltinCharacter {
@@ -104,7 +323,7 @@ impl MetaData for BuiltinCharacter {
                 and top him off with a stylish headdress."
             }
             Self::Kain => {
-                concat!("Mother of origins, guardian of passages, generator of new life in flood waters, orgasm, birth waters, baptism.")
+                "Mother of origins, guardian of passages, generator of new life in flood waters, orgasm, birth waters, baptism."
             }
             Self::Vamp => {
                 "Lie in wait inside the walls to hunt the strays.
@@ -122,45 +341,251 @@ impl MetaData for BuiltinCharacter {
 }
 
 /// Core trait that any character must implement from.
-pub trait Char: Send + Sync {
-    /// The standard way to create a character.
-    fn new() -> Self;
-    /// Build a character giving it its components.
-    fn build(inventory: &Inventory, stats: &Stats, health: &Health) -> Self;
+///
+/// This is the runtime interface of a character, It's object safe so characters of
+/// different types can be stored together as `Box<dyn Char>`, See [`CharFactory`] for creating them.
+pub trait Char: CharClone + Send + Sync {
     /// Returns an immutable reference to the character's inventory.
     fn inventory(&self) -> &Inventory;
+    /// Returns a mutable reference to the character's inventory.
+    fn inventory_mut(&mut self) -> &mut Inventory;
+    /// Returns an immutable reference to the character's equipped weapons.
+    fn equipment(&self) -> &Equipment;
+    /// Returns a mutable reference to the character's equipped weapons.
+    ///
+    /// This bypasses class restrictions, Use [`Char::equip`] instead.
+    fn equipment_mut(&mut self) -> &mut Equipment;
     /// Returns an immutable reference to the character's health.
     fn health(&self) -> &Health;
+    /// Returns a mutable reference to the character's health.
+    fn health_mut(&mut self) -> &mut Health;
+    /// Returns an immutable reference to the character's mana.
+    fn mana(&self) -> &Mana;
+    /// Returns a mutable reference to the character's mana.
+    fn mana_mut(&mut self) -> &mut Mana;
     /// Returns an immutable reference for this character's class.
     fn class(&self) -> &CharacterClass;
     /// Returns an immutable reference to the character's stats.
     fn stats(&self) -> &Stats;
+    /// Returns an immutable reference to the character's resistances.
+    fn resistances(&self) -> &Resistense;
+    /// Returns an immutable reference to the character's level.
+    fn level(&self) -> &Level;
+    /// Returns a mutable reference to the character's level.
+    fn level_mut(&mut self) -> &mut Level;
+    /// Returns an immutable reference to the character's wallet.
+    fn wallet(&self) -> &Wallet;
+    /// Returns a mutable reference to the character's wallet.
+    fn wallet_mut(&mut self) -> &mut Wallet;
     /// Whether this character is builtin or not. Defautls to `false`.
     fn is_builtin(&self) -> bool {
         false
     }
+
+    /// A unique name of this character's type, Used to spawn it again when loading save games.
+    ///
+    /// Characters without one can't be saved. Defaults to `None`.
+    fn kind(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The name gameplay events refer to this character by, Its kind or its class name.
+    fn event_name(&self) -> &'static str {
+        self.kind().unwrap_or_else(|| self.class().name())
+    }
+
+    /// The experience rewarded for defeating this character. Defaults to `0`.
+    fn experience_reward(&self) -> u32 {
+        0
+    }
+
+    /// The loot table rolled when this character is defeated. Defaults to `None`.
+    fn loot_table(&self) -> Option<&'static LootTable> {
+        None
+    }
+
+    /// The talents this character learned, Characters without any can't learn talents. Defaults to `None`.
+    fn talents(&self) -> Option<&Talents> {
+        None
+    }
+
+    /// Returns a mutable reference to the character's talents. Defaults to `None`.
+    fn talents_mut(&mut self) -> Option<&mut Talents> {
+        None
+    }
+
+    /// The abilities this character is able to use.
+    ///
+    /// Defaults to its talents' abilities, Or its class abilities if it has no talents.
+    fn abilities(&self) -> &[Ability] {
+        match self.talents() {
+            Some(talents) => talents.abilities(),
+            None => self.class().abilities(),
+        }
+    }
+
+    /// The character's stats including the bonuses of its talents,
+    /// Movement speed is reduced by the encumbrance of its inventory.
+    fn effective_stats(&self) -> Stats {
+        let mut stats = match self.talents() {
+            Some(talents) => talents.apply(self.stats()),
+            None => *self.stats(),
+        };
+        stats.movement_speed = stats
+            .movement_speed
+            .saturating_mul(self.inventory().encumbrance().movement_speed())
+            / 100;
+        stats
+    }
+
+    /// Learn a talent from the character's class talent tree, See [`Talents::learn`].
+    ///
+    /// Health talents raise the character's max health.
+    fn learn_talent(&mut self, id: &str) -> anyhow::Result<()> {
+        let level = *self.level();
+        let class = *self.class();
+        let before = self.effective_stats().health;
+        self.talents_mut()
+            .ok_or_else(|| anyhow::anyhow!("{} characters can't learn talents", class.name()))?
+            .learn(id, &level)?;
+        update_max_health(self, before);
+        Ok(())
+    }
+
+    /// Forget every learned talent, Returning the refunded talent points.
+    fn respec(&mut self) -> u32 {
+        let before = self.effective_stats().health;
+        let refunded = self.talents_mut().map_or(0, Talents::respec);
+        update_max_health(self, before);
+        refunded
+    }
+
+    /// Returns the character's main hand weapon if any.
+    fn weapon(&self) -> Option<&Weapon> {
+        self.equipment().main_hand()
+    }
+
+    /// The damage a basic attack from this character deals with its equipped weapons.
+    ///
+    /// Unarmed characters deal 1 damage.
+    fn attack_damage(&self) -> u32 {
+        let damage = self.equipment().damage();
+        self.talents()
+            .map_or(damage, |talents| talents.weapon_damage(damage))
+            .max(1)
+    }
+
+    /// Equip a weapon in the given slot.
+    ///
+    /// The weapon type must be allowed by the character's class.
+    /// Weapons that were unequipped to make room for it are put in the inventory,
+    /// If the inventory can't hold them nothing is changed.
+    fn equip(&mut self, weapon: Weapon, slot: EquipSlot) -> anyhow::Result<()> {
+        let class = *self.class();
+        if !class.can_wield(weapon.weapon_type()) {
+            return Err(anyhow::anyhow!(
+                "{} cannot wield {}",
+                class.name(),
+                weapon.weapon_type().name()
+            ));
+        }
+
+        let previous = *self.equipment();
+        let mut inventory = self.inventory().clone();
+        for displaced in self.equipment_mut().equip(weapon, slot)? {
+            if let Err(err) = inventory.put_weapon(displaced) {
+                *self.equipment_mut() = previous;
+                return Err(err);
+            }
+        }
+        *self.inventory_mut() = inventory;
+        Ok(())
+    }
+
+    /// Equip the weapon at the given inventory index in the given slot.
+    ///
+    /// This is how weapons are swapped, The previously equipped weapon goes back to the inventory.
+    fn equip_from_inventory(&mut self, index: usize, slot: EquipSlot) -> anyhow::Result<()> {
+        let previous = self.inventory().clone();
+        let weapon = self
+            .inventory_mut()
+            .take_weapon(index)
+            .ok_or_else(|| anyhow::anyhow!("No weapon at inventory index {}", index))?;
+
+        if let Err(err) = self.equip(weapon, slot) {
+            *self.inventory_mut() = previous;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Swap the main hand and off hand weapons.
+    fn swap_weapons(&mut self) -> anyhow::Result<()> {
+        self.equipment_mut().swap_hands()
+    }
+}
+
+/// Move the character's max health by how much its health stat changed from `before`.
+fn update_max_health<C: Char + ?Sized>(character: &mut C, before: u32) {
+    let after = character.effective_stats().health;
+    let health = character.health_mut();
+    let max = health.max().saturating_add(after).saturating_sub(before);
+    health.set_max(max);
+}
+
+/// Constructors of a character type.
+///
+/// These are kept out of [`Char`] since they return `Self`, Which trait objects can't.
+pub trait CharFactory: Char + Sized {
+    /// The standard way to create a character.
+    fn new() -> Self;
+    /// Build a character giving it its components.
+    fn build(inventory: &Inventory, stats: &Stats, health: &Health) -> Self;
+}
+
+/// Cloning for boxed characters.
+///
+/// This is implemented for every [`Char`] that implements [`Clone`].
+pub trait CharClone {
+    fn clone_box(&self) -> Box<dyn Char>;
+}
+
+impl<T> CharClone for T
+where
+    T: Char + Clone + 'static,
+{
+    fn clone_box(&self) -> Box<dyn Char> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Char> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
 }
 
 /// A generic character builder that can be used to create a character.
 ///
-/// This accepts a `CharImpl` type that must be an item that implements [`Char`].
+/// This accepts a `CharImpl` type that must be an item that implements [`CharFactory`].
 ///
 /// ```, ignore
-/// use characters::{Char, CharImpl, Susanoo};
+/// use characters::{CharFactory, Character, Susanoo};
 /// // Construct a new susanoo character.
 /// let new_susanoo = Character::<Susanoo>::new();
 /// ```
 #[derive(Clone, Debug, PartialEq)]
-pub struct Character<CharImpl: Char> {
+pub struct Character<CharImpl: CharFactory> {
     base: CharImpl,
 }
 
 impl<CharImpl> Character<CharImpl>
 where
-    CharImpl: Char,
+    CharImpl: CharFactory,
 {
     #[must_use]
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> CharImpl {
-        <CharImpl as Char>::new()
+        <CharImpl as CharFactory>::new()
     }
 }
@@ -0,0 +1,392 @@
+//! A builder for user defined characters.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::character::{Char, CharacterClass, MetaData};
+use crate::level::Level;
+use crate::stats::{Resistense, Stats};
+use crate::talents::Talents;
+use abilites::Ability;
+use components::{Consumable, EquipSlot, Equipment, Health, Inventory, Mana, Wallet, Weapon};
+
+/// A character built by a [`CharacterBuilder`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomCharacter {
+    name: &'static str,
+    lore: &'static str,
+    class: CharacterClass,
+    health: Health,
+    mana: Mana,
+    inventory: Inventory,
+    equipment: Equipment,
+    stats: Stats,
+    resistances: Resistense,
+    level: Level,
+    wallet: Wallet,
+    talents: Talents,
+    experience_reward: u32,
+}
+
+impl MetaData for CustomCharacter {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Lore about the character's background.
+    fn description(&self) -> &'static str {
+        self.lore
+    }
+}
+
+impl std::fmt::Display for CustomCharacter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}(class: {}, health: {})",
+            self.name,
+            self.class.name(),
+            self.health.current(),
+        )
+    }
+}
+
+impl Char for CustomCharacter {
+    fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+
+    fn equipment(&self) -> &Equipment {
+        &self.equipment
+    }
+
+    fn equipment_mut(&mut self) -> &mut Equipment {
+        &mut self.equipment
+    }
+
+    fn health(&self) -> &Health {
+        &self.health
+    }
+
+    fn health_mut(&mut self) -> &mut Health {
+        &mut self.health
+    }
+
+    fn mana(&self) -> &Mana {
+        &self.mana
+    }
+
+    fn mana_mut(&mut self) -> &mut Mana {
+        &mut self.mana
+    }
+
+    fn class(&self) -> &CharacterClass {
+        &self.class
+    }
+
+    fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    fn resistances(&self) -> &Resistense {
+        &self.resistances
+    }
+
+    fn level(&self) -> &Level {
+        &self.level
+    }
+
+    fn level_mut(&mut self) -> &mut Level {
+        &mut self.level
+    }
+
+    fn wallet(&self) -> &Wallet {
+        &self.wallet
+    }
+
+    fn wallet_mut(&mut self) -> &mut Wallet {
+        &mut self.wallet
+    }
+
+    fn kind(&self) -> Option<&'static str> {
+        Some(self.name)
+    }
+
+    fn experience_reward(&self) -> u32 {
+        self.experience_reward
+    }
+
+    fn talents(&self) -> Option<&Talents> {
+        Some(&self.talents)
+    }
+
+    fn talents_mut(&mut self) -> Option<&mut Talents> {
+        Some(&mut self.talents)
+    }
+}
+
+/// Define a character without implementing [`Char`] by hand.
+///
+/// Characters without abilities use their class abilities, And characters without
+/// weapons start with their class starter weapon.
+///
+/// ```
+/// use characters::{Char, CharacterBuilder, CharacterClass, Stats};
+/// use characters::components::{EquipSlot, Weapon, WeaponType};
+///
+/// let builder = CharacterBuilder::new("Freya", CharacterClass::Warlock)
+///     .lore("Rides to war in a chariot pulled by two cats.")
+///     .stats(Stats {
+///         mp5: 5,
+///         ..Stats::default()
+///     })
+///     .weapon(Weapon::new(WeaponType::Daggers), EquipSlot::MainHand);
+///
+/// let freya = builder.build().unwrap();
+/// assert_eq!(freya.kind(), Some("Freya"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CharacterBuilder {
+    name: &'static str,
+    lore: &'static str,
+    class: CharacterClass,
+    stats: Stats,
+    resistances: Resistense,
+    health: Option<u32>,
+    mana: Option<u32>,
+    level: u32,
+    gold: u64,
+    weapons: Vec<(Weapon, EquipSlot)>,
+    inventory: Vec<Weapon>,
+    consumables: Vec<Consumable>,
+    abilities: Vec<Ability>,
+    experience_reward: u32,
+}
+
+impl CharacterBuilder {
+    /// Start defining a character with a unique name and a class.
+    pub fn new(name: &'static str, class: CharacterClass) -> CharacterBuilder {
+        CharacterBuilder {
+            name,
+            lore: "",
+            class,
+            stats: Stats::default(),
+            resistances: Resistense::default(),
+            health: None,
+            mana: None,
+            level: 1,
+            gold: 0,
+            weapons: Vec::new(),
+            inventory: Vec::new(),
+            consumables: Vec::new(),
+            abilities: Vec::new(),
+            experience_reward: 0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn class(&self) -> CharacterClass {
+        self.class
+    }
+
+    /// Lore about the character's background.
+    pub fn lore(mut self, lore: &'static str) -> Self {
+        self.lore = lore;
+        self
+    }
+
+    pub fn stats(mut self, stats: Stats) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    pub fn resistances(mut self, resistances: Resistense) -> Self {
+        self.resistances = resistances;
+        self
+    }
+
+    /// The health the character starts with. Defaults to `100`.
+    ///
+    /// This is also the character's max health if it's above `100`.
+    pub fn health(mut self, health: u32) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// The character's max mana. Defaults to `100`.
+    pub fn mana(mut self, mana: u32) -> Self {
+        self.mana = Some(mana);
+        self
+    }
+
+    /// The level the character starts at. Defaults to `1`.
+    pub fn level(mut self, level: u32) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// The gold the character starts with. Defaults to `0`.
+    pub fn gold(mut self, gold: u64) -> Self {
+        self.gold = gold;
+        self
+    }
+
+    /// Equip a starting weapon in the given slot.
+    pub fn weapon(mut self, weapon: Weapon, slot: EquipSlot) -> Self {
+        self.weapons.push((weapon, slot));
+        self
+    }
+
+    /// Put a starting weapon in the character's inventory.
+    pub fn item(mut self, weapon: Weapon) -> Self {
+        self.inventory.push(weapon);
+        self
+    }
+
+    /// Put a starting consumable in the character's inventory.
+    pub fn consumable(mut self, consumable: Consumable) -> Self {
+        self.consumables.push(consumable);
+        self
+    }
+
+    /// Give the character an ability, Replacing its class abilities.
+    pub fn ability(mut self, ability: Ability) -> Self {
+        self.abilities.push(ability);
+        self
+    }
+
+    /// The experience rewarded for defeating the character.
+    pub fn experience_reward(mut self, experience: u32) -> Self {
+        self.experience_reward = experience;
+        self
+    }
+
+    /// Build a new character.
+    ///
+    /// Fails if the class can't wield a starting weapon or the starting gear doesn't fit.
+    pub fn build(&self) -> anyhow::Result<CustomCharacter> {
+        let mut character = CustomCharacter {
+            name: self.name,
+            lore: self.lore,
+            class: self.class,
+            health: Health::new(self.health),
+            mana: Mana::with_resource(self.class.resource(), self.mana),
+            inventory: Inventory::new(),
+            equipment: Equipment::default(),
+            stats: self.stats,
+            resistances: self.resistances,
+            level: Level::new(self.level),
+            wallet: Wallet::new(self.gold),
+            talents: if self.abilities.is_empty() {
+                Talents::new(self.class)
+            } else {
+                Talents::with_abilities(self.class, self.abilities.clone())
+            },
+            experience_reward: self.experience_reward,
+        };
+
+        for weapon in &self.inventory {
+            character.inventory.put_weapon(*weapon)?;
+        }
+        for consumable in &self.consumables {
+            character.inventory.put_consumable(*consumable)?;
+        }
+        if self.weapons.is_empty() {
+            character.equip(
+                Weapon::new(self.class.starter_weapon()),
+                EquipSlot::MainHand,
+            )?;
+        }
+        for (weapon, slot) in &self.weapons {
+            character.equip(*weapon, *slot)?;
+        }
+        Ok(character)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use abilites::{AbilityEffect, AbilityTarget};
+    use components::{consumable::ConsumableType, WeaponType};
+
+    #[test]
+    fn test_build() {
+        let character = CharacterBuilder::new("Loki", CharacterClass::Assassin)
+            .lore("The trickster.")
+            .health(80)
+            .level(7)
+            .gold(300)
+            .resistances(Resistense::new(0, 0, 50, 0))
+            .item(Weapon::new(WeaponType::Bow))
+            .consumable(Consumable::new(ConsumableType::HealthPotion))
+            .ability(Ability::new(
+                "Shapeshift",
+                "Take another form.",
+                AbilityTarget::Caster,
+                AbilityEffect::Heal(10),
+                2,
+            ))
+            .build()
+            .unwrap();
+
+        assert_eq!(character.description(), "The trickster.");
+        assert_eq!(character.health().current(), 80);
+        assert_eq!(character.level().level(), 7);
+        assert_eq!(character.wallet().gold(), 300);
+        assert_eq!(character.abilities()[0].name(), "Shapeshift");
+        assert_eq!(character.inventory().len(), 2);
+        assert_eq!(
+            character.weapon().unwrap().weapon_type(),
+            CharacterClass::Assassin.starter_weapon()
+        );
+    }
+
+    #[test]
+    fn test_class_restrictions() {
+        let builder = CharacterBuilder::new("Thor", CharacterClass::Warlock)
+            .weapon(Weapon::new(WeaponType::Mace), EquipSlot::MainHand);
+        assert!(builder.build().is_err());
+
+        let thor = CharacterBuilder::new("Thor", CharacterClass::Warrior)
+            .weapon(Weapon::new(WeaponType::Mace), EquipSlot::MainHand)
+            .weapon(Weapon::new(WeaponType::Claw), EquipSlot::OffHand)
+            .build()
+            .unwrap();
+        assert!(thor.equipment().is_dual_wielding());
+        assert_eq!(thor.abilities(), CharacterClass::Warrior.abilities());
+    }
+}
@@ -0,0 +1,274 @@
+//! Changing characters while emitting gameplay events.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::character::Char;
+use components::{EquipSlot, EventBus, GameEvent, Loot, Weapon};
+
+/// The [`GameEvent::LevelUp`] for every level gained from the start level.
+pub(crate) fn level_ups(
+    target: &'static str,
+    start: u32,
+    gained: u32,
+) -> impl Iterator<Item = GameEvent> {
+    (start + 1..=start + gained).map(move |level| GameEvent::LevelUp { target, level })
+}
+
+/// Whether the character with the given event name is a player, Everyone is if no players are tracked.
+pub(crate) fn is_player(players: &[String], name: &str) -> bool {
+    players.is_empty() || players.iter().any(|p| p == name)
+}
+
+/// A character whose changes are emitted to an [`EventBus`].
+///
+/// Events are emitted right away, After the change succeeded.
+///
+/// ```
+/// use characters::{CharFactory, Observed, Tyr};
+/// use components::{EventBus, EventKind};
+/// use std::sync::{Arc, Mutex};
+///
+/// let levels = Arc::new(Mutex::new(Vec::new()));
+/// let mut events = EventBus::new();
+/// let sink = levels.clone();
+/// events.subscribe_to(EventKind::LevelUp, move |event| sink.lock().unwrap().push(*event));
+///
+/// let mut tyr = Tyr::new();
+/// Observed::new(&mut tyr, &mut events).gain_experience(300);
+/// assert_eq!(levels.lock().unwrap().len(), 2);
+/// ```
+pub struct Observed<'a> {
+    character: &'a mut dyn Char,
+    events: &'a mut EventBus,
+}
+
+impl<'a> Observed<'a> {
+    pub fn new(character: &'a mut dyn Char, events: &'a mut EventBus) -> Observed<'a> {
+        Observed { character, events }
+    }
+
+    pub fn character(&self) -> &dyn Char {
+        self.character
+    }
+
+    fn name(&self) -> &'static str {
+        self.character.event_name()
+    }
+
+    /// Deal damage to the character, Emitting [`GameEvent::Died`] too if it killed it.
+    ///
+    /// ## Returns
+    /// [`u32`] The character's remaining health.
+    pub fn damage(&mut self, source: Option<&'static str>, amount: u32) -> u32 {
+        let alive = !self.character.health().is_killed();
+        let before = self.character.health().current();
+        let health = self.character.health_mut().drip(amount);
+        self.events.emit(GameEvent::DamageDealt {
+            source,
+            target: self.name(),
+            amount: before - health,
+            ammo: None,
+        });
+        if alive && health == 0 {
+            self.events.emit(GameEvent::Died {
+                target: self.name(),
+            });
+        }
+        health
+    }
+
+    /// Heal the character, Dead characters can't be healed.
+    ///
+    /// ## Returns
+    /// [`u32`] The amount of health restored.
+    pub fn heal(&mut self, amount: u32) -> anyhow::Result<u32> {
+        let before = self.character.health().current();
+        let health = self
+            .character
+            .health_mut()
+            .incr(amount)
+            .map_err(|err| anyhow::anyhow!("{}", err))?;
+        let healed = health - before;
+        if healed > 0 {
+            self.events.emit(GameEvent::Healed {
+                target: self.name(),
+                amount: healed,
+            });
+        }
+        Ok(healed)
+    }
+
+    pub fn kill(&mut self) {
+        if !self.character.health().is_killed() {
+            self.character.health_mut().kill();
+            self.events.emit(GameEvent::Died {
+                target: self.name(),
+            });
+        }
+    }
+
+    /// Revive the character, Fails if it's alive.
+    pub fn revive(&mut self) -> anyhow::Result<bool> {
+        let revived = self.character.health_mut().revive()?;
+        self.events.emit(GameEvent::Revived {
+            target: self.name(),
+        });
+        Ok(revived)
+    }
+
+    /// Put a looted item in the character's inventory.
+    pub fn pick_up(&mut self, item: Loot) -> anyhow::Result<()> {
+        self.character.inventory_mut().put_loot(item)?;
+        self.events.emit(GameEvent::ItemPickedUp {
+            owner: self.name(),
+            item,
+        });
+        Ok(())
+    }
+
+    /// Equip a weapon in the given slot, See [`Char::equip`].
+    pub fn equip(&mut self, weapon: Weapon, slot: EquipSlot) -> anyhow::Result<()> {
+        self.character.equip(weapon, slot)?;
+        self.equipped(slot);
+        Ok(())
+    }
+
+    /// Equip the weapon at the given inventory index in the given slot, See [`Char::equip_from_inventory`].
+    pub fn equip_from_inventory(&mut self, index: usize, slot: EquipSlot) -> anyhow::Result<()> {
+        self.character.equip_from_inventory(index, slot)?;
+        self.equipped(slot);
+        Ok(())
+    }
+
+    fn equipped(&mut self, slot: EquipSlot) {
+        if let Some(weapon) = self.character.equipment().get(slot).copied() {
+            self.events.emit(GameEvent::ItemEquipped {
+                owner: self.name(),
+                weapon,
+                slot,
+            });
+        }
+    }
+
+    /// Gain experience, Emitting a [`GameEvent::LevelUp`] for every level gained.
+    ///
+    /// ## Returns
+    /// [`u32`] The amount of levels gained.
+    pub fn gain_experience(&mut self, experience: u32) -> u32 {
+        let start = self.character.level().level();
+        let gained = self.character.level_mut().gain(experience);
+        for event in level_ups(self.name(), start, gained) {
+            self.events.emit(event);
+        }
+        gained
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CharFactory, Kain, Vamp};
+    use components::{Consumable, ConsumableType, EventKind, WeaponType};
+    use std::sync::{Arc, Mutex};
+
+    fn recorder(events: &mut EventBus) -> Arc<Mutex<Vec<GameEvent>>> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = log.clone();
+        events.subscribe(move |event| sink.lock().unwrap().push(*event));
+        log
+    }
+
+    #[test]
+    fn test_health_events() {
+        let mut events = EventBus::new();
+        let log = recorder(&mut events);
+        let mut kain = Kain::new();
+        let mut observed = Observed::new(&mut kain, &mut events);
+
+        assert_eq!(observed.damage(Some("Vamp"), 30), 70);
+        assert_eq!(observed.heal(10).unwrap(), 10);
+        assert_eq!(observed.damage(None, 500), 0);
+        observed.kill();
+        observed.revive().unwrap();
+        assert!(observed.revive().is_err());
+
+        let kinds: Vec<_> = log.lock().unwrap().iter().map(|e| e.kind()).collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::DamageDealt,
+                EventKind::Healed,
+                EventKind::DamageDealt,
+                EventKind::Died,
+                EventKind::Revived
+            ]
+        );
+        assert_eq!(
+            log.lock().unwrap()[2],
+            GameEvent::DamageDealt {
+                source: None,
+                target: "Kain",
+                amount: 80,
+                ammo: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_item_events() {
+        let mut events = EventBus::new();
+        let log = recorder(&mut events);
+        let mut vamp = Vamp::new();
+        let mut observed = Observed::new(&mut vamp, &mut events);
+
+        let elixir = Loot::Consumable(Consumable::new(ConsumableType::Elixir));
+        observed.pick_up(elixir).unwrap();
+        let claw = Weapon::new(WeaponType::Claw);
+        observed.equip(claw, EquipSlot::MainHand).unwrap();
+        assert!(observed
+            .equip(Weapon::new(WeaponType::Bow), EquipSlot::MainHand)
+            .is_err());
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                GameEvent::ItemPickedUp {
+                    owner: "Vamp",
+                    item: elixir
+                },
+                GameEvent::ItemEquipped {
+                    owner: "Vamp",
+                    weapon: claw,
+                    slot: EquipSlot::MainHand
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt};
+
+/// The highest level a character can reach.
+pub const MAX_LEVEL: u32 = 50;
+
+/// The saved state of a level, Validated when loaded.
+#[derive(Serialize, Deserialize)]
+struct LevelRecord {
+    level: u32,
+    experience: u32,
+}
+
+/// Core level component, Tracks the level and the experience towards the next one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "LevelRecord", into = "LevelRecord")]
+pub struct Level {
+    level: u32,
+    experience: u32,
+}
+
+impl TryFrom<LevelRecord> for Level {
+    type Error = anyhow::Error;
+
+    fn try_from(record: LevelRecord) -> anyhow::Result<Level> {
+        if !(1..=MAX_LEVEL).contains(&record.level) {
+            return Err(anyhow::anyhow!(
+                "Level {} is not between 1 and {}",
+                record.level,
+                MAX_LEVEL
+            ));
+        }
+        let level = Level::new(record.level);
+        if record.experience > 0 && record.experience >= level.experience_to_next() {
+            return Err(anyhow::anyhow!(
+                "Level {} can't have {} experience",
+                record.level,
+                record.experience
+            ));
+        }
+        Ok(Level {
+            experience: record.experience,
+            ..level
+        })
+    }
+}
+
+impl From<Level> for LevelRecord {
+    fn from(level: Level) -> LevelRecord {
+        LevelRecord {
+            level: level.level,
+            experience: level.experience,
+        }
+    }
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level {
+            level: 1,
+            experience: 0,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Level(level: {}, xp: {}/{})",
+            self.level,
+            self.experience,
+            self.experience_to_next()
+        )
+    }
+}
+
+impl Level {
+    /// Creates a new level with no experience, Clamped between 1 and [`MAX_LEVEL`].
+    pub fn new(level: u32) -> Level {
+        Level {
+            level: level.clamp(1, MAX_LEVEL),
+            experience: 0,
+        }
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// The experience gained towards the next level.
+    pub fn experience(&self) -> u32 {
+        self.experience
+    }
+
+    /// The experience needed to reach the next level. `0` at max level.
+    pub fn experience_to_next(&self) -> u32 {
+        if self.is_max() {
+            0
+        } else {
+            self.level * 100
+        }
+    }
+
+    pub fn is_max(&self) -> bool {
+        self.level >= MAX_LEVEL
+    }
+
+    /// The talent points earned, One for every level after the first.
+    pub fn talent_points(&self) -> u32 {
+        self.level - 1
+    }
+
+    /// Gain experience, Leveling up as many times as it allows.
+    ///
+    /// ## Returns
+    /// [`u32`] The amount of levels gained.
+    pub fn gain(&mut self, experience: u32) -> u32 {
+        let start = self.level;
+        let mut experience = self.experience.saturating_add(experience);
+
+        while !self.is_max() && experience >= self.experience_to_next() {
+            experience -= self.experience_to_next();
+            self.level += 1;
+        }
+        self.experience = if self.is_max() { 0 } else { experience };
+        self.level - start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gain() {
+        let mut level = Level::default();
+        assert_eq!(level.gain(50), 0);
+        // 100 to level 2, 200 to level 3.
+        assert_eq!(level.gain(260), 2);
+        assert_eq!((level.level(), level.experience()), (3, 10));
+
+        assert_eq!(level.gain(u32::MAX), MAX_LEVEL - 3);
+        assert!(level.is_max());
+        assert_eq!(level.experience(), 0);
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut level = Level::default();
+        level.gain(150);
+        let json = serde_json::to_string(&level).unwrap();
+        assert_eq!(serde_json::from_str::<Level>(&json).unwrap(), level);
+
+        for json in [
+            r#"{"level": 0, "experience": 0}"#,
+            r#"{"level": 51, "experience": 0}"#,
+            r#"{"level": 2, "experience": 200}"#,
+        ] {
+            assert!(serde_json::from_str::<Level>(json).is_err());
+        }
+    }
+}
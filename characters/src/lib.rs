@@ -30,11 +30,37 @@
 
 #![feature(decl_macro)]
 
+mod achievements;
 mod builtins;
 mod character;
+mod custom;
+mod events;
+mod level;
+mod monsters;
+mod party;
+mod quests;
+mod registry;
+mod save;
 mod stats;
+mod talents;
+mod trade;
 
+pub use self::achievements::{Achievement, AchievementProgress, Achievements, Criteria};
 pub use self::builtins::{Kain, Susanoo, Tyr, Vamp};
-pub use self::character::{Char, Character, CharacterClass};
-pub use self::stats::{Resistense, Stats};
+pub use self::character::{Char, CharClone, CharFactory, Character, CharacterClass, ClassPassive};
+pub use self::custom::{CharacterBuilder, CustomCharacter};
+pub use self::events::Observed;
+pub use self::level::{Level, MAX_LEVEL};
+pub use self::monsters::{
+    Bestiary, BestiaryEntry, CaveSpider, DireWolf, FrostWraith, Ghoul, Imp, Monster,
+    MonsterArchetype, MonsterFamily, SkeletonArcher,
+};
+pub use self::party::{Member, Party, PartyBuff, Row, MAX_PARTY_SIZE};
+pub use self::quests::{Objective, Quest, QuestLog, QuestProgress, Reward};
+pub use self::registry::{CharacterRegistry, RegistryEntry};
+pub use self::save::{CharacterSave, PartySave, SaveGame, SAVE_VERSION};
+pub use self::stats::{Resistense, Stat, Stats};
+pub use self::talents::{Talent, TalentEffect, Talents};
+pub use self::trade::{Offer, Trade, TradeSide};
+pub use abilites;
 pub use components;
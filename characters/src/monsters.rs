@@ -0,0 +1,581 @@
+//! Monsters and the bestiary they're registered in.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use components::{
+    status::{StatusEffect, StatusKind},
+    EquipSlot, Equipment, Health, Inventory, LootTable, Mana, Wallet, Weapon, WeaponType,
+};
+
+use crate::character::{Char, CharFactory, CharacterClass, MetaData};
+use crate::level::Level;
+use crate::stats::{Resistense, Stats};
+use abilites::{Ability, AbilityEffect, AbilityTarget};
+
+/// Monster families, Used to group monsters in the [`Bestiary`].
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MonsterFamily {
+    Undead,
+    Beast,
+    Demon,
+    Elemental,
+}
+
+impl MetaData for MonsterFamily {
+    fn name(&self) -> &'static str {
+        match self {
+            MonsterFamily::Undead => "Undead",
+            MonsterFamily::Beast => "Beast",
+            MonsterFamily::Demon => "Demon",
+            MonsterFamily::Elemental => "Elemental",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            MonsterFamily::Undead => "Restless corpses that refuse to stay in their graves.",
+            MonsterFamily::Beast => "Wild creatures of the forests and caves.",
+            MonsterFamily::Demon => "Fiends crawling out of the burning pits.",
+            MonsterFamily::Elemental => "Living storms of ice and fire.",
+        }
+    }
+}
+
+/// The definition every monster of a kind is spawned from.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct MonsterArchetype {
+    /// Unique id used to look the monster up in the [`Bestiary`].
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub family: MonsterFamily,
+    pub class: CharacterClass,
+    pub level: u32,
+    /// The health the monster spawns with, Also its max health.
+    pub health: u32,
+    pub stats: Stats,
+    pub resistances: Resistense,
+    /// The weapon the monster spawns with. Monsters ignore class weapon restrictions.
+    pub weapon: Option<WeaponType>,
+    pub abilities: &'static [Ability],
+    /// The id of the [`LootTable`] rolled when this monster is defeated.
+    pub loot_table: &'static str,
+    /// The experience rewarded for defeating this monster.
+    pub experience: u32,
+}
+
+impl MetaData for MonsterArchetype {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+}
+
+impl MonsterArchetype {
+    /// Returns the loot table this monster references if it exists.
+    pub fn loot_table(&self) -> Option<&'static LootTable> {
+        LootTable::get(self.loot_table)
+    }
+}
+
+/// A character spawned from a [`MonsterArchetype`].
+pub trait Monster: CharFactory {
+    /// Returns the archetype this monster is spawned from.
+    fn archetype() -> &'static MonsterArchetype;
+}
+
+macro impl_monster($name:ident, $archetype:expr) {
+    /// Monster spawned from its archetype.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct $name {
+        health: Health,
+        mana: Mana,
+        inventory: Inventory,
+        equipment: Equipment,
+        stats: Stats,
+        resistances: Resistense,
+        level: Level,
+        wallet: Wallet,
+        class: CharacterClass,
+    }
+
+    impl Monster for $name {
+        fn archetype() -> &'static MonsterArchetype {
+            &$archetype
+        }
+    }
+
+    impl ::std::fmt::Display for $name {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "{}(level: {}, health: {})",
+                $archetype.name,
+                self.level.level(),
+                self.health.current(),
+            )
+        }
+    }
+
+    impl CharFactory for $name {
+        fn new() -> Self {
+            let archetype = Self::archetype();
+            let mut health = Health::new(Some(archetype.health));
+            health.set_max(archetype.health);
+            let mut monster = Self::build(&Inventory::default(), &archetype.stats, &health);
+            if let Some(weapon_type) = archetype.weapon {
+                monster
+                    .equipment
+                    .equip(Weapon::new(weapon_type), EquipSlot::MainHand)
+                    .unwrap();
+            }
+            monster
+        }
+
+        fn build(inventory: &Inventory, stats: &Stats, health: &Health) -> Self {
+            Self {
+                class: $archetype.class,
+                health: *health,
+                mana: Mana::with_resource($archetype.class.resource(), None),
+                inventory: inventory.clone(),
+                equipment: Equipment::default(),
+                stats: *stats,
+                resistances: $archetype.resistances,
+                level: Level::new($archetype.level),
+                wallet: Wallet::default(),
+            }
+        }
+    }
+
+    impl Char for $name {
+        fn inventory(&self) -> &Inventory {
+            &self.inventory
+        }
+
+        fn inventory_mut(&mut self) -> &mut Inventory {
+            &mut self.inventory
+        }
+
+        fn equipment(&self) -> &Equipment {
+            &self.equipment
+        }
+
+        fn equipment_mut(&mut self) -> &mut Equipment {
+            &mut self.equipment
+        }
+
+        fn health(&self) -> &Health {
+            &self.health
+        }
+
+        fn health_mut(&mut self) -> &mut Health {
+            &mut self.health
+        }
+
+        fn mana(&self) -> &Mana {
+            &self.mana
+        }
+
+        fn mana_mut(&mut self) -> &mut Mana {
+            &mut self.mana
+        }
+
+        fn class(&self) -> &CharacterClass {
+            &self.class
+        }
+
+        fn stats(&self) -> &Stats {
+            &self.stats
+        }
+
+        fn resistances(&self) -> &Resistense {
+            &self.resistances
+        }
+
+        fn level(&self) -> &Level {
+            &self.level
+        }
+
+        fn level_mut(&mut self) -> &mut Level {
+            &mut self.level
+        }
+
+        fn wallet(&self) -> &Wallet {
+            &self.wallet
+        }
+
+        fn wallet_mut(&mut self) -> &mut Wallet {
+            &mut self.wallet
+        }
+
+        fn kind(&self) -> Option<&'static str> {
+            Some($archetype.id)
+        }
+
+        fn experience_reward(&self) -> u32 {
+            $archetype.experience
+        }
+
+        fn loot_table(&self) -> Option<&'static LootTable> {
+            Self::archetype().loot_table()
+        }
+
+        fn abilities(&self) -> &[Ability] {
+            $archetype.abilities
+        }
+    }
+}
+
+const fn stats(attack_speed: usize, movement_speed: u32, evasion: u32) -> Stats {
+    Stats {
+        mp5: 0,
+        hp5: 0,
+        health: 0,
+        evasion,
+        movement_speed,
+        attack_speed,
+    }
+}
+
+pub const GHOUL: MonsterArchetype = MonsterArchetype {
+    id: "ghoul",
+    name: "Ghoul",
+    description: "A rotting corpse that tears at the living with its claws.",
+    family: MonsterFamily::Undead,
+    class: CharacterClass::Warrior,
+    level: 2,
+    health: 90,
+    stats: stats(10, 0, 0),
+    resistances: Resistense::new(30, 0, 10, 0),
+    weapon: Some(WeaponType::Claw),
+    abilities: &[Ability::new(
+        "Rend",
+        "Tear the flesh of the enemy leaving it bleeding.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Apply(StatusEffect::new(StatusKind::Bleed, 3, 4)),
+        3,
+    )
+    .with_cost(10)],
+    loot_table: "undead",
+    experience: 40,
+};
+
+pub const SKELETON_ARCHER: MonsterArchetype = MonsterArchetype {
+    id: "skeleton_archer",
+    name: "Skeleton Archer",
+    description: "Bones held together by old magic and a grudge.",
+    family: MonsterFamily::Undead,
+    class: CharacterClass::Assassin,
+    level: 3,
+    health: 70,
+    stats: stats(0, 0, 10),
+    resistances: Resistense::new(50, 0, 0, 0),
+    weapon: Some(WeaponType::Bow),
+    abilities: &[Ability::new(
+        "Piercing Shot",
+        "A slow aimed shot.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Damage(20),
+        2,
+    )
+    .with_cost(15)
+    .with_cast_time(1000)],
+    loot_table: "undead",
+    experience: 55,
+};
+
+pub const DIRE_WOLF: MonsterArchetype = MonsterArchetype {
+    id: "dire_wolf",
+    name: "Dire Wolf",
+    description: "Hunts in packs, Quick and hungry.",
+    family: MonsterFamily::Beast,
+    class: CharacterClass::Assassin,
+    level: 1,
+    health: 60,
+    stats: stats(20, 20, 5),
+    resistances: Resistense::new(0, 0, 0, 0),
+    weapon: None,
+    abilities: &[Ability::new(
+        "Savage Bite",
+        "Sink its teeth into the enemy.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Damage(12),
+        1,
+    )
+    .with_cost(10)],
+    loot_table: "beast",
+    experience: 25,
+};
+
+pub const CAVE_SPIDER: MonsterArchetype = MonsterArchetype {
+    id: "cave_spider",
+    name: "Cave Spider",
+    description: "Lurks in the dark, Its bite is venomous.",
+    family: MonsterFamily::Beast,
+    class: CharacterClass::Assassin,
+    level: 2,
+    health: 50,
+    stats: stats(10, 10, 15),
+    resistances: Resistense::new(40, 0, 0, 0),
+    weapon: Some(WeaponType::Daggers),
+    abilities: &[Ability::new(
+        "Venom Spit",
+        "Spit venom poisoning the enemy.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Apply(StatusEffect::new(StatusKind::Toxin, 4, 4)),
+        3,
+    )
+    .with_cost(10)],
+    loot_table: "beast",
+    experience: 35,
+};
+
+pub const IMP: MonsterArchetype = MonsterArchetype {
+    id: "imp",
+    name: "Imp",
+    description: "A small cackling demon that hurls fire.",
+    family: MonsterFamily::Demon,
+    class: CharacterClass::Warlock,
+    level: 3,
+    health: 65,
+    stats: stats(0, 10, 10),
+    resistances: Resistense::new(0, 40, 0, 0),
+    weapon: Some(WeaponType::Rod),
+    abilities: &[Ability::new(
+        "Fireball",
+        "Hurl a ball of fire.",
+        AbilityTarget::Enemy,
+        AbilityEffect::Damage(18),
+        2,
+    )
+    .with_cost(20)
+    .with_cast_time(1500)],
+    loot_table: "demon",
+    experience: 60,
+};
+
+pub const FROST_WRAITH: MonsterArchetype = MonsterArchetype {
+    id: "frost_wraith",
+    name: "Frost Wraith",
+    description: "A howling spirit of the frozen peaks.",
+    family: MonsterFamily::Elemental,
+    class: CharacterClass::Warlock,
+    level: 5,
+    health: 120,
+    stats: stats(0, 0, 10),
+    resistances: Resistense::new(0, 60, 0, 20),
+    weapon: Some(WeaponType::Rod),
+    abilities: &[
+        Ability::new(
+            "Frost Nova",
+            "Freeze the enemy solid.",
+            AbilityTarget::Enemy,
+            AbilityEffect::Apply(StatusEffect::new(StatusKind::Freeze, 1, 0)),
+            4,
+        )
+        .with_cost(25)
+        .with_cast_time(1000),
+        Ability::new(
+            "Ice Shard",
+            "A shard of ice.",
+            AbilityTarget::Enemy,
+            AbilityEffect::Damage(16),
+            1,
+        )
+        .with_cost(10),
+    ],
+    loot_table: "elemental",
+    experience: 100,
+};
+
+impl_monster!(Ghoul, GHOUL);
+impl_monster!(SkeletonArcher, SKELETON_ARCHER);
+impl_monster!(DireWolf, DIRE_WOLF);
+impl_monster!(CaveSpider, CAVE_SPIDER);
+impl_monster!(Imp, IMP);
+impl_monster!(FrostWraith, FROST_WRAITH);
+
+/// A bestiary entry, The monster's archetype and how to spawn it.
+#[derive(Clone, Copy)]
+pub struct BestiaryEntry {
+    archetype: &'static MonsterArchetype,
+    spawn: fn() -> Box<dyn Char>,
+}
+
+impl BestiaryEntry {
+    pub fn archetype(&self) -> &'static MonsterArchetype {
+        self.archetype
+    }
+
+    /// Spawn a new monster of this entry.
+    pub fn spawn(&self) -> Box<dyn Char> {
+        (self.spawn)()
+    }
+
+    /// The function spawning monsters of this entry.
+    pub fn spawner(&self) -> fn() -> Box<dyn Char> {
+        self.spawn
+    }
+}
+
+fn spawn<M: CharFactory + 'static>() -> Box<dyn Char> {
+    Box::new(M::new())
+}
+
+/// A registry of monsters that can be looked up by id or family.
+///
+/// The default bestiary contains all builtin monsters.
+///
+/// ```
+/// use characters::{Bestiary, MonsterFamily};
+///
+/// let bestiary = Bestiary::default();
+/// let ghoul = bestiary.spawn("ghoul").unwrap();
+/// assert_eq!(ghoul.experience_reward(), 40);
+///
+/// for entry in bestiary.by_family(MonsterFamily::Beast) {
+///     println!("{}", entry.archetype().name);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Bestiary {
+    entries: Vec<BestiaryEntry>,
+}
+
+impl Default for Bestiary {
+    fn default() -> Self {
+        let mut bestiary = Bestiary::empty();
+        bestiary.register::<Ghoul>().unwrap();
+        bestiary.register::<SkeletonArcher>().unwrap();
+        bestiary.register::<DireWolf>().unwrap();
+        bestiary.register::<CaveSpider>().unwrap();
+        bestiary.register::<Imp>().unwrap();
+        bestiary.register::<FrostWraith>().unwrap();
+        bestiary
+    }
+}
+
+impl Bestiary {
+    /// Creates a bestiary with no monsters.
+    pub fn empty() -> Bestiary {
+        Bestiary {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Register a monster type.
+    ///
+    /// Fails if a monster with the same id is already registered.
+    pub fn register<M: Monster + 'static>(&mut self) -> anyhow::Result<()> {
+        let archetype = M::archetype();
+        if self.get(archetype.id).is_some() {
+            return Err(anyhow::anyhow!(
+                "A monster with id {} is already registered",
+                archetype.id
+            ));
+        }
+        self.entries.push(BestiaryEntry {
+            archetype,
+            spawn: spawn::<M>,
+        });
+        Ok(())
+    }
+
+    /// Returns the monster with the given id if any.
+    pub fn get(&self, id: &str) -> Option<&BestiaryEntry> {
+        self.entries.iter().find(|entry| entry.archetype.id == id)
+    }
+
+    /// Spawn the monster with the given id if any.
+    pub fn spawn(&self, id: &str) -> Option<Box<dyn Char>> {
+        self.get(id).map(BestiaryEntry::spawn)
+    }
+
+    /// An iterator over the monsters of the given family.
+    pub fn by_family(&self, family: MonsterFamily) -> impl Iterator<Item = &BestiaryEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.archetype.family == family)
+    }
+
+    /// An iterator over all monsters.
+    pub fn iter(&self) -> impl Iterator<Item = &BestiaryEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn() {
+        let wraith = FrostWraith::new();
+        assert_eq!(wraith.level().level(), 5);
+        assert_eq!(wraith.health().current(), 120);
+        assert_eq!(wraith.health().max(), 120);
+        assert_eq!(wraith.weapon().unwrap().weapon_type(), WeaponType::Rod);
+        assert_eq!(wraith.abilities().len(), 2);
+        assert_eq!(wraith.loot_table().unwrap().id(), "elemental");
+
+        let mut wraith = wraith;
+        wraith.health_mut().drip(30);
+        wraith.health_mut().incr(20).unwrap();
+        assert_eq!(wraith.health().current(), 110);
+
+        let mut spider = CaveSpider::new();
+        assert_eq!(spider.health().max(), 50);
+        spider.health_mut().drip(10);
+        spider.health_mut().incr(20).unwrap();
+        assert_eq!(spider.health().current(), 50);
+
+        let wolf = DireWolf::new();
+        assert!(wolf.weapon().is_none());
+        assert_eq!(wolf.attack_damage(), 1);
+    }
+
+    #[test]
+    fn test_bestiary() {
+        let mut bestiary = Bestiary::default();
+        assert!(bestiary.get("imp").is_some());
+        assert!(bestiary.spawn("dragon").is_none());
+        assert_eq!(bestiary.by_family(MonsterFamily::Undead).count(), 2);
+
+        // Every monster references an existing loot table.
+        assert!(bestiary
+            .iter()
+            .all(|e| e.archetype().loot_table().is_some()));
+
+        assert!(bestiary.register::<Imp>().is_err());
+    }
+}
@@ -0,0 +1,421 @@
+//! Parties of characters that adventure and fight together.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::character::{Char, MetaData};
+use components::Inventory;
+use serde::{Deserialize, Serialize};
+
+/// The default amount of characters a party can hold.
+pub const MAX_PARTY_SIZE: usize = 4;
+
+/// The formation row a party member stands in.
+///
+/// Back row members can't be reached by melee attacks while the front row is still fighting.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Row {
+    #[default]
+    Front,
+    Back,
+}
+
+/// Buffs that apply to every member of a party.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum PartyBuff {
+    /// Increases the damage members deal by a percentage.
+    Damage(u32),
+    /// Increases the members' chance to evade attacks.
+    Evasion(u32),
+    /// Increases the members' initiative.
+    Initiative(u32),
+}
+
+impl MetaData for PartyBuff {
+    fn name(&self) -> &'static str {
+        match self {
+            PartyBuff::Damage(_) => "Battle Cry",
+            PartyBuff::Evasion(_) => "Fleet Footed",
+            PartyBuff::Initiative(_) => "Vigilance",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            PartyBuff::Damage(_) => "The party hits harder.",
+            PartyBuff::Evasion(_) => "The party is harder to hit.",
+            PartyBuff::Initiative(_) => "The party acts sooner.",
+        }
+    }
+}
+
+/// A character in a party.
+#[derive(Clone)]
+pub struct Member {
+    character: Box<dyn Char>,
+    row: Row,
+}
+
+impl Member {
+    pub fn character(&self) -> &dyn Char {
+        self.character.as_ref()
+    }
+
+    pub fn character_mut(&mut self) -> &mut dyn Char {
+        self.character.as_mut()
+    }
+
+    /// The formation row this member stands in.
+    pub fn row(&self) -> Row {
+        self.row
+    }
+
+    pub fn into_character(self) -> Box<dyn Char> {
+        self.character
+    }
+}
+
+/// A group of characters of any type led by one of them.
+///
+/// ```
+/// use characters::{Char, CharFactory, Kain, Party, PartyBuff, Row, Tyr};
+///
+/// let mut party = Party::new();
+/// party.join(Box::new(Tyr::new()), Row::Front).unwrap();
+/// party.join(Box::new(Kain::new()), Row::Back).unwrap();
+/// party.add_buff(PartyBuff::Damage(10));
+///
+/// assert_eq!(party.leader().unwrap().character().kind(), Some("Tyr"));
+/// ```
+#[derive(Clone)]
+pub struct Party {
+    members: Vec<Member>,
+    leader: usize,
+    capacity: usize,
+    shared_inventory: Option<Inventory>,
+    buffs: Vec<PartyBuff>,
+}
+
+impl Default for Party {
+    fn default() -> Self {
+        Party::with_capacity(MAX_PARTY_SIZE)
+    }
+}
+
+impl std::fmt::Display for Party {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Party(members: {}/{}, buffs: {})",
+            self.members.len(),
+            self.capacity,
+            self.buffs.len()
+        )
+    }
+}
+
+impl Party {
+    /// Creates an empty party of up to [`MAX_PARTY_SIZE`] members.
+    pub fn new() -> Party {
+        Party::default()
+    }
+
+    /// Creates an empty party of up to `capacity` members, At least one.
+    pub fn with_capacity(capacity: usize) -> Party {
+        Party {
+            members: Vec::new(),
+            leader: 0,
+            capacity: capacity.max(1),
+            shared_inventory: None,
+            buffs: Vec::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.members.len() >= self.capacity
+    }
+
+    /// Add a character to the party in the given row.
+    ///
+    /// ## Returns
+    /// The index of the new member, Or an error if the party is full.
+    pub fn join(&mut self, character: Box<dyn Char>, row: Row) -> anyhow::Result<usize> {
+        if self.is_full() {
+            return Err(anyhow::anyhow!(
+                "The party is full, It can only hold {} members",
+                self.capacity
+            ));
+        }
+        self.members.push(Member { character, row });
+        Ok(self.members.len() - 1)
+    }
+
+    /// Remove the member at the given index.
+    ///
+    /// If the leader leaves the first member takes the lead.
+    pub fn leave(&mut self, index: usize) -> Option<Member> {
+        if index >= self.members.len() {
+            return None;
+        }
+        if self.leader == index {
+            self.leader = 0;
+        } else if self.leader > index {
+            self.leader -= 1;
+        }
+        Some(self.members.remove(index))
+    }
+
+    /// Remove all members keeping the party's leader, buffs and shared inventory.
+    ///
+    /// Members that join again in the same order restore the party.
+    pub fn drain(&mut self) -> Vec<Member> {
+        self.members.drain(..).collect()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Member> {
+        self.members.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Member> {
+        self.members.get_mut(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Member> {
+        self.members.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Member> {
+        self.members.iter_mut()
+    }
+
+    /// The members standing in the given row and their indices.
+    pub fn row(&self, row: Row) -> impl Iterator<Item = (usize, &Member)> {
+        self.members
+            .iter()
+            .enumerate()
+            .filter(move |(_, member)| member.row == row)
+    }
+
+    /// Move the member at the given index to another row.
+    pub fn set_row(&mut self, index: usize, row: Row) -> anyhow::Result<()> {
+        let member = self
+            .members
+            .get_mut(index)
+            .ok_or_else(|| anyhow::anyhow!("No party member at index {}", index))?;
+        member.row = row;
+        Ok(())
+    }
+
+    /// Returns the party's leader if the party isn't empty.
+    pub fn leader(&self) -> Option<&Member> {
+        self.members.get(self.leader)
+    }
+
+    pub fn leader_index(&self) -> usize {
+        self.leader
+    }
+
+    /// Make the member at the given index the party's leader.
+    pub fn set_leader(&mut self, index: usize) -> anyhow::Result<()> {
+        if index >= self.members.len() {
+            return Err(anyhow::anyhow!("No party member at index {}", index));
+        }
+        self.leader = index;
+        Ok(())
+    }
+
+    /// Whether every member of the party is dead.
+    pub fn is_defeated(&self) -> bool {
+        self.members
+            .iter()
+            .all(|member| member.character.health().is_killed())
+    }
+
+    /// Share an inventory between all members, Replacing the previously shared one.
+    ///
+    /// Members keep their own inventories but use the shared one while it's set.
+    pub fn share_inventory(&mut self, inventory: Inventory) -> Option<Inventory> {
+        self.shared_inventory.replace(inventory)
+    }
+
+    /// Stop sharing an inventory, Returning it.
+    pub fn unshare_inventory(&mut self) -> Option<Inventory> {
+        self.shared_inventory.take()
+    }
+
+    pub fn shared_inventory(&self) -> Option<&Inventory> {
+        self.shared_inventory.as_ref()
+    }
+
+    pub fn shared_inventory_mut(&mut self) -> Option<&mut Inventory> {
+        self.shared_inventory.as_mut()
+    }
+
+    /// The inventory the member at the given index uses, The shared one if any.
+    pub fn inventory(&self, index: usize) -> Option<&Inventory> {
+        let member = self.members.get(index)?;
+        Some(
+            self.shared_inventory
+                .as_ref()
+                .unwrap_or_else(|| member.character.inventory()),
+        )
+    }
+
+    /// A mutable reference to the inventory the member at the given index uses.
+    pub fn inventory_mut(&mut self, index: usize) -> Option<&mut Inventory> {
+        let member = self.members.get_mut(index)?;
+        match self.shared_inventory.as_mut() {
+            Some(shared) => Some(shared),
+            None => Some(member.character.inventory_mut()),
+        }
+    }
+
+    pub fn buffs(&self) -> &[PartyBuff] {
+        &self.buffs
+    }
+
+    pub fn add_buff(&mut self, buff: PartyBuff) {
+        self.buffs.push(buff);
+    }
+
+    /// Remove a buff, Returning whether the party had it.
+    pub fn remove_buff(&mut self, buff: PartyBuff) -> bool {
+        match self.buffs.iter().position(|b| *b == buff) {
+            Some(index) => {
+                self.buffs.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The total damage percentage bonus of the party's buffs.
+    pub fn damage_bonus(&self) -> u32 {
+        self.sum_buffs(|buff| match buff {
+            PartyBuff::Damage(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    /// The total evasion bonus of the party's buffs.
+    pub fn evasion_bonus(&self) -> u32 {
+        self.sum_buffs(|buff| match buff {
+            PartyBuff::Evasion(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    /// The total initiative bonus of the party's buffs.
+    pub fn initiative_bonus(&self) -> u32 {
+        self.sum_buffs(|buff| match buff {
+            PartyBuff::Initiative(amount) => amount,
+            _ => 0,
+        })
+    }
+
+    fn sum_buffs(&self, f: impl Fn(PartyBuff) -> u32) -> u32 {
+        self.buffs
+            .iter()
+            .map(|buff| f(*buff))
+            .fold(0, u32::saturating_add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CharFactory, Ghoul, Kain, Susanoo, Tyr, Vamp};
+    use components::consumable::{Consumable, ConsumableType};
+
+    #[test]
+    fn test_members() {
+        let mut party = Party::new();
+        party.join(Box::new(Tyr::new()), Row::Front).unwrap();
+        party.join(Box::new(Kain::new()), Row::Back).unwrap();
+        party.join(Box::new(Ghoul::new()), Row::Front).unwrap();
+        party.join(Box::new(Vamp::new()), Row::Back).unwrap();
+        assert!(party.join(Box::new(Susanoo::new()), Row::Back).is_err());
+
+        assert_eq!(party.row(Row::Back).count(), 2);
+        party.set_leader(2).unwrap();
+        assert!(party.set_leader(4).is_err());
+
+        party.leave(0).unwrap();
+        assert_eq!(party.leader().unwrap().character().kind(), Some("ghoul"));
+        party.leave(1).unwrap();
+        assert_eq!(party.leader().unwrap().character().kind(), Some("Kain"));
+        assert!(!party.is_defeated());
+    }
+
+    #[test]
+    fn test_shared_inventory() {
+        let mut party = Party::new();
+        party.join(Box::new(Tyr::new()), Row::Front).unwrap();
+        party.join(Box::new(Kain::new()), Row::Back).unwrap();
+
+        party.share_inventory(Inventory::new());
+        party
+            .inventory_mut(0)
+            .unwrap()
+            .put_consumable(Consumable::new(ConsumableType::Elixir))
+            .unwrap();
+        assert_eq!(party.inventory(1).unwrap().get_consumables().len(), 1);
+
+        let shared = party.unshare_inventory().unwrap();
+        assert_eq!(shared.get_consumables().len(), 1);
+        assert!(party.inventory(1).unwrap().get_consumables().is_empty());
+    }
+
+    #[test]
+    fn test_buffs() {
+        let mut party = Party::new();
+        party.add_buff(PartyBuff::Damage(10));
+        party.add_buff(PartyBuff::Damage(15));
+        party.add_buff(PartyBuff::Evasion(u32::MAX));
+        assert_eq!(party.damage_bonus(), 25);
+        assert_eq!(party.evasion_bonus(), u32::MAX);
+        assert_eq!(party.initiative_bonus(), 0);
+
+        assert!(party.remove_buff(PartyBuff::Damage(10)));
+        assert!(!party.remove_buff(PartyBuff::Damage(10)));
+        assert_eq!(party.damage_bonus(), 15);
+    }
+}
@@ -0,0 +1,736 @@
+//! Quests, Their objectives, progress and rewards.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::character::{Char, MetaData};
+use crate::events::{is_player, level_ups};
+use crate::level::Level;
+use components::{EventBus, GameEvent, ItemRarity, ItemType, Loot, LootTable};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt};
+
+/// ## What a quest asks for.
+/// - [`Objective::Kill`]
+///     - Defeat an amount of characters of a kind, See [`Char::kind`].
+/// - [`Objective::Collect`]
+///     - Pick up an amount of items of a type, And of a rarity if given.
+/// - [`Objective::ReachLevel`]
+///     - Reach a level.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Objective {
+    Kill {
+        target: &'static str,
+        count: u32,
+    },
+    Collect {
+        item_type: ItemType,
+        rarity: Option<ItemRarity>,
+        count: u32,
+    },
+    ReachLevel(u32),
+}
+
+impl Objective {
+    /// The progress needed to complete this objective.
+    pub fn required(&self) -> u32 {
+        match self {
+            Objective::Kill { count, .. } | Objective::Collect { count, .. } => *count,
+            Objective::ReachLevel(level) => *level,
+        }
+    }
+
+    /// Returns this objective's progress after the event of the given players.
+    fn advance(&self, progress: u32, event: &GameEvent, players: &[String]) -> u32 {
+        let player = |name: &str| is_player(players, name);
+        match (self, event) {
+            (Objective::Kill { target, .. }, GameEvent::Died { target: died })
+                if target == died && (players.is_empty() || !player(died)) =>
+            {
+                progress + 1
+            }
+            (
+                Objective::Collect {
+                    item_type, rarity, ..
+                },
+                GameEvent::ItemPickedUp { owner, item },
+            ) if player(owner)
+                && item.item().item_type() == *item_type
+                && rarity.is_none_or(|r| item.item().rarity() == r) =>
+            {
+                progress + 1
+            }
+            (Objective::ReachLevel(_), GameEvent::LevelUp { target, level }) if player(target) => {
+                progress.max(*level)
+            }
+            _ => progress,
+        }
+        .min(self.required())
+    }
+}
+
+/// What completing a quest rewards.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Reward {
+    experience: u32,
+    gold: u64,
+    loot_table: Option<&'static str>,
+}
+
+impl Reward {
+    pub const fn new(experience: u32, gold: u64) -> Reward {
+        Reward {
+            experience,
+            gold,
+            loot_table: None,
+        }
+    }
+
+    /// Roll the loot table with the given id too.
+    pub const fn with_loot(mut self, loot_table: &'static str) -> Reward {
+        self.loot_table = Some(loot_table);
+        self
+    }
+
+    pub fn experience(&self) -> u32 {
+        self.experience
+    }
+
+    pub fn gold(&self) -> u64 {
+        self.gold
+    }
+
+    pub fn loot_table(&self) -> Option<&'static LootTable> {
+        self.loot_table.and_then(LootTable::get)
+    }
+
+    /// Give this reward to the character.
+    ///
+    /// Nothing is given if the rolled loot doesn't fit in the character's inventory.
+    /// A [`GameEvent::LevelUp`] is queued for every level gained, Dispatch the bus to handle them.
+    ///
+    /// ## Returns
+    /// The items rolled from the loot table.
+    pub fn grant<R: Rng + ?Sized>(
+        &self,
+        character: &mut dyn Char,
+        events: &mut EventBus,
+        rng: &mut R,
+    ) -> anyhow::Result<Vec<Loot>> {
+        let loot = self
+            .loot_table()
+            .map(|table| table.roll(rng))
+            .unwrap_or_default();
+        let mut inventory = character.inventory().clone();
+        let mut wallet = *character.wallet();
+        for item in &loot {
+            inventory.put_loot(*item)?;
+        }
+        wallet.deposit(self.gold)?;
+
+        *character.inventory_mut() = inventory;
+        *character.wallet_mut() = wallet;
+        let start = character.level().level();
+        let gained = character.level_mut().gain(self.experience);
+        events.extend(level_ups(character.event_name(), start, gained));
+        Ok(loot)
+    }
+}
+
+/// A quest, Quests that require other quests form chains.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Quest {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    objectives: &'static [Objective],
+    requires: &'static [&'static str],
+    min_level: u32,
+    reward: Reward,
+}
+
+impl fmt::Display for Quest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Quest(name: {}, objectives: {}, description: {})",
+            self.name,
+            self.objectives.len(),
+            self.description
+        )
+    }
+}
+
+impl MetaData for Quest {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+}
+
+impl Quest {
+    /// Creates a new quest available from level 1 with no prerequisites.
+    pub const fn new(
+        id: &'static str,
+        name: &'static str,
+        description: &'static str,
+        objectives: &'static [Objective],
+        reward: Reward,
+    ) -> Quest {
+        Quest {
+            id,
+            name,
+            description,
+            objectives,
+            requires: &[],
+            min_level: 1,
+            reward,
+        }
+    }
+
+    /// Set the ids of the quests that must be completed before this one.
+    pub const fn with_requires(mut self, requires: &'static [&'static str]) -> Quest {
+        self.requires = requires;
+        self
+    }
+
+    /// Set the level needed to accept this quest.
+    pub const fn with_min_level(mut self, level: u32) -> Quest {
+        self.min_level = level;
+        self
+    }
+
+    /// Returns the quest with the given id if any.
+    pub fn get(id: &str) -> Option<&'static Quest> {
+        QUESTS.iter().find(|q| q.id == id)
+    }
+
+    /// Every quest.
+    pub fn all() -> &'static [Quest] {
+        &QUESTS
+    }
+
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn objectives(&self) -> &'static [Objective] {
+        self.objectives
+    }
+
+    /// The ids of the quests that must be completed before this one.
+    pub fn requires(&self) -> &'static [&'static str] {
+        self.requires
+    }
+
+    pub fn min_level(&self) -> u32 {
+        self.min_level
+    }
+
+    pub fn reward(&self) -> Reward {
+        self.reward
+    }
+}
+
+static QUESTS: [Quest; 5] = [
+    Quest::new(
+        "pest_control",
+        "Pest Control",
+        "Spiders nest in the cellar again, Clear them out.",
+        &[Objective::Kill {
+            target: "cave_spider",
+            count: 3,
+        }],
+        Reward::new(100, 20),
+    ),
+    Quest::new(
+        "grave_robbers",
+        "Grave Robbers",
+        "Something crawled out of the cellar and into the graveyard.",
+        &[
+            Objective::Kill {
+                target: "ghoul",
+                count: 2,
+            },
+            Objective::Kill {
+                target: "skeleton_archer",
+                count: 2,
+            },
+        ],
+        Reward::new(250, 50).with_loot("undead"),
+    )
+    .with_requires(&["pest_control"]),
+    Quest::new(
+        "cold_case",
+        "Cold Case",
+        "The graves were opened by something colder than death.",
+        &[
+            Objective::ReachLevel(4),
+            Objective::Kill {
+                target: "frost_wraith",
+                count: 1,
+            },
+        ],
+        Reward::new(500, 150).with_loot("elemental"),
+    )
+    .with_requires(&["grave_robbers"])
+    .with_min_level(3),
+    Quest::new(
+        "scavenger",
+        "Scavenger",
+        "The smith pays for any scraps of material brought in.",
+        &[Objective::Collect {
+            item_type: ItemType::Material,
+            rarity: None,
+            count: 5,
+        }],
+        Reward::new(50, 30),
+    ),
+    Quest::new(
+        "treasure_hunter",
+        "Treasure Hunter",
+        "Find a weapon worthy of legends.",
+        &[Objective::Collect {
+            item_type: ItemType::Weapon,
+            rarity: Some(ItemRarity::Exotic),
+            count: 1,
+        }],
+        Reward::new(200, 200),
+    )
+    .with_min_level(5),
+];
+
+/// An accepted quest and the progress of each of its objectives.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct QuestProgress {
+    quest: &'static Quest,
+    progress: Vec<u32>,
+}
+
+impl QuestProgress {
+    pub fn quest(&self) -> &'static Quest {
+        self.quest
+    }
+
+    /// The progress of each objective in the order of [`Quest::objectives`].
+    pub fn progress(&self) -> &[u32] {
+        &self.progress
+    }
+
+    /// Whether every objective is done.
+    pub fn is_complete(&self) -> bool {
+        self.quest
+            .objectives
+            .iter()
+            .zip(&self.progress)
+            .all(|(objective, progress)| *progress >= objective.required())
+    }
+}
+
+/// The saved state of a quest log, Quests are saved by their ids.
+#[derive(Serialize, Deserialize)]
+struct QuestLogRecord {
+    #[serde(default)]
+    players: Vec<String>,
+    active: Vec<(String, Vec<u32>)>,
+    completed: Vec<String>,
+}
+
+/// The quests a player accepted and completed.
+///
+/// Progress is fed by gameplay events of the tracked players, Subscribe the log to an event bus to track it.
+/// Every character is a player if none are tracked, Kills count the deaths of characters that aren't players.
+///
+/// ```
+/// use characters::{Char, CharFactory, Observed, QuestLog, Tyr};
+/// use components::{EventBus, Loot, Material, MaterialType};
+/// use std::sync::{Arc, Mutex};
+///
+/// let mut tyr = Tyr::new();
+/// let quests = Arc::new(Mutex::new(QuestLog::new()));
+/// quests.lock().unwrap().accept("scavenger", tyr.level()).unwrap();
+///
+/// let mut events = EventBus::new();
+/// let log = quests.clone();
+/// events.subscribe(move |event| {
+///     log.lock().unwrap().handle(event);
+/// });
+///
+/// for _ in 0..5 {
+///     let scrap = Loot::Material(Material::new(MaterialType::Scrap));
+///     Observed::new(&mut tyr, &mut events).pick_up(scrap).unwrap();
+/// }
+///
+/// let mut rng = rand::thread_rng();
+/// quests
+///     .lock()
+///     .unwrap()
+///     .turn_in("scavenger", &mut tyr, &mut events, &mut rng)
+///     .unwrap();
+/// assert_eq!(tyr.wallet().gold(), 30);
+/// assert_eq!(events.dispatch(), 0);
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "QuestLogRecord", into = "QuestLogRecord")]
+pub struct QuestLog {
+    players: Vec<String>,
+    active: Vec<QuestProgress>,
+    completed: Vec<&'static str>,
+}
+
+impl TryFrom<QuestLogRecord> for QuestLog {
+    type Error = anyhow::Error;
+
+    fn try_from(record: QuestLogRecord) -> anyhow::Result<QuestLog> {
+        let quest =
+            |id: &str| Quest::get(id).ok_or_else(|| anyhow::anyhow!("Unknown quest {}", id));
+        let mut log = QuestLog::new();
+        log.players = record.players;
+        for id in &record.completed {
+            log.completed.push(quest(id)?.id);
+        }
+        for (id, progress) in record.active {
+            let quest = quest(&id)?;
+            if progress.len() != quest.objectives.len() {
+                return Err(anyhow::anyhow!(
+                    "Quest {} has {} objectives",
+                    id,
+                    quest.objectives.len()
+                ));
+            }
+            log.active.push(QuestProgress { quest, progress });
+        }
+        Ok(log)
+    }
+}
+
+impl From<QuestLog> for QuestLogRecord {
+    fn from(log: QuestLog) -> QuestLogRecord {
+        QuestLogRecord {
+            players: log.players.clone(),
+            active: log
+                .active
+                .iter()
+                .map(|p| (p.quest.id.to_string(), p.progress.clone()))
+                .collect(),
+            completed: log.completed.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+}
+
+impl QuestLog {
+    /// Creates a new empty quest log.
+    pub fn new() -> QuestLog {
+        Self::default()
+    }
+
+    /// Track the events of the character with the given event name as a player's, See [`Char::event_name`].
+    pub fn track(&mut self, player: &str) {
+        if !self.players.iter().any(|p| p == player) {
+            self.players.push(player.to_string());
+        }
+    }
+
+    /// The event names of the tracked players.
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    pub fn active(&self) -> &[QuestProgress] {
+        &self.active
+    }
+
+    /// Returns the progress of the accepted quest with the given id if any.
+    pub fn get(&self, id: &str) -> Option<&QuestProgress> {
+        self.active.iter().find(|p| p.quest.id == id)
+    }
+
+    /// The ids of the completed quests in the order they were turned in.
+    pub fn completed(&self) -> &[&'static str] {
+        &self.completed
+    }
+
+    pub fn is_completed(&self, id: &str) -> bool {
+        self.completed.contains(&id)
+    }
+
+    /// Check whether the quest can be accepted at the given level.
+    pub fn check_accept(&self, quest: &Quest, level: &Level) -> anyhow::Result<()> {
+        if self.is_completed(quest.id) || self.get(quest.id).is_some() {
+            return Err(anyhow::anyhow!("Quest {} was already accepted", quest.name));
+        }
+        if level.level() < quest.min_level {
+            return Err(anyhow::anyhow!(
+                "Quest {} needs level {}",
+                quest.name,
+                quest.min_level
+            ));
+        }
+        if let Some(missing) = quest.requires.iter().find(|id| !self.is_completed(id)) {
+            return Err(anyhow::anyhow!(
+                "Quest {} needs quest {} completed first",
+                quest.name,
+                missing
+            ));
+        }
+        Ok(())
+    }
+
+    /// The quests that can be accepted at the given level.
+    pub fn available<'a>(&'a self, level: &'a Level) -> impl Iterator<Item = &'static Quest> + 'a {
+        Quest::all()
+            .iter()
+            .filter(move |q| self.check_accept(q, level).is_ok())
+    }
+
+    /// Accept the quest with the given id at the given level.
+    ///
+    /// Level objectives start at the given level.
+    pub fn accept(&mut self, id: &str, level: &Level) -> anyhow::Result<()> {
+        let quest = Quest::get(id).ok_or_else(|| anyhow::anyhow!("Unknown quest {}", id))?;
+        self.check_accept(quest, level)?;
+        let progress = quest
+            .objectives
+            .iter()
+            .map(|objective| match objective {
+                Objective::ReachLevel(required) => level.level().min(*required),
+                _ => 0,
+            })
+            .collect();
+        self.active.push(QuestProgress { quest, progress });
+        Ok(())
+    }
+
+    /// Drop an accepted quest and its progress.
+    pub fn abandon(&mut self, id: &str) -> anyhow::Result<()> {
+        let index = self
+            .active
+            .iter()
+            .position(|p| p.quest.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Quest {} isn't accepted", id))?;
+        self.active.remove(index);
+        Ok(())
+    }
+
+    /// Advance the accepted quests' objectives by the event if it's of a tracked player.
+    ///
+    /// ## Returns
+    /// The ids of the quests the event completed.
+    pub fn handle(&mut self, event: &GameEvent) -> Vec<&'static str> {
+        let mut completed = Vec::new();
+        for active in &mut self.active {
+            let was_complete = active.is_complete();
+            for (objective, progress) in active.quest.objectives.iter().zip(&mut active.progress) {
+                *progress = objective.advance(*progress, event, &self.players);
+            }
+            if !was_complete && active.is_complete() {
+                completed.push(active.quest.id);
+            }
+        }
+        completed
+    }
+
+    /// Turn in a complete quest, Rewarding the character, See [`Reward::grant`].
+    ///
+    /// ## Returns
+    /// The items rolled from the reward's loot table.
+    pub fn turn_in<R: Rng + ?Sized>(
+        &mut self,
+        id: &str,
+        character: &mut dyn Char,
+        events: &mut EventBus,
+        rng: &mut R,
+    ) -> anyhow::Result<Vec<Loot>> {
+        let index = self
+            .active
+            .iter()
+            .position(|p| p.quest.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Quest {} isn't accepted", id))?;
+        let quest = self.active[index].quest;
+        if !self.active[index].is_complete() {
+            return Err(anyhow::anyhow!("Quest {} isn't complete", quest.name));
+        }
+
+        let loot = quest.reward.grant(character, events, rng)?;
+        self.active.remove(index);
+        self.completed.push(quest.id);
+        Ok(loot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CharFactory, Tyr};
+    use components::{Consumable, ConsumableType, Material, MaterialType, Weapon, WeaponType};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn died(target: &'static str) -> GameEvent {
+        GameEvent::Died { target }
+    }
+
+    #[test]
+    fn test_chain() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut tyr = Tyr::new();
+        let mut events = EventBus::new();
+        let mut log = QuestLog::new();
+        let level = *tyr.level();
+
+        assert!(log.accept("grave_robbers", &level).is_err());
+        assert!(log.available(&level).all(|q| q.id() != "grave_robbers"));
+        log.accept("pest_control", &level).unwrap();
+        assert!(log.accept("pest_control", &level).is_err());
+
+        assert!(log.handle(&died("cave_spider")).is_empty());
+        log.handle(&died("ghoul"));
+        assert!(log
+            .turn_in("pest_control", &mut tyr, &mut events, &mut rng)
+            .is_err());
+        log.handle(&died("cave_spider"));
+        assert_eq!(log.handle(&died("cave_spider")), vec!["pest_control"]);
+        assert_eq!(log.get("pest_control").unwrap().progress(), &[3]);
+
+        log.turn_in("pest_control", &mut tyr, &mut events, &mut rng)
+            .unwrap();
+        assert!(log.is_completed("pest_control"));
+        assert_eq!(tyr.wallet().gold(), 20);
+        assert_eq!(tyr.level().level(), 2);
+        assert_eq!(
+            events.pending().collect::<Vec<_>>(),
+            vec![&GameEvent::LevelUp {
+                target: "Tyr",
+                level: 2
+            }]
+        );
+
+        log.accept("grave_robbers", tyr.level()).unwrap();
+        for target in ["ghoul", "ghoul", "skeleton_archer", "skeleton_archer"].iter() {
+            log.handle(&died(target));
+        }
+        let loot = log
+            .turn_in("grave_robbers", &mut tyr, &mut events, &mut rng)
+            .unwrap();
+        assert!(loot.iter().all(|item| tyr.inventory().count_loot(item) > 0));
+        assert!(log.accept("cold_case", tyr.level()).is_ok());
+    }
+
+    #[test]
+    fn test_objectives() {
+        let mut log = QuestLog::new();
+        let level = Level::new(5);
+        log.accept("treasure_hunter", &level).unwrap();
+        log.accept("scavenger", &level).unwrap();
+
+        let pick_up = |item| GameEvent::ItemPickedUp { owner: "Tyr", item };
+        let rare = Weapon::new(WeaponType::Mace).with_rarity(ItemRarity::Rare);
+        log.handle(&pick_up(Loot::Weapon(rare)));
+        log.handle(&pick_up(Loot::Consumable(Consumable::new(
+            ConsumableType::Elixir,
+        ))));
+        assert!(!log.get("treasure_hunter").unwrap().is_complete());
+        assert_eq!(log.get("scavenger").unwrap().progress(), &[0]);
+
+        let exotic = rare.with_rarity(ItemRarity::Exotic);
+        assert_eq!(
+            log.handle(&pick_up(Loot::Weapon(exotic))),
+            vec!["treasure_hunter"]
+        );
+
+        let mut cold = QuestLog::new();
+        cold.completed = vec!["pest_control", "grave_robbers"];
+        cold.accept("cold_case", &Level::new(3)).unwrap();
+        assert_eq!(cold.get("cold_case").unwrap().progress(), &[3, 0]);
+        cold.handle(&GameEvent::LevelUp {
+            target: "Tyr",
+            level: 4,
+        });
+        assert_eq!(cold.get("cold_case").unwrap().progress(), &[4, 0]);
+        cold.abandon("cold_case").unwrap();
+        assert!(cold.active().is_empty());
+    }
+
+    #[test]
+    fn test_players() {
+        let mut log = QuestLog::new();
+        log.track("Tyr");
+        log.track("Tyr");
+        assert_eq!(log.players(), &["Tyr".to_string()]);
+        let level = Level::new(5);
+        log.accept("pest_control", &level).unwrap();
+        log.accept("scavenger", &level).unwrap();
+
+        log.handle(&died("cave_spider"));
+        log.handle(&died("Tyr"));
+        assert_eq!(log.get("pest_control").unwrap().progress(), &[1]);
+
+        let scrap = Loot::Material(Material::new(MaterialType::Scrap));
+        log.handle(&GameEvent::ItemPickedUp {
+            owner: "Kain",
+            item: scrap,
+        });
+        assert_eq!(log.get("scavenger").unwrap().progress(), &[0]);
+        log.handle(&GameEvent::ItemPickedUp {
+            owner: "Tyr",
+            item: scrap,
+        });
+        assert_eq!(log.get("scavenger").unwrap().progress(), &[1]);
+
+        let mut cold = QuestLog::new();
+        cold.track("Tyr");
+        cold.completed = vec!["pest_control", "grave_robbers"];
+        cold.accept("cold_case", &Level::new(3)).unwrap();
+        cold.handle(&GameEvent::LevelUp {
+            target: "cave_spider",
+            level: 10,
+        });
+        assert_eq!(cold.get("cold_case").unwrap().progress(), &[3, 0]);
+    }
+
+    #[test]
+    fn test_save() {
+        let mut log = QuestLog::new();
+        log.accept("pest_control", &Level::default()).unwrap();
+        log.handle(&died("cave_spider"));
+        log.completed.push("scavenger");
+        log.track("Tyr");
+
+        let json = serde_json::to_string(&log).unwrap();
+        let loaded: QuestLog = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, log);
+        assert!(serde_json::from_str::<QuestLog>(
+            r#"{"active":[["dragon_slayer",[0]]],"completed":[]}"#
+        )
+        .is_err());
+    }
+}
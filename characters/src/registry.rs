@@ -0,0 +1,235 @@
+//! A registry of character types that spawns them as trait objects.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::character::{Char, CharFactory, CharacterClass};
+use crate::custom::CharacterBuilder;
+use crate::monsters::Bestiary;
+use crate::{Kain, Susanoo, Tyr, Vamp};
+use std::sync::Arc;
+
+/// A function that spawns characters of a registered type.
+type Spawner = Arc<dyn Fn() -> Box<dyn Char> + Send + Sync>;
+
+/// A registered character type.
+#[derive(Clone)]
+pub struct RegistryEntry {
+    name: &'static str,
+    class: CharacterClass,
+    spawn: Spawner,
+}
+
+impl RegistryEntry {
+    /// The name the character type is registered by.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The class characters of this type spawn with.
+    pub fn class(&self) -> CharacterClass {
+        self.class
+    }
+
+    /// Spawn a new character of this type.
+    pub fn spawn(&self) -> Box<dyn Char> {
+        (self.spawn)()
+    }
+}
+
+fn spawn<C: CharFactory + 'static>() -> Box<dyn Char> {
+    Box::new(C::new())
+}
+
+/// Character types that can be spawned by name or class without knowing their concrete type.
+///
+/// The default registry contains the builtin characters and every bestiary monster,
+/// Registered by their [`Char::kind`].
+///
+/// ```
+/// use characters::{CharacterClass, CharacterRegistry};
+///
+/// let registry = CharacterRegistry::default();
+/// let vamp = registry.spawn("Vamp").unwrap();
+/// let party: Vec<_> = registry
+///     .by_class(CharacterClass::Warrior)
+///     .map(|entry| entry.spawn())
+///     .collect();
+/// ```
+#[derive(Clone)]
+pub struct CharacterRegistry {
+    entries: Vec<RegistryEntry>,
+}
+
+impl Default for CharacterRegistry {
+    fn default() -> Self {
+        let mut registry = CharacterRegistry::empty();
+        registry.register::<Tyr>("Tyr").unwrap();
+        registry.register::<Kain>("Kain").unwrap();
+        registry.register::<Vamp>("Vamp").unwrap();
+        registry.register::<Susanoo>("Susanoo").unwrap();
+        registry.extend_bestiary(&Bestiary::default()).unwrap();
+        registry
+    }
+}
+
+impl CharacterRegistry {
+    /// Creates a registry with no character types.
+    pub fn empty() -> CharacterRegistry {
+        CharacterRegistry {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Register a character type by name.
+    ///
+    /// Fails if a character type with the same name is already registered.
+    pub fn register<C: CharFactory + 'static>(&mut self, name: &'static str) -> anyhow::Result<()> {
+        let class = *C::new().class();
+        self.insert(RegistryEntry {
+            name,
+            class,
+            spawn: Arc::new(spawn::<C>),
+        })
+    }
+
+    /// Register a character defined by a builder by its name.
+    ///
+    /// The builder is checked to build successfully before it's registered.
+    pub fn register_builder(&mut self, builder: CharacterBuilder) -> anyhow::Result<()> {
+        builder.build()?;
+        self.insert(RegistryEntry {
+            name: builder.name(),
+            class: builder.class(),
+            spawn: Arc::new(move || {
+                // Checked when registered.
+                Box::new(builder.build().unwrap())
+            }),
+        })
+    }
+
+    /// Register every monster of a bestiary by its id.
+    pub fn extend_bestiary(&mut self, bestiary: &Bestiary) -> anyhow::Result<()> {
+        for entry in bestiary.iter() {
+            let archetype = entry.archetype();
+            self.insert(RegistryEntry {
+                name: archetype.id,
+                class: archetype.class,
+                spawn: Arc::new(entry.spawner()),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, entry: RegistryEntry) -> anyhow::Result<()> {
+        if self.get(entry.name).is_some() {
+            return Err(anyhow::anyhow!(
+                "A character named {} is already registered",
+                entry.name
+            ));
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Returns the character type registered by the given name if any.
+    pub fn get(&self, name: &str) -> Option<&RegistryEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Spawn a character of the type registered by the given name if any.
+    pub fn spawn(&self, name: &str) -> Option<Box<dyn Char>> {
+        self.get(name).map(RegistryEntry::spawn)
+    }
+
+    /// An iterator over the character types of the given class.
+    pub fn by_class(&self, class: CharacterClass) -> impl Iterator<Item = &RegistryEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.class == class)
+    }
+
+    /// An iterator over all registered character types.
+    pub fn iter(&self) -> impl Iterator<Item = &RegistryEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let mut registry = CharacterRegistry::default();
+        assert!(registry.register::<Tyr>("Tyr").is_err());
+
+        // Every character type is registered by its kind.
+        for entry in registry.iter() {
+            assert_eq!(entry.spawn().kind(), Some(entry.name()));
+        }
+        assert_eq!(
+            registry.by_class(CharacterClass::Vampire).count(),
+            1,
+            "Only Vamp is a vampire"
+        );
+        assert!(registry.spawn("Dragon").is_none());
+    }
+
+    #[test]
+    fn test_register_builder() {
+        let mut registry = CharacterRegistry::default();
+        let builder = CharacterBuilder::new("Odin", CharacterClass::Warlock).level(20);
+        registry.register_builder(builder.clone()).unwrap();
+        assert!(registry.register_builder(builder).is_err());
+
+        let odin = registry.spawn("Odin").unwrap();
+        assert_eq!(odin.kind(), Some("Odin"));
+        assert_eq!(odin.level().level(), 20);
+        assert!(registry
+            .by_class(CharacterClass::Warlock)
+            .any(|entry| entry.name() == "Odin"));
+    }
+
+    #[test]
+    fn test_clone_box() {
+        let registry = CharacterRegistry::default();
+        let mut characters: Vec<Box<dyn Char>> = vec![
+            registry.spawn("Susanoo").unwrap(),
+            registry.spawn("imp").unwrap(),
+        ];
+        let cloned = characters.clone();
+        characters[0].health_mut().drip(50);
+
+        assert_eq!(cloned[0].health().current(), 100);
+        assert_eq!(characters[0].health().current(), 50);
+        assert_eq!(cloned[1].kind(), Some("imp"));
+        assert_eq!(cloned[1].equipment(), characters[1].equipment());
+    }
+}
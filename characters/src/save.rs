@@ -0,0 +1,364 @@
+//! Serializable save games of parties.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::achievements::Achievements;
+use crate::character::{Char, MetaData};
+use crate::level::Level;
+use crate::party::{Party, PartyBuff, Row};
+use crate::quests::QuestLog;
+use crate::registry::CharacterRegistry;
+use components::{Equipment, Health, Inventory, Mana, Stash, Wallet};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// The current save game format version.
+pub const SAVE_VERSION: u32 = 1;
+
+/// The saved state of a character.
+///
+/// Stats, resistances and abilities come from the character's type and aren't saved,
+/// Learned talents are saved by their ids in the order they were learned.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CharacterSave {
+    pub kind: String,
+    pub health: Health,
+    pub mana: Mana,
+    pub level: Level,
+    pub inventory: Inventory,
+    pub equipment: Equipment,
+    #[serde(default)]
+    pub wallet: Wallet,
+    #[serde(default)]
+    pub talents: Vec<String>,
+}
+
+impl CharacterSave {
+    /// Save a character, Fails if it has no [`Char::kind`].
+    pub fn new(character: &dyn Char) -> anyhow::Result<CharacterSave> {
+        let kind = character.kind().ok_or_else(|| {
+            anyhow::anyhow!("{} characters can't be saved", character.class().name())
+        })?;
+        Ok(CharacterSave {
+            kind: kind.to_string(),
+            health: *character.health(),
+            mana: *character.mana(),
+            level: *character.level(),
+            inventory: character.inventory().clone(),
+            equipment: *character.equipment(),
+            wallet: *character.wallet(),
+            talents: character
+                .talents()
+                .map(|talents| talents.learned().map(|t| t.id().to_string()).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Spawn the saved character with the given spawner and restore its state.
+    ///
+    /// Fails if the saved talents aren't a legal build for the character at its saved level.
+    pub fn restore<F>(&self, spawn: F) -> anyhow::Result<Box<dyn Char>>
+    where
+        F: Fn(&str) -> Option<Box<dyn Char>>,
+    {
+        let mut character = spawn(&self.kind)
+            .ok_or_else(|| anyhow::anyhow!("Unknown character kind {}", self.kind))?;
+        *character.health_mut() = self.health;
+        *character.mana_mut() = self.mana;
+        *character.level_mut() = self.level;
+        *character.inventory_mut() = self.inventory.clone();
+        *character.equipment_mut() = self.equipment;
+        *character.wallet_mut() = self.wallet;
+        if !self.talents.is_empty() {
+            let level = self.level;
+            character
+                .talents_mut()
+                .ok_or_else(|| anyhow::anyhow!("{} characters can't learn talents", self.kind))?
+                .restore(&self.talents, &level)?;
+        }
+        Ok(character)
+    }
+}
+
+/// The saved state of a party.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartySave {
+    pub capacity: usize,
+    pub leader: usize,
+    pub members: Vec<(Row, CharacterSave)>,
+    pub shared_inventory: Option<Inventory>,
+    pub buffs: Vec<PartyBuff>,
+}
+
+impl PartySave {
+    pub fn new(party: &Party) -> anyhow::Result<PartySave> {
+        let members = party
+            .iter()
+            .map(|member| Ok((member.row(), CharacterSave::new(member.character())?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(PartySave {
+            capacity: party.capacity(),
+            leader: party.leader_index(),
+            members,
+            shared_inventory: party.shared_inventory().cloned(),
+            buffs: party.buffs().to_vec(),
+        })
+    }
+
+    /// Restore the party spawning its members with the given spawner.
+    pub fn restore<F>(&self, spawn: F) -> anyhow::Result<Party>
+    where
+        F: Fn(&str) -> Option<Box<dyn Char>>,
+    {
+        let mut party = Party::with_capacity(self.capacity);
+        for (row, member) in &self.members {
+            party.join(member.restore(&spawn)?, *row)?;
+        }
+        if !party.is_empty() {
+            party.set_leader(self.leader)?;
+        }
+        if let Some(inventory) = &self.shared_inventory {
+            party.share_inventory(inventory.clone());
+        }
+        for buff in &self.buffs {
+            party.add_buff(*buff);
+        }
+        Ok(party)
+    }
+}
+
+/// A save game, Stored as JSON.
+///
+/// The account's [`Stash`], [`QuestLog`] and [`Achievements`] are saved along with the party,
+/// See [`SaveGame::with_stash`], [`SaveGame::with_quests`] and [`SaveGame::with_achievements`].
+///
+/// Characters are restored by their [`Char::kind`] from a [`CharacterRegistry`],
+/// Custom characters need to be registered in the registry passed to [`SaveGame::restore_with`].
+///
+/// ```
+/// use characters::{CharFactory, Party, Row, SaveGame, Vamp};
+///
+/// let mut party = Party::new();
+/// party.join(Box::new(Vamp::new()), Row::Front).unwrap();
+///
+/// let json = SaveGame::new(&party).unwrap().to_json().unwrap();
+/// let party = SaveGame::from_json(&json).unwrap().restore().unwrap();
+/// assert_eq!(party.len(), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub party: PartySave,
+    #[serde(default)]
+    pub stash: Stash,
+    #[serde(default)]
+    pub quests: QuestLog,
+    #[serde(default)]
+    pub achievements: Achievements,
+}
+
+impl SaveGame {
+    /// Save the state of a party.
+    pub fn new(party: &Party) -> anyhow::Result<SaveGame> {
+        Ok(SaveGame {
+            version: SAVE_VERSION,
+            party: PartySave::new(party)?,
+            stash: Stash::new(),
+            quests: QuestLog::new(),
+            achievements: Achievements::new(),
+        })
+    }
+
+    /// Save the state of the account's stash along with the party.
+    pub fn with_stash(mut self, stash: &Stash) -> SaveGame {
+        self.stash = stash.clone();
+        self
+    }
+
+    /// Save the quests accepted and completed along with the party.
+    pub fn with_quests(mut self, quests: &QuestLog) -> SaveGame {
+        self.quests = quests.clone();
+        self
+    }
+
+    /// Save the progress towards achievements along with the party.
+    pub fn with_achievements(mut self, achievements: &Achievements) -> SaveGame {
+        self.achievements = achievements.clone();
+        self
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a save game, Fails if it was saved by an unsupported version.
+    pub fn from_json(json: &str) -> anyhow::Result<SaveGame> {
+        let save: SaveGame = serde_json::from_str(json)?;
+        if save.version > SAVE_VERSION {
+            return Err(anyhow::anyhow!(
+                "Save game version {} is newer than the supported version {}",
+                save.version,
+                SAVE_VERSION
+            ));
+        }
+        Ok(save)
+    }
+
+    /// Write the save game to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Read a save game from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<SaveGame> {
+        SaveGame::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Restore the saved party of builtin characters and bestiary monsters.
+    pub fn restore(&self) -> anyhow::Result<Party> {
+        self.restore_with(&CharacterRegistry::default())
+    }
+
+    /// Restore the saved party spawning characters from the given registry.
+    pub fn restore_with(&self, registry: &CharacterRegistry) -> anyhow::Result<Party> {
+        self.party.restore(|kind| registry.spawn(kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CharFactory, FrostWraith, Kain, PartyBuff, Tyr, Vamp};
+    use components::{
+        consumable::{Consumable, ConsumableType},
+        ItemType,
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let mut tyr = Tyr::new();
+        tyr.health_mut().drip(40);
+        tyr.level_mut().gain(300);
+        tyr.wallet_mut().deposit(250).unwrap();
+        tyr.learn_talent("iron_skin").unwrap();
+        tyr.learn_talent("battle_trance").unwrap();
+        tyr.inventory_mut()
+            .put_consumable(Consumable::new(ConsumableType::Antidote))
+            .unwrap();
+
+        let mut party = Party::with_capacity(3);
+        party.join(Box::new(Kain::new()), Row::Back).unwrap();
+        party.join(Box::new(tyr.clone()), Row::Front).unwrap();
+        party.join(Box::new(FrostWraith::new()), Row::Back).unwrap();
+        party.set_leader(1).unwrap();
+        party.share_inventory(Inventory::new());
+        party.add_buff(PartyBuff::Initiative(5));
+
+        let save = SaveGame::new(&party).unwrap();
+        let loaded = SaveGame::from_json(&save.to_json().unwrap()).unwrap();
+        assert_eq!(loaded, save);
+
+        let restored = loaded.restore().unwrap();
+        assert_eq!(restored.capacity(), 3);
+        assert_eq!(restored.buffs(), &[PartyBuff::Initiative(5)]);
+        assert!(restored.shared_inventory().is_some());
+
+        let leader = restored.leader().unwrap();
+        assert_eq!(leader.row(), Row::Front);
+        assert_eq!(leader.character().health(), tyr.health());
+        assert_eq!(leader.character().level(), tyr.level());
+        assert_eq!(leader.character().inventory(), tyr.inventory());
+        assert_eq!(leader.character().equipment(), tyr.equipment());
+        assert_eq!(leader.character().talents(), tyr.talents());
+        assert_eq!(leader.character().wallet().gold(), 250);
+        assert_eq!(
+            restored.get(2).unwrap().character().kind(),
+            Some("frost_wraith")
+        );
+    }
+
+    #[test]
+    fn test_stash() {
+        let mut vamp = Vamp::new();
+        let mut kain = Kain::new();
+        vamp.inventory_mut()
+            .put_consumable(Consumable::new(ConsumableType::Elixir))
+            .unwrap();
+
+        let mut stash = Stash::new();
+        let tab = stash.add_tab("Shared", 10);
+        stash
+            .deposit(tab, vamp.inventory_mut(), ItemType::Consumable, 0)
+            .unwrap();
+
+        let mut party = Party::new();
+        party.join(Box::new(vamp), Row::Front).unwrap();
+        let mut achievements = Achievements::new();
+        achievements.handle(&components::GameEvent::Revived { target: "Vamp" });
+        let mut quests = QuestLog::new();
+        quests.accept("pest_control", &Level::default()).unwrap();
+        let json = SaveGame::new(&party)
+            .unwrap()
+            .with_stash(&stash)
+            .with_quests(&quests)
+            .with_achievements(&achievements)
+            .to_json()
+            .unwrap();
+        let save = SaveGame::from_json(&json).unwrap();
+        assert_eq!(save.quests, quests);
+        assert_eq!(save.achievements, achievements);
+        let mut loaded = save.stash;
+        assert_eq!(loaded, stash);
+
+        loaded
+            .withdraw(tab, kain.inventory_mut(), ItemType::Consumable, 0)
+            .unwrap();
+        assert_eq!(kain.inventory().get_consumables().len(), 1);
+        assert!(loaded.is_empty());
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let mut save = SaveGame::new(&Party::new()).unwrap();
+        save.version = SAVE_VERSION + 1;
+        assert!(SaveGame::from_json(&save.to_json().unwrap()).is_err());
+
+        save.version = SAVE_VERSION;
+        save.party
+            .members
+            .push((Row::Front, CharacterSave::new(&Vamp::new()).unwrap()));
+        save.party.members[0].1.talents = vec!["iron_skin".to_string()];
+        assert!(save.restore().is_err());
+
+        save.party.members[0].1.kind = "Dragon".to_string();
+        assert!(save.restore().is_err());
+    }
+}
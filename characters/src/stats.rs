@@ -28,18 +28,49 @@
 // OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::fmt;
+use components::weapon::WeaponAmmoType;
+use std::{convert::TryFrom, fmt};
+
+/// The highest damage reduction percentage a resistance can provide.
+const MAX_RESISTANCE: u32 = 75;
+
+/// The stats of [`Stats`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stat {
+    Mp5,
+    Hp5,
+    Health,
+    Evasion,
+    MovementSpeed,
+    AttackSpeed,
+}
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Stat::Mp5 => "MP5",
+            Stat::Hp5 => "HP5",
+            Stat::Health => "Health",
+            Stat::Evasion => "Evasion",
+            Stat::MovementSpeed => "Movement Speed",
+            Stat::AttackSpeed => "Attack Speed",
+        };
+        write!(f, "{}", name)
+    }
+}
 
 #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
 pub struct Stats {
     pub mp5: u32,
     pub hp5: u32,
+    /// Max health on top of the health bar's own, See [`crate::Char::learn_talent`].
     pub health: u32,
     pub evasion: u32,
     pub movement_speed: u32,
     pub attack_speed: usize,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for Stats {
     fn default() -> Self {
         Stats {
@@ -71,7 +102,45 @@ impl fmt::Display for Stats {
     }
 }
 
-impl Stats {}
+impl Stats {
+    /// Returns the value of the given stat.
+    pub fn get(&self, stat: Stat) -> u32 {
+        match stat {
+            Stat::Mp5 => self.mp5,
+            Stat::Hp5 => self.hp5,
+            Stat::Health => self.health,
+            Stat::Evasion => self.evasion,
+            Stat::MovementSpeed => self.movement_speed,
+            Stat::AttackSpeed => u32::try_from(self.attack_speed).unwrap_or(u32::MAX),
+        }
+    }
+
+    /// Increase the given stat by an amount.
+    pub fn add(&mut self, stat: Stat, amount: u32) {
+        match stat {
+            Stat::Mp5 => self.mp5 = self.mp5.saturating_add(amount),
+            Stat::Hp5 => self.hp5 = self.hp5.saturating_add(amount),
+            Stat::Health => self.health = self.health.saturating_add(amount),
+            Stat::Evasion => self.evasion = self.evasion.saturating_add(amount),
+            Stat::MovementSpeed => self.movement_speed = self.movement_speed.saturating_add(amount),
+            Stat::AttackSpeed => {
+                self.attack_speed = self
+                    .attack_speed
+                    .saturating_add(usize::try_from(amount).unwrap_or(usize::MAX))
+            }
+        }
+    }
+
+    /// The initiative used to order turns in a battle.
+    ///
+    /// Attack speed weights twice as much as movement speed.
+    pub fn initiative(&self) -> u32 {
+        u32::try_from(self.attack_speed)
+            .unwrap_or(u32::MAX)
+            .saturating_mul(2)
+            .saturating_add(self.movement_speed)
+    }
+}
 
 #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
 pub struct Resistense {
@@ -97,6 +166,7 @@ impl fmt::Display for Resistense {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for Resistense {
     fn default() -> Self {
         Resistense {
@@ -108,4 +178,31 @@ impl Default for Resistense {
     }
 }
 
-impl Resistense {}
+impl Resistense {
+    pub const fn new(toxin: u32, elemental: u32, void: u32, radiant: u32) -> Resistense {
+        Resistense {
+            toxcin: toxin,
+            elemental,
+            void,
+            radiant,
+        }
+    }
+
+    /// Returns the resistance against the given ammo type.
+    pub fn against(&self, ammo_type: WeaponAmmoType) -> u32 {
+        match ammo_type {
+            WeaponAmmoType::Toxic => self.toxcin,
+            WeaponAmmoType::Elemental => self.elemental,
+            WeaponAmmoType::Void => self.void,
+            WeaponAmmoType::Radiant => self.radiant,
+        }
+    }
+
+    /// Reduce damage of the given ammo type.
+    ///
+    /// Resistance is a reduction percentage capped at 75.
+    pub fn mitigate(&self, damage: u32, ammo_type: WeaponAmmoType) -> u32 {
+        let resistance = self.against(ammo_type).min(MAX_RESISTANCE);
+        damage * (100 - resistance) / 100
+    }
+}
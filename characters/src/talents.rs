@@ -0,0 +1,584 @@
+//! Talent trees, Points earned by leveling spent on class specific bonuses.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::character::{CharacterClass, MetaData};
+use crate::level::Level;
+use crate::stats::{Stat, Stats};
+use abilites::{Ability, AbilityEffect, AbilityTarget};
+use components::status::{StatusEffect, StatusKind};
+use std::fmt;
+
+/// ## What a talent does once learned.
+/// - [`TalentEffect::Stat`]
+///     - Increases a stat by an amount.
+/// - [`TalentEffect::Ability`]
+///     - Unlocks an ability.
+/// - [`TalentEffect::WeaponDamage`]
+///     - Increases the damage of weapon attacks by a percentage.
+/// - [`TalentEffect::StatusChance`]
+///     - Increases the chance percentage of applying the weapon's on hit status.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum TalentEffect {
+    Stat(Stat, u32),
+    Ability(Ability),
+    WeaponDamage(u32),
+    StatusChance(u32),
+}
+
+/// A node of a class's talent tree.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Talent {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    cost: u32,
+    requires: &'static [&'static str],
+    effect: TalentEffect,
+}
+
+impl fmt::Display for Talent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Talent(name: {}, cost: {}, description: {})",
+            self.name, self.cost, self.description
+        )
+    }
+}
+
+impl MetaData for Talent {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+}
+
+impl Talent {
+    /// Creates a new talent that costs one point and has no prerequisites.
+    pub const fn new(
+        id: &'static str,
+        name: &'static str,
+        description: &'static str,
+        effect: TalentEffect,
+    ) -> Talent {
+        Talent {
+            id,
+            name,
+            description,
+            cost: 1,
+            requires: &[],
+            effect,
+        }
+    }
+
+    /// Set the talent points this talent costs.
+    pub const fn with_cost(mut self, cost: u32) -> Talent {
+        self.cost = cost;
+        self
+    }
+
+    /// Set the ids of the talents that must be learned before this one.
+    pub const fn with_requires(mut self, requires: &'static [&'static str]) -> Talent {
+        self.requires = requires;
+        self
+    }
+
+    /// The unique id of this talent within its tree.
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    /// The ids of the talents that must be learned before this one.
+    pub fn requires(&self) -> &'static [&'static str] {
+        self.requires
+    }
+
+    pub fn effect(&self) -> TalentEffect {
+        self.effect
+    }
+}
+
+/// The talents a character learned from its class's talent tree.
+///
+/// Every level after the first grants one talent point, See [`Level::talent_points`].
+///
+/// ```
+/// use characters::{CharacterClass, Level, Talents};
+///
+/// let mut talents = Talents::new(CharacterClass::Warrior);
+/// let level = Level::new(3);
+///
+/// talents.learn("iron_skin", &level).unwrap();
+/// assert!(talents.learn("heavy_blows", &level).is_err());
+/// assert_eq!(talents.respec(), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Talents {
+    class: CharacterClass,
+    learned: Vec<&'static Talent>,
+    abilities: Vec<Ability>,
+    base: usize,
+}
+
+impl Talents {
+    /// No learned talents from the class's tree.
+    pub fn new(class: CharacterClass) -> Talents {
+        Talents::with_abilities(class, class.abilities().to_vec())
+    }
+
+    /// No learned talents, Unlocked abilities are added after the given abilities.
+    pub fn with_abilities(class: CharacterClass, abilities: Vec<Ability>) -> Talents {
+        Talents {
+            class,
+            learned: Vec::new(),
+            base: abilities.len(),
+            abilities,
+        }
+    }
+
+    pub fn class(&self) -> CharacterClass {
+        self.class
+    }
+
+    /// The talent tree these talents are learned from.
+    pub fn tree(&self) -> &'static [Talent] {
+        self.class.talent_tree()
+    }
+
+    /// The learned talents in the order they were learned.
+    pub fn learned(&self) -> impl Iterator<Item = &'static Talent> + '_ {
+        self.learned.iter().copied()
+    }
+
+    pub fn is_learned(&self, id: &str) -> bool {
+        self.learned.iter().any(|talent| talent.id == id)
+    }
+
+    /// The talent points spent on learned talents.
+    pub fn spent(&self) -> u32 {
+        self.learned.iter().map(|talent| talent.cost).sum()
+    }
+
+    /// The talent points left to spend at the given level.
+    pub fn remaining(&self, level: &Level) -> u32 {
+        level.talent_points().saturating_sub(self.spent())
+    }
+
+    /// Learn a talent from the tree.
+    ///
+    /// Fails if it's already learned, Its prerequisites aren't learned or
+    /// there aren't enough talent points left at the given level.
+    pub fn learn(&mut self, id: &str, level: &Level) -> anyhow::Result<()> {
+        let talent = self
+            .tree()
+            .iter()
+            .find(|talent| talent.id == id)
+            .ok_or_else(|| anyhow::anyhow!("{} has no talent {}", self.class.name(), id))?;
+
+        if self.is_learned(id) {
+            return Err(anyhow::anyhow!("{} is already learned", talent.name));
+        }
+        if let Some(missing) = talent.requires.iter().find(|id| !self.is_learned(id)) {
+            return Err(anyhow::anyhow!("{} requires {}", talent.name, missing));
+        }
+        if talent.cost > self.remaining(level) {
+            return Err(anyhow::anyhow!(
+                "Not enough talent points for {}, {} left",
+                talent.name,
+                self.remaining(level)
+            ));
+        }
+
+        if let TalentEffect::Ability(ability) = talent.effect {
+            self.abilities.push(ability);
+        }
+        self.learned.push(talent);
+        Ok(())
+    }
+
+    /// Forget every learned talent.
+    ///
+    /// ## Returns
+    /// [`u32`] The refunded talent points.
+    pub fn respec(&mut self) -> u32 {
+        let refunded = self.spent();
+        self.learned.clear();
+        self.abilities.truncate(self.base);
+        refunded
+    }
+
+    /// Replace the learned talents by learning the given talent ids in order.
+    ///
+    /// This is how saved builds are loaded, Nothing is changed if the build isn't legal at the given level.
+    pub fn restore<S: AsRef<str>>(&mut self, ids: &[S], level: &Level) -> anyhow::Result<()> {
+        let mut talents = self.clone();
+        talents.respec();
+        for id in ids {
+            talents.learn(id.as_ref(), level)?;
+        }
+        *self = talents;
+        Ok(())
+    }
+
+    /// Check that the learned talents are still affordable at the given level.
+    pub fn validate(&self, level: &Level) -> anyhow::Result<()> {
+        if self.spent() > level.talent_points() {
+            return Err(anyhow::anyhow!(
+                "{} talent points spent but only {} earned",
+                self.spent(),
+                level.talent_points()
+            ));
+        }
+        Ok(())
+    }
+
+    /// The base abilities followed by the abilities unlocked by learned talents.
+    pub fn abilities(&self) -> &[Ability] {
+        &self.abilities
+    }
+
+    /// Returns the given stats with the bonuses of the learned talents.
+    pub fn apply(&self, stats: &Stats) -> Stats {
+        let mut stats = *stats;
+        for talent in &self.learned {
+            if let TalentEffect::Stat(stat, amount) = talent.effect {
+                stats.add(stat, amount);
+            }
+        }
+        stats
+    }
+
+    /// Returns the given weapon damage increased by the learned talents.
+    pub fn weapon_damage(&self, damage: u32) -> u32 {
+        let bonus: u32 = self
+            .learned
+            .iter()
+            .filter_map(|talent| match talent.effect {
+                TalentEffect::WeaponDamage(bonus) => Some(bonus),
+                _ => None,
+            })
+            .sum();
+        damage.saturating_mul(100 + bonus) / 100
+    }
+
+    /// The extra chance percentage of applying the weapon's on hit status.
+    pub fn status_chance(&self) -> u32 {
+        self.learned
+            .iter()
+            .filter_map(|talent| match talent.effect {
+                TalentEffect::StatusChance(bonus) => Some(bonus),
+                _ => None,
+            })
+            .sum()
+    }
+}
+
+pub(crate) const WARRIOR_TALENTS: [Talent; 5] = [
+    Talent::new(
+        "iron_skin",
+        "Iron Skin",
+        "Years of beatings toughened the hide.",
+        TalentEffect::Stat(Stat::Health, 20),
+    ),
+    Talent::new(
+        "battle_trance",
+        "Battle Trance",
+        "Swing faster as the fight drags on.",
+        TalentEffect::Stat(Stat::AttackSpeed, 5),
+    ),
+    Talent::new(
+        "heavy_blows",
+        "Heavy Blows",
+        "Put the whole body behind every swing.",
+        TalentEffect::WeaponDamage(15),
+    )
+    .with_cost(2)
+    .with_requires(&["iron_skin"]),
+    Talent::new(
+        "brutal_edge",
+        "Brutal Edge",
+        "Weapon hits are more likely to leave a mark.",
+        TalentEffect::StatusChance(15),
+    )
+    .with_cost(2)
+    .with_requires(&["heavy_blows"]),
+    Talent::new(
+        "whirlwind",
+        "Whirlwind",
+        "Unlocks Whirlwind.",
+        TalentEffect::Ability(
+            Ability::new(
+                "Whirlwind",
+                "Spin with the weapon held out, Hitting hard.",
+                AbilityTarget::Enemy,
+                AbilityEffect::Damage(30),
+                3,
+            )
+            .with_cost(35),
+        ),
+    )
+    .with_cost(3)
+    .with_requires(&["heavy_blows", "battle_trance"]),
+];
+
+pub(crate) const WARLOCK_TALENTS: [Talent; 5] = [
+    Talent::new(
+        "dark_pact",
+        "Dark Pact",
+        "The void answers faster.",
+        TalentEffect::Stat(Stat::Mp5, 5),
+    ),
+    Talent::new(
+        "void_shell",
+        "Void Shell",
+        "A thin veil of the void bends blows away.",
+        TalentEffect::Stat(Stat::Evasion, 5),
+    ),
+    Talent::new(
+        "searing_void",
+        "Searing Void",
+        "Weapons burn with void energy.",
+        TalentEffect::WeaponDamage(10),
+    )
+    .with_cost(2)
+    .with_requires(&["dark_pact"]),
+    Talent::new(
+        "hexbound",
+        "Hexbound",
+        "Weapon hits are more likely to curse.",
+        TalentEffect::StatusChance(20),
+    )
+    .with_cost(2)
+    .with_requires(&["searing_void"]),
+    Talent::new(
+        "curse_of_agony",
+        "Curse of Agony",
+        "Unlocks Curse of Agony.",
+        TalentEffect::Ability(
+            Ability::new(
+                "Curse of Agony",
+                "A curse that rots the target from within.",
+                AbilityTarget::Enemy,
+                AbilityEffect::Apply(StatusEffect::new(StatusKind::Toxin, 4, 6)),
+                3,
+            )
+            .with_cost(30)
+            .with_cast_time(1500),
+        ),
+    )
+    .with_cost(3)
+    .with_requires(&["dark_pact"]),
+];
+
+pub(crate) const VAMPIRE_TALENTS: [Talent; 5] = [
+    Talent::new(
+        "night_blood",
+        "Night Blood",
+        "Wounds close on their own.",
+        TalentEffect::Stat(Stat::Hp5, 3),
+    ),
+    Talent::new(
+        "mist_form",
+        "Mist Form",
+        "Turn to mist right before the blow lands.",
+        TalentEffect::Stat(Stat::Evasion, 5),
+    ),
+    Talent::new(
+        "fangs",
+        "Fangs",
+        "Sharper fangs, Deeper cuts.",
+        TalentEffect::WeaponDamage(15),
+    )
+    .with_cost(2)
+    .with_requires(&["night_blood"]),
+    Talent::new(
+        "hemorrhage",
+        "Hemorrhage",
+        "Weapon hits are more likely to bleed.",
+        TalentEffect::StatusChance(15),
+    )
+    .with_cost(2)
+    .with_requires(&["fangs"]),
+    Talent::new(
+        "crimson_feast",
+        "Crimson Feast",
+        "Unlocks Crimson Feast.",
+        TalentEffect::Ability(
+            Ability::new(
+                "Crimson Feast",
+                "Drink deep from stored blood.",
+                AbilityTarget::Caster,
+                AbilityEffect::Heal(35),
+                4,
+            )
+            .with_cost(30),
+        ),
+    )
+    .with_cost(3)
+    .with_requires(&["night_blood", "mist_form"]),
+];
+
+pub(crate) const ASSASSIN_TALENTS: [Talent; 5] = [
+    Talent::new(
+        "fleet_foot",
+        "Fleet Foot",
+        "Always a step ahead.",
+        TalentEffect::Stat(Stat::MovementSpeed, 10),
+    ),
+    Talent::new(
+        "quick_hands",
+        "Quick Hands",
+        "Strike before the victim blinks.",
+        TalentEffect::Stat(Stat::AttackSpeed, 10),
+    ),
+    Talent::new(
+        "poisoned_blades",
+        "Poisoned Blades",
+        "Weapon hits are more likely to poison.",
+        TalentEffect::StatusChance(20),
+    )
+    .with_cost(2)
+    .with_requires(&["quick_hands"]),
+    Talent::new(
+        "lethality",
+        "Lethality",
+        "Know exactly where to cut.",
+        TalentEffect::WeaponDamage(20),
+    )
+    .with_cost(2)
+    .with_requires(&["quick_hands"]),
+    Talent::new(
+        "fan_of_knives",
+        "Fan of Knives",
+        "Unlocks Fan of Knives.",
+        TalentEffect::Ability(
+            Ability::new(
+                "Fan of Knives",
+                "Throw a fan of knives that leave the target bleeding.",
+                AbilityTarget::Enemy,
+                AbilityEffect::Apply(StatusEffect::new(StatusKind::Bleed, 3, 8)),
+                3,
+            )
+            .with_cost(30),
+        ),
+    )
+    .with_cost(3)
+    .with_requires(&["poisoned_blades", "lethality"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_learn() {
+        let mut talents = Talents::new(CharacterClass::Warrior);
+        let level = Level::new(5);
+        assert_eq!(talents.remaining(&level), 4);
+
+        assert!(talents.learn("fireball", &level).is_err());
+        assert!(talents.learn("heavy_blows", &level).is_err());
+        talents.learn("iron_skin", &level).unwrap();
+        assert!(talents.learn("iron_skin", &level).is_err());
+        talents.learn("heavy_blows", &level).unwrap();
+        talents.learn("battle_trance", &level).unwrap();
+        assert_eq!(talents.remaining(&level), 0);
+        assert!(talents.learn("whirlwind", &level).is_err());
+
+        let stats = talents.apply(&Stats::default());
+        assert_eq!(stats.health, 20);
+        assert_eq!(stats.attack_speed, 5);
+        assert_eq!(talents.weapon_damage(20), 23);
+        assert_eq!(talents.status_chance(), 0);
+    }
+
+    #[test]
+    fn test_abilities() {
+        let mut talents = Talents::new(CharacterClass::Vampire);
+        let level = Level::new(10);
+        talents
+            .restore(&["night_blood", "mist_form", "crimson_feast"], &level)
+            .unwrap();
+        assert_eq!(talents.abilities().len(), 3);
+        assert_eq!(talents.abilities()[2].name(), "Crimson Feast");
+
+        assert_eq!(talents.respec(), 5);
+        assert_eq!(talents.abilities(), CharacterClass::Vampire.abilities());
+    }
+
+    #[test]
+    fn test_restore() {
+        let mut talents = Talents::new(CharacterClass::Assassin);
+        let level = Level::new(4);
+        talents.learn("fleet_foot", &level).unwrap();
+
+        // Out of order and too expensive builds are rejected and change nothing.
+        assert!(talents
+            .restore(&["lethality", "quick_hands"], &level)
+            .is_err());
+        assert!(talents
+            .restore(&["quick_hands", "lethality", "poisoned_blades"], &level)
+            .is_err());
+        assert!(talents.is_learned("fleet_foot"));
+
+        talents
+            .restore(&["quick_hands", "lethality"], &level)
+            .unwrap();
+        assert!(!talents.is_learned("fleet_foot"));
+        assert!(talents.validate(&level).is_ok());
+        assert!(talents.validate(&Level::new(2)).is_err());
+    }
+
+    #[test]
+    fn test_trees() {
+        for class in [
+            CharacterClass::Warrior,
+            CharacterClass::Warlock,
+            CharacterClass::Vampire,
+            CharacterClass::Assassin,
+        ] {
+            let tree = class.talent_tree();
+            for talent in tree {
+                assert_eq!(tree.iter().filter(|t| t.id == talent.id).count(), 1);
+                for id in talent.requires {
+                    assert!(tree.iter().any(|t| t.id == *id));
+                }
+            }
+        }
+    }
+}
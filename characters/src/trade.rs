@@ -0,0 +1,366 @@
+//! Trading items and gold between two characters.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::character::Char;
+use components::{Inventory, ItemType, Loot, Wallet};
+
+/// The two sides of a [`Trade`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TradeSide {
+    Left,
+    Right,
+}
+
+impl TradeSide {
+    pub fn other(&self) -> TradeSide {
+        match self {
+            TradeSide::Left => TradeSide::Right,
+            TradeSide::Right => TradeSide::Left,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            TradeSide::Left => 0,
+            TradeSide::Right => 1,
+        }
+    }
+}
+
+/// The items and gold one side of a trade offers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Offer {
+    items: Vec<Loot>,
+    gold: u64,
+}
+
+impl Offer {
+    /// The offered items, These are copies of the items in the offering inventory.
+    ///
+    /// Stackable items are traded by type, See [`Loot::is_same`].
+    pub fn items(&self) -> &[Loot] {
+        &self.items
+    }
+
+    pub fn gold(&self) -> u64 {
+        self.gold
+    }
+}
+
+/// A trade session between two characters.
+///
+/// Both sides make their offers and confirm, Changing any offer withdraws both confirmations.
+/// The exchange is then applied at once with [`Trade::execute`], Either everything is traded or nothing.
+///
+/// ```
+/// use characters::{Char, CharFactory, Kain, Trade, TradeSide, Tyr};
+/// use characters::components::ItemType;
+///
+/// let mut tyr = Tyr::new();
+/// let mut kain = Kain::new();
+/// kain.wallet_mut().deposit(100).unwrap();
+/// let mace = tyr.equipment_mut().unequip(characters::components::EquipSlot::MainHand).unwrap();
+/// tyr.inventory_mut().put_weapon(mace).unwrap();
+///
+/// let mut trade = Trade::new();
+/// trade.offer_item(TradeSide::Left, tyr.inventory(), ItemType::Weapon, 0).unwrap();
+/// trade.offer_gold(TradeSide::Right, kain.wallet(), 100).unwrap();
+/// trade.confirm(TradeSide::Left);
+/// trade.confirm(TradeSide::Right);
+/// trade.execute(&mut tyr, &mut kain).unwrap();
+///
+/// assert_eq!(tyr.wallet().gold(), 100);
+/// assert_eq!(kain.inventory().weapon(0), Some(&mace));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trade {
+    offers: [Offer; 2],
+    confirmed: [bool; 2],
+}
+
+impl Trade {
+    /// Start a new trade with empty offers.
+    pub fn new() -> Trade {
+        Trade::default()
+    }
+
+    pub fn offer(&self, side: TradeSide) -> &Offer {
+        &self.offers[side.index()]
+    }
+
+    /// Offer the item of the given type at the given index of the side's inventory.
+    ///
+    /// Fails if there's no such item left in the inventory that isn't already offered.
+    pub fn offer_item(
+        &mut self,
+        side: TradeSide,
+        inventory: &Inventory,
+        item_type: ItemType,
+        index: usize,
+    ) -> anyhow::Result<()> {
+        let item = inventory
+            .get(item_type, index)
+            .ok_or_else(|| anyhow::anyhow!("No {:?} at inventory index {}", item_type, index))?;
+        let offer = &mut self.offers[side.index()];
+        let offered = offer.items.iter().filter(|i| i.is_same(&item)).count();
+        if offered >= inventory.count_loot(&item) {
+            return Err(anyhow::anyhow!("{} is already offered", item.item().name()));
+        }
+        offer.items.push(item);
+        self.unconfirm();
+        Ok(())
+    }
+
+    /// Take back the offered item at the given offer index.
+    pub fn withdraw_item(&mut self, side: TradeSide, index: usize) -> Option<Loot> {
+        if index >= self.offer(side).items.len() {
+            return None;
+        }
+        self.unconfirm();
+        Some(self.offers[side.index()].items.remove(index))
+    }
+
+    /// Set the gold the side offers, Fails if the wallet can't afford it.
+    pub fn offer_gold(
+        &mut self,
+        side: TradeSide,
+        wallet: &Wallet,
+        gold: u64,
+    ) -> anyhow::Result<()> {
+        if !wallet.can_afford(gold) {
+            return Err(anyhow::anyhow!(
+                "Not enough gold, {} of {} needed",
+                wallet.gold(),
+                gold
+            ));
+        }
+        self.offers[side.index()].gold = gold;
+        self.unconfirm();
+        Ok(())
+    }
+
+    /// Confirm the side accepts the current offers.
+    pub fn confirm(&mut self, side: TradeSide) {
+        self.confirmed[side.index()] = true;
+    }
+
+    pub fn is_confirmed(&self, side: TradeSide) -> bool {
+        self.confirmed[side.index()]
+    }
+
+    /// Exchange the offers between the left and right characters.
+    ///
+    /// Fails without changing either character if a side didn't confirm, An offered item or gold
+    /// is no longer there, Or either inventory or wallet can't hold what it receives.
+    /// The offers and confirmations are cleared once exchanged, So a trade is only executed once.
+    pub fn execute(&mut self, left: &mut dyn Char, right: &mut dyn Char) -> anyhow::Result<()> {
+        if !self.confirmed.iter().all(|confirmed| *confirmed) {
+            return Err(anyhow::anyhow!("Both sides must confirm the trade"));
+        }
+
+        let mut inventories = [left.inventory().clone(), right.inventory().clone()];
+        let mut wallets = [*left.wallet(), *right.wallet()];
+
+        for side in [TradeSide::Left, TradeSide::Right] {
+            let offer = self.offer(side);
+            for item in &offer.items {
+                inventories[side.index()].take_loot(item).ok_or_else(|| {
+                    anyhow::anyhow!("{} is no longer in the inventory", item.item().name())
+                })?;
+            }
+            wallets[side.index()].withdraw(offer.gold)?;
+        }
+        for side in [TradeSide::Left, TradeSide::Right] {
+            let offer = self.offer(side.other());
+            for item in &offer.items {
+                inventories[side.index()].put_loot(*item)?;
+            }
+            wallets[side.index()].deposit(offer.gold)?;
+        }
+
+        let [left_inventory, right_inventory] = inventories;
+        *left.inventory_mut() = left_inventory;
+        *right.inventory_mut() = right_inventory;
+        *left.wallet_mut() = wallets[0];
+        *right.wallet_mut() = wallets[1];
+        *self = Trade::new();
+        Ok(())
+    }
+
+    fn unconfirm(&mut self) {
+        self.confirmed = [false; 2];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CharFactory, Kain, Vamp};
+    use components::{Consumable, ConsumableType, Weapon, WeaponType};
+
+    fn traders() -> (Vamp, Kain) {
+        let mut vamp = Vamp::new();
+        vamp.inventory_mut()
+            .put_weapon(Weapon::new(WeaponType::Claw).with_durability(40))
+            .unwrap();
+        vamp.wallet_mut().deposit(50).unwrap();
+
+        let mut kain = Kain::new();
+        kain.inventory_mut()
+            .put_consumable(Consumable::new(ConsumableType::Elixir))
+            .unwrap();
+        (vamp, kain)
+    }
+
+    fn confirmed(trade: &mut Trade) {
+        trade.confirm(TradeSide::Left);
+        trade.confirm(TradeSide::Right);
+    }
+
+    #[test]
+    fn test_exchange() {
+        let (mut vamp, mut kain) = traders();
+        let claw = *vamp.inventory().weapon(0).unwrap();
+        let elixir = *kain.inventory().get_consumables()[0].item();
+
+        let mut trade = Trade::new();
+        trade
+            .offer_item(TradeSide::Left, vamp.inventory(), ItemType::Weapon, 0)
+            .unwrap();
+        assert!(trade
+            .offer_item(TradeSide::Left, vamp.inventory(), ItemType::Weapon, 0)
+            .is_err());
+        trade
+            .offer_gold(TradeSide::Left, vamp.wallet(), 30)
+            .unwrap();
+        assert!(trade
+            .offer_gold(TradeSide::Right, kain.wallet(), 1)
+            .is_err());
+        trade
+            .offer_item(TradeSide::Right, kain.inventory(), ItemType::Consumable, 0)
+            .unwrap();
+
+        assert!(trade.execute(&mut vamp, &mut kain).is_err());
+        confirmed(&mut trade);
+        trade.execute(&mut vamp, &mut kain).unwrap();
+
+        assert_eq!(kain.inventory().len(), 1);
+        assert_eq!(kain.inventory().weapon(0), Some(&claw));
+        assert_eq!(vamp.inventory().get_consumables()[0].item(), &elixir);
+        assert!(vamp.inventory().get_weapons().is_empty());
+        assert_eq!(vamp.wallet().gold(), 20);
+        assert_eq!(kain.wallet().gold(), 30);
+
+        // The offers were exchanged, So executing again trades nothing.
+        assert_eq!(trade, Trade::new());
+        assert!(trade.execute(&mut vamp, &mut kain).is_err());
+        confirmed(&mut trade);
+        trade.execute(&mut vamp, &mut kain).unwrap();
+        assert_eq!(vamp.wallet().gold(), 20);
+        assert_eq!(kain.wallet().gold(), 30);
+    }
+
+    #[test]
+    fn test_stackables() {
+        let (mut vamp, mut kain) = traders();
+        vamp.inventory_mut()
+            .put_consumable(Consumable::new(ConsumableType::Elixir))
+            .unwrap();
+        let elixir = Loot::Consumable(*kain.inventory().get_consumables()[0].item());
+
+        let mut trade = Trade::new();
+        trade
+            .offer_item(TradeSide::Right, kain.inventory(), ItemType::Consumable, 0)
+            .unwrap();
+        assert!(trade
+            .offer_item(TradeSide::Right, kain.inventory(), ItemType::Consumable, 0)
+            .is_err());
+        confirmed(&mut trade);
+        trade.execute(&mut vamp, &mut kain).unwrap();
+
+        // The traded elixir joined the stack already there.
+        assert_eq!(vamp.inventory().get_consumables().len(), 1);
+        assert_eq!(vamp.inventory().get_consumables()[0].quantity(), 2);
+        assert_eq!(vamp.inventory().count_loot(&elixir), 2);
+        assert!(vamp
+            .inventory_mut()
+            .take_loot(&elixir)
+            .unwrap()
+            .is_same(&elixir));
+        assert_eq!(vamp.inventory().count_loot(&elixir), 1);
+        assert_eq!(kain.inventory().count_loot(&elixir), 0);
+    }
+
+    #[test]
+    fn test_changes_unconfirm() {
+        let (vamp, _) = traders();
+        let mut trade = Trade::new();
+        confirmed(&mut trade);
+        trade
+            .offer_item(TradeSide::Left, vamp.inventory(), ItemType::Weapon, 0)
+            .unwrap();
+        assert!(!trade.is_confirmed(TradeSide::Right));
+
+        confirmed(&mut trade);
+        assert!(trade.withdraw_item(TradeSide::Left, 0).is_some());
+        assert!(!trade.is_confirmed(TradeSide::Left));
+        assert!(trade.withdraw_item(TradeSide::Left, 0).is_none());
+    }
+
+    #[test]
+    fn test_atomic() {
+        let (mut vamp, mut kain) = traders();
+        while !kain.inventory().is_full() {
+            kain.inventory_mut()
+                .put_consumable(Consumable::new(ConsumableType::Antidote))
+                .unwrap();
+        }
+        let (before_vamp, before_kain) = (vamp.clone(), kain.clone());
+
+        let mut trade = Trade::new();
+        trade
+            .offer_item(TradeSide::Left, vamp.inventory(), ItemType::Weapon, 0)
+            .unwrap();
+        trade
+            .offer_gold(TradeSide::Left, vamp.wallet(), 50)
+            .unwrap();
+        confirmed(&mut trade);
+        assert!(trade.execute(&mut vamp, &mut kain).is_err());
+        assert_eq!(vamp, before_vamp);
+        assert_eq!(kain, before_kain);
+
+        // Items that left the inventory after being offered fail the trade too.
+        kain.inventory_mut().take_consumable(0);
+        vamp.inventory_mut().take_weapon(0);
+        assert!(trade.execute(&mut vamp, &mut kain).is_err());
+        assert_eq!(kain.wallet().gold(), 0);
+    }
+}
@@ -0,0 +1,763 @@
+//! The game state and how it reacts to the player's input, Independent of the terminal.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use characters::{
+    Achievements, Bestiary, Char, CharacterRegistry, Observed, Party, QuestLog, Row, SaveGame,
+};
+use combat::{ai::Utility, Action, Arena, Battle, BattleEvent, BattleOutcome, CombatantId, Side};
+use components::{EquipSlot, EventBus, GameEvent, Loot, MetaData};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use ratatui::crossterm::event::KeyCode;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// The characters a player can pick from.
+pub const BUILTINS: [&str; 4] = ["Vamp", "Kain", "Susanoo", "Tyr"];
+/// The amount of log lines kept.
+const LOG_SIZE: usize = 100;
+
+/// The screen the player is looking at.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Screen {
+    /// Picking a builtin character.
+    Select,
+    /// Between fights, Viewing the character, quests and achievements.
+    Camp,
+    Fight,
+    /// Picking up the drops of a won fight.
+    Loot,
+}
+
+/// The menu of the player's turn.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Menu {
+    Actions,
+    Abilities,
+    Consumables,
+}
+
+/// The top level actions of a turn.
+pub const ACTIONS: [&str; 4] = ["Attack", "Abilities", "Consumables", "Flee"];
+
+/// The whole game, Driven by [`App::handle`].
+pub struct App {
+    screen: Screen,
+    menu: Menu,
+    cursor: usize,
+    /// `None` before a character is picked and while it's fighting.
+    player: Option<Box<dyn Char>>,
+    battle: Option<Battle>,
+    drops: Vec<Loot>,
+    quests: QuestLog,
+    achievements: Achievements,
+    events: EventBus,
+    /// The gameplay events emitted since they were last handled.
+    journal: Arc<Mutex<Vec<GameEvent>>>,
+    log: Vec<String>,
+    enemies: Utility,
+    registry: CharacterRegistry,
+    bestiary: Bestiary,
+    rng: StdRng,
+    save_path: PathBuf,
+    quit: bool,
+}
+
+impl App {
+    /// Creates a new game saving to the given path.
+    pub fn new(seed: u64, save_path: PathBuf) -> App {
+        let journal = Arc::new(Mutex::new(Vec::new()));
+        let mut events = EventBus::new();
+        {
+            let journal = Arc::clone(&journal);
+            events.subscribe(move |event| journal.lock().unwrap().push(*event));
+        }
+        App {
+            screen: Screen::Select,
+            menu: Menu::Actions,
+            cursor: 0,
+            player: None,
+            battle: None,
+            drops: Vec::new(),
+            quests: QuestLog::new(),
+            achievements: Achievements::new(),
+            events,
+            journal,
+            log: vec!["Pick a character to begin.".to_string()],
+            enemies: Utility::default(),
+            registry: CharacterRegistry::default(),
+            bestiary: Bestiary::default(),
+            rng: StdRng::seed_from_u64(seed),
+            save_path,
+            quit: false,
+        }
+    }
+
+    pub fn screen(&self) -> Screen {
+        self.screen
+    }
+
+    pub fn menu(&self) -> Menu {
+        self.menu
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The player's character, `None` before one is picked and while it's fighting.
+    pub fn player(&self) -> Option<&dyn Char> {
+        self.player.as_deref()
+    }
+
+    pub fn battle(&self) -> Option<&Battle> {
+        self.battle.as_ref()
+    }
+
+    /// The drops of the last won fight that weren't picked up.
+    pub fn drops(&self) -> &[Loot] {
+        &self.drops
+    }
+
+    pub fn quests(&self) -> &QuestLog {
+        &self.quests
+    }
+
+    pub fn achievements(&self) -> &Achievements {
+        &self.achievements
+    }
+
+    /// Messages about what happened, Newest last.
+    pub fn log(&self) -> &[String] {
+        &self.log
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// The name of a character shown to the player.
+    pub fn display_name(&self, character: &dyn Char) -> &'static str {
+        let kind = character.event_name();
+        self.bestiary
+            .get(kind)
+            .map_or(kind, |entry| entry.archetype().name)
+    }
+
+    /// The amount of entries of the current screen's list the cursor moves over.
+    pub fn entries(&self) -> usize {
+        match self.screen {
+            Screen::Select => BUILTINS.len(),
+            Screen::Camp => self
+                .player()
+                .map_or(0, |p| p.inventory().get_weapons().len()),
+            Screen::Fight => match self.menu {
+                Menu::Actions => ACTIONS.len(),
+                Menu::Abilities => self.fighter().map_or(0, |p| p.abilities().len()),
+                Menu::Consumables => self
+                    .fighter()
+                    .map_or(0, |p| p.inventory().get_consumables().len()),
+            },
+            Screen::Loot => self.drops.len(),
+        }
+    }
+
+    /// The player's character while fighting.
+    pub fn fighter(&self) -> Option<&dyn Char> {
+        self.battle
+            .as_ref()
+            .and_then(|b| b.arena().get(player_id()))
+            .map(|c| c.character())
+    }
+
+    /// React to a key press.
+    pub fn handle(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Up => {
+                self.cursor = self.cursor.saturating_sub(1);
+                return;
+            }
+            KeyCode::Down => {
+                self.cursor = (self.cursor + 1).min(self.entries().saturating_sub(1));
+                return;
+            }
+            _ => {}
+        }
+
+        let result = match self.screen {
+            Screen::Select => self.handle_select(key),
+            Screen::Camp => self.handle_camp(key),
+            Screen::Fight => self.handle_fight(key),
+            Screen::Loot => self.handle_loot(key),
+        };
+        if let Err(err) = result {
+            self.say(err.to_string());
+        }
+        self.cursor = self.cursor.min(self.entries().saturating_sub(1));
+    }
+
+    fn handle_select(&mut self, key: KeyCode) -> anyhow::Result<()> {
+        match key {
+            KeyCode::Enter => {
+                let name = BUILTINS[self.cursor];
+                let player = self
+                    .registry
+                    .spawn(name)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown character {}", name))?;
+                self.achievements.track(player.event_name());
+                self.quests.track(player.event_name());
+                self.player = Some(player);
+                self.say(format!("You set out as {}.", name));
+                self.go(Screen::Camp);
+            }
+            KeyCode::Char('l') => self.load()?,
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_camp(&mut self, key: KeyCode) -> anyhow::Result<()> {
+        match key {
+            KeyCode::Char('f') => self.start_fight(),
+            KeyCode::Char('r') => {
+                let health = self.player_mut()?.health_mut();
+                let before = health.current();
+                let after = health.incr(health.max()).unwrap_or(before);
+                self.say(format!("You rest and recover {} health.", after - before));
+            }
+            KeyCode::Enter => {
+                let index = self.cursor;
+                let player = self.player.as_deref_mut().ok_or_else(no_player)?;
+                Observed::new(player, &mut self.events)
+                    .equip_from_inventory(index, EquipSlot::MainHand)?;
+                self.flush();
+                self.say("Weapon equipped.".to_string());
+            }
+            KeyCode::Char('a') => {
+                let player = self.player.as_deref().ok_or_else(no_player)?;
+                let quest = self
+                    .quests
+                    .available(player.level())
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("There are no quests available"))?;
+                let level = *player.level();
+                self.quests.accept(quest.id(), &level)?;
+                self.say(format!("Accepted {}.", quest.name()));
+            }
+            KeyCode::Char('t') => self.turn_in()?,
+            KeyCode::Char('s') => self.save()?,
+            KeyCode::Char('l') => self.load()?,
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_fight(&mut self, key: KeyCode) -> anyhow::Result<()> {
+        let action = match (self.menu, key) {
+            (Menu::Actions, KeyCode::Enter) => match ACTIONS[self.cursor] {
+                "Attack" => Some(Action::Attack(self.target()?)),
+                "Abilities" => {
+                    self.menu = Menu::Abilities;
+                    self.cursor = 0;
+                    None
+                }
+                "Consumables" => {
+                    self.menu = Menu::Consumables;
+                    self.cursor = 0;
+                    None
+                }
+                _ => Some(Action::Flee),
+            },
+            (Menu::Abilities, KeyCode::Enter) => {
+                let fighter = self.fighter().ok_or_else(no_player)?;
+                let ability = fighter
+                    .abilities()
+                    .get(self.cursor)
+                    .ok_or_else(|| anyhow::anyhow!("No ability to use"))?;
+                let target = match ability.target() {
+                    abilites::AbilityTarget::Enemy => self.target()?,
+                    _ => player_id(),
+                };
+                Some(Action::UseAbility {
+                    ability: self.cursor,
+                    target,
+                })
+            }
+            (Menu::Consumables, KeyCode::Enter) => Some(Action::UseConsumable(self.cursor)),
+            (Menu::Abilities, KeyCode::Esc) | (Menu::Consumables, KeyCode::Esc) => {
+                self.menu = Menu::Actions;
+                self.cursor = 0;
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(action) = action {
+            let battle = self.battle.as_mut().ok_or_else(no_player)?;
+            let mut chosen = |_: &Arena, _: CombatantId| action;
+            let events = battle.next_turn(&mut chosen, &mut self.enemies)?;
+            self.record(&events);
+            self.menu = Menu::Actions;
+            self.cursor = 0;
+            self.advance()?;
+        }
+        Ok(())
+    }
+
+    fn handle_loot(&mut self, key: KeyCode) -> anyhow::Result<()> {
+        match key {
+            KeyCode::Enter if !self.drops.is_empty() => {
+                let loot = self.drops[self.cursor];
+                self.pick_up(loot)?;
+                self.drops.remove(self.cursor);
+            }
+            KeyCode::Char('a') => {
+                while let Some(loot) = self.drops.first().copied() {
+                    self.pick_up(loot)?;
+                    self.drops.remove(0);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('d') => {
+                self.drops.clear();
+            }
+            _ => {}
+        }
+        if self.drops.is_empty() {
+            self.go(Screen::Camp);
+        }
+        Ok(())
+    }
+
+    fn start_fight(&mut self) {
+        let player = match self.player.take() {
+            Some(player) => player,
+            None => return,
+        };
+        let level = player.level().level();
+        let entries: Vec<_> = self
+            .bestiary
+            .iter()
+            .filter(|entry| entry.archetype().level <= level + 1)
+            .copied()
+            .collect();
+        let entry = match entries.choose(&mut self.rng) {
+            Some(entry) => *entry,
+            None => *self.bestiary.iter().next().expect("The bestiary is empty"),
+        };
+
+        self.say(format!("A {} appears!", entry.archetype().name));
+        self.battle = Some(Battle::new(
+            vec![player],
+            vec![entry.spawn()],
+            self.rng.gen(),
+        ));
+        self.go(Screen::Fight);
+        self.menu = Menu::Actions;
+        if let Err(err) = self.advance() {
+            self.say(err.to_string());
+        }
+    }
+
+    /// Take the enemies' turns until it's the player's turn or the fight is over.
+    fn advance(&mut self) -> anyhow::Result<()> {
+        loop {
+            let battle = self.battle.as_mut().ok_or_else(no_player)?;
+            let actor = match battle.whose_turn() {
+                Some(actor) => actor,
+                None => return self.end_fight(),
+            };
+            let incapacitated = battle
+                .arena()
+                .get(actor)
+                .is_some_and(|c| c.statuses().incapacitated_by().is_some());
+            if actor.side == Side::Allies && !incapacitated {
+                return Ok(());
+            }
+            // Incapacitated players aren't asked for an action.
+            let events = battle.next_turn(&mut |_: &Arena, _| Action::Flee, &mut self.enemies)?;
+            self.record(&events);
+        }
+    }
+
+    fn end_fight(&mut self) -> anyhow::Result<()> {
+        let battle = self.battle.take().ok_or_else(no_player)?;
+        let outcome = battle.outcome();
+        let experience = battle.arena().experience_reward();
+        let (allies, enemies) = battle.into_sides();
+        let mut player = allies.into_iter().next().ok_or_else(no_player)?;
+
+        match outcome {
+            Some(BattleOutcome::Victory) => {
+                let levels =
+                    Observed::new(player.as_mut(), &mut self.events).gain_experience(experience);
+                self.say(format!("Victory! You gain {} experience.", experience));
+                if levels > 0 {
+                    self.say(format!("You reached level {}.", player.level().level()));
+                }
+                self.drops = enemies
+                    .iter()
+                    .filter_map(|enemy| enemy.loot_table())
+                    .flat_map(|table| table.roll(&mut self.rng))
+                    .collect();
+            }
+            Some(BattleOutcome::Defeat) => {
+                Observed::new(player.as_mut(), &mut self.events).revive()?;
+                self.say("You were defeated and wake up back at camp.".to_string());
+            }
+            Some(BattleOutcome::Fled) => self.say("You got away.".to_string()),
+            _ => self.say("The fight dragged on until both sides gave up.".to_string()),
+        }
+        self.player = Some(player);
+        self.flush();
+        self.go(if self.drops.is_empty() {
+            Screen::Camp
+        } else {
+            Screen::Loot
+        });
+        Ok(())
+    }
+
+    fn pick_up(&mut self, loot: Loot) -> anyhow::Result<()> {
+        let player = self.player.as_deref_mut().ok_or_else(no_player)?;
+        Observed::new(player, &mut self.events).pick_up(loot)?;
+        self.flush();
+        self.say(format!("Picked up {}.", loot.item().name()));
+        Ok(())
+    }
+
+    /// Turn in completed quests and claim unlocked achievements.
+    fn turn_in(&mut self) -> anyhow::Result<()> {
+        let player = self.player.as_deref_mut().ok_or_else(no_player)?;
+        let mut messages = Vec::new();
+        let completed: Vec<_> = self
+            .quests
+            .active()
+            .iter()
+            .filter(|p| p.is_complete())
+            .map(|p| p.quest())
+            .collect();
+        for quest in completed {
+            let loot = self
+                .quests
+                .turn_in(quest.id(), player, &mut self.events, &mut self.rng)?;
+            messages.push(format!(
+                "Turned in {} for {} items.",
+                quest.name(),
+                loot.len()
+            ));
+        }
+        let unclaimed: Vec<_> = self
+            .achievements
+            .progress()
+            .iter()
+            .filter(|p| p.is_unlocked() && !p.is_claimed())
+            .map(|p| p.achievement())
+            .collect();
+        for achievement in unclaimed {
+            self.achievements
+                .claim(achievement.id(), player, &mut self.events, &mut self.rng)?;
+            messages.push(format!("Claimed {}.", achievement.name()));
+        }
+        if messages.is_empty() {
+            messages.push("Nothing to turn in.".to_string());
+        }
+        for message in messages {
+            self.say(message);
+        }
+        self.events.dispatch();
+        self.flush();
+        Ok(())
+    }
+
+    fn save(&mut self) -> anyhow::Result<()> {
+        let player = self.player.as_deref().ok_or_else(no_player)?;
+        let mut party = Party::new();
+        party.join(player.clone_box(), Row::Front)?;
+        SaveGame::new(&party)?
+            .with_quests(&self.quests)
+            .with_achievements(&self.achievements)
+            .save(&self.save_path)?;
+        self.say(format!("Saved to {}.", self.save_path.display()));
+        Ok(())
+    }
+
+    fn load(&mut self) -> anyhow::Result<()> {
+        let save = SaveGame::load(&self.save_path)?;
+        let player = save
+            .restore_with(&self.registry)?
+            .drain()
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("The save game has no characters"))?
+            .into_character();
+        self.quests = save.quests;
+        self.achievements = save.achievements;
+        self.say(format!(
+            "Loaded {} from {}.",
+            self.display_name(player.as_ref()),
+            self.save_path.display()
+        ));
+        self.player = Some(player);
+        self.go(Screen::Camp);
+        Ok(())
+    }
+
+    /// Log the battle events and handle their gameplay events.
+    fn record(&mut self, events: &[BattleEvent]) {
+        if let Some(battle) = self.battle.as_ref() {
+            let lines: Vec<_> = events
+                .iter()
+                .map(|event| self.describe(battle.arena(), event))
+                .collect();
+            battle.arena().publish(events, &mut self.events);
+            for line in lines {
+                self.say(line);
+            }
+        }
+        self.flush();
+    }
+
+    /// Feed the journaled gameplay events to the quests and achievements.
+    fn flush(&mut self) {
+        let events: Vec<_> = self.journal.lock().unwrap().drain(..).collect();
+        for event in events.iter() {
+            for id in self.quests.handle(event) {
+                let quest = characters::Quest::get(id).map_or(id, |q| q.name());
+                self.say(format!("Quest complete: {}.", quest));
+            }
+            for id in self.achievements.handle(event) {
+                let achievement = characters::Achievement::get(id).map_or(id, |a| a.name());
+                self.say(format!("Achievement unlocked: {}.", achievement));
+            }
+        }
+    }
+
+    fn describe(&self, arena: &Arena, event: &BattleEvent) -> String {
+        let name = |id: CombatantId| {
+            arena
+                .get(id)
+                .map_or("Someone", |c| self.display_name(c.character()))
+        };
+        match *event {
+            BattleEvent::Damage {
+                source,
+                target,
+                amount,
+                ..
+            } => format!("{} hits {} for {}.", name(source), name(target), amount),
+            BattleEvent::Evaded { source, target } => {
+                format!("{} evades {}.", name(target), name(source))
+            }
+            BattleEvent::CriticalHit { source, .. } => {
+                format!("{} lands a critical hit!", name(source))
+            }
+            BattleEvent::Healed { target, amount, .. } => {
+                format!("{} heals {}.", name(target), amount)
+            }
+            BattleEvent::Regenerated {
+                target,
+                health,
+                mana,
+            } => format!(
+                "{} regenerates {} health and {} mana.",
+                name(target),
+                health,
+                mana
+            ),
+            BattleEvent::CastStarted { caster, ability } => {
+                format!("{} starts casting {}.", name(caster), ability)
+            }
+            BattleEvent::CastInterrupted { caster, ability } => {
+                format!("{}'s {} is interrupted.", name(caster), ability)
+            }
+            BattleEvent::AbilityUsed { caster, ability } => {
+                format!("{} uses {}.", name(caster), ability)
+            }
+            BattleEvent::ConsumableUsed { user, consumable } => {
+                format!("{} uses {}.", name(user), consumable.name())
+            }
+            BattleEvent::StatusApplied { target, kind } => {
+                format!("{} suffers {:?}.", name(target), kind)
+            }
+            BattleEvent::StatusDamage {
+                target,
+                kind,
+                amount,
+            } => format!("{} takes {} {:?} damage.", name(target), amount, kind),
+            BattleEvent::Incapacitated { target, kind } => {
+                format!("{} can't act, {:?}.", name(target), kind)
+            }
+            BattleEvent::WeaponBroke { owner, weapon } => {
+                format!("{}'s {} broke!", name(owner), weapon)
+            }
+            BattleEvent::Fled(id) => format!("{} fled.", name(id)),
+            BattleEvent::FleeFailed(id) => format!("{} failed to flee.", name(id)),
+            BattleEvent::Died(id) => format!("{} died.", name(id)),
+        }
+    }
+
+    /// The first enemy still fighting.
+    fn target(&self) -> anyhow::Result<CombatantId> {
+        self.battle
+            .as_ref()
+            .and_then(|b| b.arena().active(Side::Enemies).next())
+            .map(|(id, _)| id)
+            .ok_or_else(|| anyhow::anyhow!("There's no one to attack"))
+    }
+
+    fn player_mut(&mut self) -> anyhow::Result<&mut Box<dyn Char>> {
+        self.player.as_mut().ok_or_else(no_player)
+    }
+
+    fn go(&mut self, screen: Screen) {
+        self.screen = screen;
+        self.cursor = 0;
+    }
+
+    fn say(&mut self, message: String) {
+        self.log.push(message);
+        if self.log.len() > LOG_SIZE {
+            self.log.remove(0);
+        }
+    }
+}
+
+/// The player is always the only ally.
+pub fn player_id() -> CombatantId {
+    CombatantId::new(Side::Allies, 0)
+}
+
+fn no_player() -> anyhow::Error {
+    anyhow::anyhow!("There's no character to do that with")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str) -> App {
+        let path = std::env::temp_dir().join(format!("client_test_{}.json", name));
+        App::new(7, path)
+    }
+
+    /// Fight until the fight is over always picking the first action.
+    fn fight(app: &mut App) {
+        app.handle(KeyCode::Char('f'));
+        for _ in 0..1000 {
+            if app.screen() != Screen::Fight {
+                return;
+            }
+            app.handle(KeyCode::Enter);
+        }
+        panic!("The fight never ended");
+    }
+
+    #[test]
+    fn test_select() {
+        let mut app = app("select");
+        assert_eq!(app.screen(), Screen::Select);
+        app.handle(KeyCode::Up);
+        assert_eq!(app.cursor(), 0);
+        for _ in 0..10 {
+            app.handle(KeyCode::Down);
+        }
+        assert_eq!(app.cursor(), BUILTINS.len() - 1);
+        app.handle(KeyCode::Enter);
+        assert_eq!(app.screen(), Screen::Camp);
+        assert_eq!(app.player().unwrap().event_name(), "Tyr");
+
+        app.handle(KeyCode::Char('q'));
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn test_fight() {
+        let mut app = app("fight");
+        app.handle(KeyCode::Enter);
+        for _ in 0..5 {
+            fight(&mut app);
+            assert!(app.battle().is_none());
+            assert!(matches!(app.screen(), Screen::Camp | Screen::Loot));
+            if app.screen() == Screen::Loot {
+                app.handle(KeyCode::Char('a'));
+            }
+            // Inventories may fill up.
+            app.handle(KeyCode::Char('d'));
+            assert_eq!(app.screen(), Screen::Camp);
+            assert!(!app.player().unwrap().health().is_killed());
+            app.handle(KeyCode::Char('r'));
+        }
+        assert!(app.log().iter().any(|l| l.contains("hits")));
+
+        // Menus go back to the actions.
+        app.handle(KeyCode::Char('f'));
+        if app.screen() == Screen::Fight {
+            app.handle(KeyCode::Down);
+            app.handle(KeyCode::Enter);
+            assert_eq!(app.menu(), Menu::Abilities);
+            assert!(app.entries() > 0);
+            app.handle(KeyCode::Esc);
+            assert_eq!((app.menu(), app.cursor()), (Menu::Actions, 0));
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut app = app("save");
+        app.handle(KeyCode::Down);
+        app.handle(KeyCode::Enter);
+        app.handle(KeyCode::Char('a'));
+        assert_eq!(app.quests().active().len(), 1);
+        fight(&mut app);
+        app.handle(KeyCode::Char('d'));
+        app.handle(KeyCode::Char('s'));
+        let player = app.player().unwrap();
+        let (level, inventory) = (*player.level(), player.inventory().clone());
+
+        let mut loaded = App::new(1, app.save_path.clone());
+        loaded.handle(KeyCode::Char('l'));
+        std::fs::remove_file(&app.save_path).unwrap();
+        assert_eq!(loaded.screen(), Screen::Camp);
+        let player = loaded.player().unwrap();
+        assert_eq!(player.event_name(), "Kain");
+        assert_eq!(player.level(), &level);
+        assert_eq!(player.inventory(), &inventory);
+        assert_eq!(loaded.quests(), app.quests());
+
+        // Nothing left to load.
+        loaded.handle(KeyCode::Char('l'));
+        assert_eq!(loaded.player().unwrap().event_name(), "Kain");
+        assert!(loaded.log().last().unwrap().contains("No such file"));
+    }
+}
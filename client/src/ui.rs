@@ -0,0 +1,466 @@
+//! Drawing the game to the terminal.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::app::{App, Menu, Screen, ACTIONS, BUILTINS};
+use characters::{Char, CharacterRegistry};
+use combat::{Combatant, Side};
+use components::{Item, MetaData, WeaponAmmoType};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Gauge, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+const AMMO_TYPES: [WeaponAmmoType; 4] = [
+    WeaponAmmoType::Toxic,
+    WeaponAmmoType::Elemental,
+    WeaponAmmoType::Void,
+    WeaponAmmoType::Radiant,
+];
+
+/// Draw the current screen.
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, log, help] = Layout::vertical([
+        Constraint::Min(10),
+        Constraint::Length(8),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    match app.screen() {
+        Screen::Select => draw_select(frame, main, app),
+        Screen::Camp => draw_camp(frame, main, app),
+        Screen::Fight => draw_fight(frame, main, app),
+        Screen::Loot => draw_loot(frame, main, app),
+    }
+    draw_log(frame, log, app);
+    frame.render_widget(
+        Paragraph::new(help_text(app)).style(Style::default().fg(Color::DarkGray)),
+        help,
+    );
+}
+
+fn help_text(app: &App) -> &'static str {
+    match (app.screen(), app.menu()) {
+        (Screen::Select, _) => "↑/↓ choose  enter pick  l load  q quit",
+        (Screen::Camp, _) => {
+            "f fight  r rest  enter equip weapon  a accept quest  t turn in  s save  l load  q quit"
+        }
+        (Screen::Fight, Menu::Actions) => "↑/↓ choose  enter act",
+        (Screen::Fight, _) => "↑/↓ choose  enter use  esc back",
+        (Screen::Loot, _) => "↑/↓ choose  enter pick up  a take all  d leave the rest",
+    }
+}
+
+fn selectable<'a>(
+    title: &'a str,
+    items: Vec<ListItem<'a>>,
+    cursor: usize,
+) -> (List<'a>, ListState) {
+    let list = List::new(items)
+        .block(Block::bordered().title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    (list, ListState::default().with_selected(Some(cursor)))
+}
+
+fn draw_select(frame: &mut Frame, area: Rect, app: &App) {
+    let registry = CharacterRegistry::default();
+    let characters: Vec<_> = BUILTINS
+        .iter()
+        .filter_map(|name| registry.spawn(name))
+        .collect();
+    let [list, details] =
+        Layout::horizontal([Constraint::Length(24), Constraint::Min(20)]).areas(area);
+
+    let items = characters
+        .iter()
+        .map(|c| ListItem::new(c.event_name()))
+        .collect();
+    let (items, mut state) = selectable("Characters", items, app.cursor());
+    frame.render_stateful_widget(items, list, &mut state);
+
+    if let Some(character) = characters.get(app.cursor()) {
+        let class = character.class();
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!("{} the {}", character.event_name(), class.name()),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(class.description()),
+            Line::default(),
+        ];
+        lines.extend(stat_lines(character.as_ref()));
+        let abilities: Vec<_> = character.abilities().iter().map(|a| a.name()).collect();
+        lines.push(Line::from(format!("Abilities: {}", abilities.join(", "))));
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title("Details")),
+            details,
+        );
+    }
+}
+
+fn stat_lines(character: &dyn Char) -> Vec<Line<'static>> {
+    let stats = character.effective_stats();
+    let resistances = character.resistances();
+    let mut lines = vec![
+        Line::from(format!(
+            "HP5 {}  MP5 {}  Health {}",
+            stats.hp5, stats.mp5, stats.health
+        )),
+        Line::from(format!(
+            "Evasion {}  Movement {}  Attack speed {}",
+            stats.evasion, stats.movement_speed, stats.attack_speed
+        )),
+    ];
+    let resistances: Vec<_> = AMMO_TYPES
+        .iter()
+        .map(|ammo| format!("{} {}", ammo.name(), resistances.against(*ammo)))
+        .collect();
+    lines.push(Line::from(format!("Resists {}", resistances.join("  "))));
+    let weapons: Vec<_> = character
+        .equipment()
+        .weapons()
+        .map(|w| format!("{} ({} {:?})", w.name(), w.ammo_type().name(), w.rarity()))
+        .collect();
+    lines.push(Line::from(format!(
+        "Wielding {}",
+        if weapons.is_empty() {
+            "nothing".to_string()
+        } else {
+            weapons.join(", ")
+        }
+    )));
+    lines
+}
+
+fn draw_camp(frame: &mut Frame, area: Rect, app: &App) {
+    let player = match app.player() {
+        Some(player) => player,
+        None => return,
+    };
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(area);
+    let [bars, sheet] = Layout::vertical([Constraint::Length(4), Constraint::Min(6)]).areas(left);
+    let [inventory, journal] =
+        Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(right);
+
+    draw_bars(frame, bars, app.display_name(player), player);
+
+    let level = player.level();
+    let mut lines = vec![
+        Line::from(format!(
+            "Level {}  ({} / {} xp)  Gold {}",
+            level.level(),
+            level.experience(),
+            level.experience() + level.experience_to_next(),
+            player.wallet().gold()
+        )),
+        Line::default(),
+    ];
+    lines.extend(stat_lines(player));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered().title("Character")),
+        sheet,
+    );
+
+    let inventory_items = player.inventory();
+    let mut items: Vec<ListItem> = inventory_items
+        .get_weapons()
+        .iter()
+        .map(|stack| {
+            let weapon = stack.item();
+            ListItem::new(format!(
+                "{} - {} {} {:?}, {} damage",
+                weapon.name(),
+                weapon.weapon_type().name(),
+                weapon.ammo_type().name(),
+                weapon.rarity(),
+                weapon.damage()
+            ))
+        })
+        .collect();
+    let weapons = items.len();
+    items.extend(
+        inventory_items
+            .items()
+            .skip(weapons)
+            .map(|(item, quantity)| ListItem::new(format!("{} x{}", item.name(), quantity))),
+    );
+    let title = format!(
+        "Inventory ({}/{})",
+        inventory_items.len(),
+        inventory_items.max_size()
+    );
+    let (list, mut state) = selectable(&title, items, app.cursor());
+    let state = if weapons == 0 {
+        &mut ListState::default()
+    } else {
+        &mut state
+    };
+    frame.render_stateful_widget(list, inventory, state);
+
+    let mut lines: Vec<Line> = app
+        .quests()
+        .active()
+        .iter()
+        .map(|progress| {
+            let quest = progress.quest();
+            Line::from(format!(
+                "{} {:?}{}",
+                quest.name(),
+                progress.progress(),
+                if progress.is_complete() {
+                    " - complete"
+                } else {
+                    ""
+                }
+            ))
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::from("No active quests."));
+    }
+    let unlocked: Vec<_> = app.achievements().unlocked().map(|a| a.name()).collect();
+    lines.push(Line::default());
+    lines.push(Line::from(format!(
+        "Achievements: {}",
+        if unlocked.is_empty() {
+            "none yet".to_string()
+        } else {
+            unlocked.join(", ")
+        }
+    )));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered().title("Journal")),
+        journal,
+    );
+}
+
+fn draw_bars(frame: &mut Frame, area: Rect, name: &str, character: &dyn Char) {
+    let block = Block::bordered().title(name.to_string());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [health, mana] =
+        Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(inner);
+
+    let current = character.health().current();
+    let max = character.health().max();
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(Color::Red))
+            .ratio(if max == 0 {
+                0.0
+            } else {
+                f64::from(current.min(max)) / f64::from(max)
+            })
+            .label(format!("Health {}", current)),
+        health,
+    );
+    let mana_bar = character.mana();
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(Color::Blue))
+            .ratio(if mana_bar.max() == 0 {
+                0.0
+            } else {
+                f64::from(mana_bar.current().min(mana_bar.max())) / f64::from(mana_bar.max())
+            })
+            .label(format!(
+                "{} {}/{}",
+                mana_bar.resource().name(),
+                mana_bar.current(),
+                mana_bar.max()
+            )),
+        mana,
+    );
+}
+
+fn draw_fight(frame: &mut Frame, area: Rect, app: &App) {
+    let battle = match app.battle() {
+        Some(battle) => battle,
+        None => return,
+    };
+    let [sides, menu] = Layout::vertical([Constraint::Length(5), Constraint::Min(4)]).areas(area);
+    let [allies, enemies] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(sides);
+
+    for (side, area) in [(Side::Allies, allies), (Side::Enemies, enemies)] {
+        if let Some(combatant) = battle.arena().side(side).first() {
+            draw_combatant(frame, area, app, combatant);
+        }
+    }
+
+    let fighter = match app.fighter() {
+        Some(fighter) => fighter,
+        None => return,
+    };
+    let (title, items): (_, Vec<ListItem>) = match app.menu() {
+        Menu::Actions => (
+            "Actions",
+            ACTIONS.iter().map(|a| ListItem::new(*a)).collect(),
+        ),
+        Menu::Abilities => {
+            let combatant = battle.arena().get(crate::app::player_id());
+            (
+                "Abilities",
+                fighter
+                    .abilities()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, ability)| {
+                        let cooldown = combatant.map_or(0, |c| c.cooldown(index));
+                        ListItem::new(format!(
+                            "{} - costs {}{}",
+                            ability.name(),
+                            ability.cost(),
+                            if cooldown > 0 {
+                                format!(", ready in {} turns", cooldown)
+                            } else {
+                                String::new()
+                            }
+                        ))
+                    })
+                    .collect(),
+            )
+        }
+        Menu::Consumables => (
+            "Consumables",
+            fighter
+                .inventory()
+                .get_consumables()
+                .iter()
+                .map(|stack| {
+                    ListItem::new(format!("{} x{}", stack.item().name(), stack.quantity()))
+                })
+                .collect(),
+        ),
+    };
+    let (list, mut state) = selectable(title, items, app.cursor());
+    frame.render_stateful_widget(list, menu, &mut state);
+}
+
+fn draw_combatant(frame: &mut Frame, area: Rect, app: &App, combatant: &Combatant) {
+    let [bars, status_area] =
+        Layout::vertical([Constraint::Length(4), Constraint::Length(1)]).areas(area);
+    draw_bars(
+        frame,
+        bars,
+        app.display_name(combatant.character()),
+        combatant.character(),
+    );
+    let statuses: Vec<_> = combatant
+        .statuses()
+        .iter()
+        .map(|s| format!("{:?}", s.kind))
+        .collect();
+    frame.render_widget(Paragraph::new(statuses.join(" ")), status_area);
+}
+
+fn draw_loot(frame: &mut Frame, area: Rect, app: &App) {
+    let items = app
+        .drops()
+        .iter()
+        .map(|loot| {
+            let item = loot.item();
+            ListItem::new(format!(
+                "{} - {:?} {:?}, worth {}",
+                item.name(),
+                item.rarity(),
+                item.item_type(),
+                item.value()
+            ))
+        })
+        .collect();
+    let (list, mut state) = selectable("Loot", items, app.cursor());
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_log(frame: &mut Frame, area: Rect, app: &App) {
+    let height = usize::from(area.height.saturating_sub(2));
+    let start = app.log().len().saturating_sub(height);
+    let lines: Vec<_> = app.log()[start..]
+        .iter()
+        .map(|line| Line::from(line.as_str()))
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title("Log")),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, crossterm::event::KeyCode, Terminal};
+
+    fn render(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(usize::from(buffer.area.width))
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut app = App::new(3, std::env::temp_dir().join("client_test_draw.json"));
+        let screen = render(&app);
+        assert!(screen.contains("Characters"));
+        assert!(screen.contains("Vamp"));
+        assert!(screen.contains("Abilities:"));
+
+        app.handle(KeyCode::Enter);
+        let screen = render(&app);
+        assert!(screen.contains("Inventory"));
+        assert!(screen.contains("Resists Toxic"));
+        assert!(screen.contains("Health 100"));
+
+        app.handle(KeyCode::Char('f'));
+        if app.screen() == Screen::Fight {
+            let screen = render(&app);
+            assert!(screen.contains("Actions"));
+            assert!(screen.contains("appears"));
+        }
+    }
+}
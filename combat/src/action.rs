@@ -0,0 +1,163 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::arena::{Arena, CombatantId};
+use components::{
+    consumable::ConsumableType,
+    status::StatusKind,
+    weapon::{WeaponAmmoType, WeaponType},
+};
+use serde::{Deserialize, Serialize};
+
+/// An action a combatant takes on its turn.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Action {
+    /// Attack an opponent with the equipped weapons.
+    Attack(CombatantId),
+    /// Use the ability at the given index of the character's abilities.
+    UseAbility { ability: usize, target: CombatantId },
+    /// Use the consumable at the given index of the character's inventory.
+    UseConsumable(usize),
+    /// Try to flee the battle.
+    Flee,
+}
+
+/// Types that choose the actions of a side's combatants.
+///
+/// Closures with the same signature are controllers too.
+pub trait Controller {
+    /// Choose the action of the given combatant whose turn it is.
+    fn choose_action(&mut self, arena: &Arena, actor: CombatantId) -> Action;
+}
+
+impl<F> Controller for F
+where
+    F: FnMut(&Arena, CombatantId) -> Action,
+{
+    fn choose_action(&mut self, arena: &Arena, actor: CombatantId) -> Action {
+        self(arena, actor)
+    }
+}
+
+/// What dealt the damage of a [`BattleEvent::Damage`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize)]
+pub enum DamageKind {
+    /// A basic attack with the main hand weapon of the given type and ammo.
+    Weapon(WeaponType, WeaponAmmoType),
+    /// A basic attack without a weapon.
+    Unarmed,
+    /// The ability with the given name.
+    Ability(&'static str),
+}
+
+/// Everything that can happen during a battle.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize)]
+pub enum BattleEvent {
+    /// The health the target lost, Not counting overkill.
+    Damage {
+        source: CombatantId,
+        target: CombatantId,
+        amount: u32,
+        kind: DamageKind,
+    },
+    Evaded {
+        source: CombatantId,
+        target: CombatantId,
+    },
+    /// An attack critically hit, Followed by its damage.
+    CriticalHit {
+        source: CombatantId,
+        target: CombatantId,
+    },
+    Healed {
+        source: CombatantId,
+        target: CombatantId,
+        amount: u32,
+    },
+    /// Health and mana restored from the character's `hp5` and `mp5` stats.
+    Regenerated {
+        target: CombatantId,
+        health: u32,
+        mana: u32,
+    },
+    /// An ability with a cast time started casting.
+    CastStarted {
+        caster: CombatantId,
+        ability: &'static str,
+    },
+    /// A cast was interrupted by a status effect or its target is no longer fighting.
+    CastInterrupted {
+        caster: CombatantId,
+        ability: &'static str,
+    },
+    AbilityUsed {
+        caster: CombatantId,
+        ability: &'static str,
+    },
+    ConsumableUsed {
+        user: CombatantId,
+        consumable: ConsumableType,
+    },
+    StatusApplied {
+        target: CombatantId,
+        kind: StatusKind,
+    },
+    StatusDamage {
+        target: CombatantId,
+        kind: StatusKind,
+        amount: u32,
+    },
+    /// The combatant skipped its turn because of a status effect.
+    Incapacitated {
+        target: CombatantId,
+        kind: StatusKind,
+    },
+    /// A weapon wore down to zero durability, It deals no damage until repaired.
+    WeaponBroke {
+        owner: CombatantId,
+        weapon: &'static str,
+    },
+    Fled(CombatantId),
+    FleeFailed(CombatantId),
+    Died(CombatantId),
+}
+
+/// The outcome of a battle from the allies' perspective.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum BattleOutcome {
+    /// All enemies are dead or have fled.
+    Victory,
+    /// All allies are dead.
+    Defeat,
+    /// All surviving allies have fled.
+    Fled,
+    /// The battle took too long.
+    Draw,
+}
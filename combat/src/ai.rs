@@ -0,0 +1,585 @@
+//! AI policies that choose the actions of computer controlled combatants.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::action::{Action, Controller};
+use crate::arena::{Arena, Combatant, CombatantId};
+use abilites::{AbilityEffect, AbilityTarget};
+use components::status::StatusKind;
+
+/// The health at or below which the builtin policies consider a combatant in danger.
+pub const LOW_HEALTH: u32 = 35;
+
+/// Returns the missing health of a combatant.
+fn missing_health(combatant: &Combatant) -> u32 {
+    let health = combatant.character().health();
+    health.max().saturating_sub(health.current())
+}
+
+/// Whether the combatant is suffering from a damage over time effect.
+fn is_afflicted(combatant: &Combatant) -> bool {
+    combatant
+        .statuses()
+        .iter()
+        .any(|effect| effect.kind.is_damage_over_time())
+}
+
+/// The active opponent with the lowest health.
+fn weakest_opponent(arena: &Arena, actor: CombatantId) -> Option<CombatantId> {
+    arena
+        .active(actor.side.opposite())
+        .min_by_key(|(_, c)| c.character().health().current())
+        .map(|(id, _)| id)
+}
+
+/// The active opponent that hits the hardest.
+fn strongest_opponent(arena: &Arena, actor: CombatantId) -> Option<CombatantId> {
+    arena
+        .active(actor.side.opposite())
+        .fold(None, |best: Option<(CombatantId, u32)>, (id, c)| {
+            let damage = c.character().attack_damage();
+            match best {
+                Some((_, most)) if most >= damage => best,
+                _ => Some((id, damage)),
+            }
+        })
+        .map(|(id, _)| id)
+}
+
+/// The most hurt active ally, Including the actor, whose health is at or below `threshold`.
+fn hurt_ally(arena: &Arena, actor: CombatantId, threshold: u32) -> Option<CombatantId> {
+    arena
+        .active(actor.side)
+        .filter(|(_, c)| c.character().health().current() <= threshold)
+        .min_by_key(|(_, c)| c.character().health().current())
+        .map(|(id, _)| id)
+}
+
+/// The index of the usable ability with the highest score if any scores above zero.
+fn best_ability<F>(arena: &Arena, actor: CombatantId, mut score: F) -> Option<usize>
+where
+    F: FnMut(AbilityTarget, AbilityEffect) -> u32,
+{
+    let combatant = arena.get(actor)?;
+    let mut best = None;
+    for (index, ability) in combatant.character().abilities().iter().enumerate() {
+        if !combatant.can_use(index) {
+            continue;
+        }
+        let points = score(ability.target(), ability.effect());
+        if points > 0 && best.is_none_or(|(_, most)| points > most) {
+            best = Some((index, points));
+        }
+    }
+    best.map(|(index, _)| index)
+}
+
+/// A usable healing ability that can target the given ally.
+fn heal_ability(arena: &Arena, actor: CombatantId, ally: CombatantId) -> Option<usize> {
+    best_ability(arena, actor, |target, effect| match (target, effect) {
+        (AbilityTarget::Ally, AbilityEffect::Heal(amount)) => amount,
+        (AbilityTarget::Caster, AbilityEffect::Heal(amount)) if ally == actor => amount,
+        _ => 0,
+    })
+}
+
+/// The index of a consumable in the actor's inventory that helps it.
+fn consumable(arena: &Arena, actor: CombatantId) -> Option<usize> {
+    let afflicted = is_afflicted(arena.get(actor)?);
+    arena
+        .inventory(actor)
+        .get_consumables()
+        .iter()
+        .position(|c| {
+            let kind = c.item().consumable_type();
+            kind.heal_amount() > 0 || (afflicted && kind.cures())
+        })
+}
+
+/// Hit the target with the hardest hitting damage ability, Or a basic attack.
+///
+/// Basic attacks go for the weakest reachable opponent if the target is out of reach.
+fn strike(arena: &Arena, actor: CombatantId, target: CombatantId) -> Action {
+    let damage = best_ability(arena, actor, |target, effect| match (target, effect) {
+        (AbilityTarget::Enemy, AbilityEffect::Damage(amount)) => amount,
+        _ => 0,
+    });
+    match damage {
+        Some(ability) => Action::UseAbility { ability, target },
+        None if arena.can_reach(actor, target) => Action::Attack(target),
+        None => arena
+            .active(actor.side.opposite())
+            .filter(|(id, _)| arena.can_reach(actor, *id))
+            .min_by_key(|(_, c)| c.character().health().current())
+            .map_or(Action::Attack(target), |(id, _)| Action::Attack(id)),
+    }
+}
+
+/// Use an ability that applies a status effect the target doesn't already suffer from.
+fn afflict<F>(arena: &Arena, actor: CombatantId, target: CombatantId, filter: F) -> Option<Action>
+where
+    F: Fn(StatusKind) -> bool,
+{
+    let statuses = arena.get(target)?.statuses();
+    best_ability(arena, actor, |kind, effect| match (kind, effect) {
+        (AbilityTarget::Enemy, AbilityEffect::Apply(effect))
+            if filter(effect.kind) && !statuses.has(effect.kind) =>
+        {
+            effect.duration.max(1)
+        }
+        _ => 0,
+    })
+    .map(|ability| Action::UseAbility { ability, target })
+}
+
+/// Falls back to fleeing when there's no one to fight.
+fn or_flee(target: Option<CombatantId>, f: impl FnOnce(CombatantId) -> Action) -> Action {
+    target.map_or(Action::Flee, f)
+}
+
+/// Always goes for the kill.
+///
+/// Focuses the weakest opponent with its strongest damage ability, Then basic attacks.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Aggressive;
+
+impl Controller for Aggressive {
+    fn choose_action(&mut self, arena: &Arena, actor: CombatantId) -> Action {
+        or_flee(weakest_opponent(arena, actor), |target| {
+            strike(arena, actor, target)
+        })
+    }
+}
+
+/// Looks after itself first.
+///
+/// Heals, drinks potions and cures ailments when its health is low, And flees when it can't.
+/// Otherwise weakens and fights the strongest opponent.
+#[derive(Clone, Copy, Debug)]
+pub struct Defensive {
+    /// The health at or below which this combatant tends to itself.
+    pub threshold: u32,
+    /// Whether to flee when low on health with nothing left to recover with.
+    pub flee: bool,
+}
+
+impl Default for Defensive {
+    fn default() -> Self {
+        Defensive {
+            threshold: LOW_HEALTH,
+            flee: true,
+        }
+    }
+}
+
+impl Controller for Defensive {
+    fn choose_action(&mut self, arena: &Arena, actor: CombatantId) -> Action {
+        let combatant = match arena.get(actor) {
+            Some(combatant) => combatant,
+            None => return Action::Flee,
+        };
+
+        if combatant.character().health().current() <= self.threshold {
+            if let Some(ability) = heal_ability(arena, actor, actor) {
+                return Action::UseAbility {
+                    ability,
+                    target: actor,
+                };
+            }
+            if let Some(index) = consumable(arena, actor) {
+                return Action::UseConsumable(index);
+            }
+            if self.flee {
+                return Action::Flee;
+            }
+        }
+
+        or_flee(strongest_opponent(arena, actor), |target| {
+            afflict(arena, actor, target, |kind| {
+                kind == StatusKind::Weaken || kind.prevents_action()
+            })
+            .unwrap_or_else(|| strike(arena, actor, target))
+        })
+    }
+}
+
+/// Keeps its side alive.
+///
+/// Heals the most hurt ally below its threshold, Fights aggressively otherwise.
+#[derive(Clone, Copy, Debug)]
+pub struct Healer {
+    /// The health at or below which allies are healed.
+    pub threshold: u32,
+}
+
+impl Default for Healer {
+    fn default() -> Self {
+        Healer { threshold: 60 }
+    }
+}
+
+impl Controller for Healer {
+    fn choose_action(&mut self, arena: &Arena, actor: CombatantId) -> Action {
+        if let Some(ally) = hurt_ally(arena, actor, self.threshold) {
+            if let Some(ability) = heal_ability(arena, actor, ally) {
+                return Action::UseAbility {
+                    ability,
+                    target: ally,
+                };
+            }
+            if ally == actor {
+                if let Some(index) = consumable(arena, actor) {
+                    return Action::UseConsumable(index);
+                }
+            }
+        }
+        Aggressive.choose_action(arena, actor)
+    }
+}
+
+/// Fights from range.
+///
+/// Keeps the strongest opponent locked down with crowd control and picks off
+/// whoever can't fight back. Combatants without a ranged weapon fight aggressively.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Kiting;
+
+impl Controller for Kiting {
+    fn choose_action(&mut self, arena: &Arena, actor: CombatantId) -> Action {
+        let ranged = arena
+            .get(actor)
+            .and_then(|c| c.character().weapon())
+            .is_some_and(|w| w.weapon_type().is_ranged());
+        if !ranged {
+            return Aggressive.choose_action(arena, actor);
+        }
+
+        let strongest = match strongest_opponent(arena, actor) {
+            Some(target) => target,
+            None => return Action::Flee,
+        };
+        let locked = |id: CombatantId| {
+            arena
+                .get(id)
+                .is_some_and(|c| c.statuses().incapacitated_by().is_some())
+        };
+        if !locked(strongest) {
+            if let Some(action) = afflict(arena, actor, strongest, |kind| kind.prevents_action()) {
+                return action;
+            }
+        }
+
+        let target = arena
+            .active(actor.side.opposite())
+            .map(|(id, _)| id)
+            .find(|&id| locked(id))
+            .unwrap_or(strongest);
+        strike(arena, actor, target)
+    }
+}
+
+/// How much a [`Utility`] policy values each kind of outcome.
+///
+/// Every candidate action is scored by the amount of its outcome multiplied by its weight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Weights {
+    /// Per point of damage dealt.
+    pub damage: u32,
+    /// Per point of missing health healed.
+    pub heal: u32,
+    /// Per turn an opponent is incapacitated for.
+    pub control: u32,
+    /// Per point of damage over time or weakening applied.
+    pub affliction: u32,
+    /// For curing damage over time effects on itself.
+    pub cure: u32,
+    /// Per point of health below [`LOW_HEALTH`] when fleeing.
+    pub flee: u32,
+    /// Per point of health an opponent is missing, Used to focus down weakened opponents.
+    pub focus: u32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            damage: 4,
+            heal: 3,
+            control: 40,
+            affliction: 2,
+            cure: 30,
+            flee: 0,
+            focus: 1,
+        }
+    }
+}
+
+/// Scores every possible action with designer tuned [`Weights`] and picks the best one.
+///
+/// ```
+/// use combat::ai::{Utility, Weights};
+///
+/// // A cowardly healer.
+/// let policy = Utility::new(Weights {
+///     heal: 10,
+///     flee: 5,
+///     ..Weights::default()
+/// });
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Utility {
+    weights: Weights,
+}
+
+impl Utility {
+    pub fn new(weights: Weights) -> Utility {
+        Utility { weights }
+    }
+
+    pub fn weights(&self) -> &Weights {
+        &self.weights
+    }
+
+    pub fn weights_mut(&mut self) -> &mut Weights {
+        &mut self.weights
+    }
+
+    /// Returns every candidate action and its score.
+    pub fn score(&self, arena: &Arena, actor: CombatantId) -> Vec<(Action, u32)> {
+        let w = &self.weights;
+        let combatant = match arena.get(actor) {
+            Some(combatant) => combatant,
+            None => return vec![(Action::Flee, 0)],
+        };
+        let character = combatant.character();
+        let damage = combatant.statuses().weaken(character.attack_damage());
+        let focus = |target: &Combatant| missing_health(target).saturating_mul(w.focus);
+        let mut scores = Vec::new();
+
+        for (id, target) in arena.active(actor.side.opposite()) {
+            if !arena.can_reach(actor, id) {
+                continue;
+            }
+            scores.push((
+                Action::Attack(id),
+                damage
+                    .saturating_mul(w.damage)
+                    .saturating_add(focus(target)),
+            ));
+        }
+
+        for (ability, found) in character.abilities().iter().enumerate() {
+            if !combatant.can_use(ability) {
+                continue;
+            }
+            let targets: Vec<(CombatantId, &Combatant)> = match found.target() {
+                AbilityTarget::Enemy => arena.active(actor.side.opposite()).collect(),
+                AbilityTarget::Ally => arena.active(actor.side).collect(),
+                AbilityTarget::Caster => vec![(actor, combatant)],
+            };
+            for (id, target) in targets {
+                let points = match found.effect() {
+                    AbilityEffect::Damage(amount) => combatant
+                        .statuses()
+                        .weaken(amount)
+                        .saturating_mul(w.damage)
+                        .saturating_add(focus(target)),
+                    AbilityEffect::Heal(amount) => {
+                        amount.min(missing_health(target)).saturating_mul(w.heal)
+                    }
+                    AbilityEffect::Apply(effect) if target.statuses().has(effect.kind) => 0,
+                    AbilityEffect::Apply(effect) if effect.kind.prevents_action() => {
+                        effect.duration.saturating_mul(w.control)
+                    }
+                    AbilityEffect::Apply(effect) => effect
+                        .duration
+                        .saturating_mul(effect.potency)
+                        .saturating_mul(w.affliction),
+                };
+                scores.push((
+                    Action::UseAbility {
+                        ability,
+                        target: id,
+                    },
+                    points,
+                ));
+            }
+        }
+
+        let afflicted = is_afflicted(combatant);
+        for (index, item) in arena.inventory(actor).get_consumables().iter().enumerate() {
+            let kind = item.item().consumable_type();
+            let mut points = kind
+                .heal_amount()
+                .min(missing_health(combatant))
+                .saturating_mul(w.heal);
+            if afflicted && kind.cures() {
+                points = points.saturating_add(w.cure);
+            }
+            scores.push((Action::UseConsumable(index), points));
+        }
+
+        let danger = LOW_HEALTH.saturating_sub(character.health().current());
+        scores.push((Action::Flee, danger.saturating_mul(w.flee)));
+        scores
+    }
+}
+
+impl Controller for Utility {
+    fn choose_action(&mut self, arena: &Arena, actor: CombatantId) -> Action {
+        // The first of equally scored actions wins, Attacks come first.
+        let mut best: Option<(Action, u32)> = None;
+        for (action, points) in self.score(arena, actor) {
+            if best.is_none_or(|(_, most)| points > most) {
+                best = Some((action, points));
+            }
+        }
+        best.map_or(Action::Flee, |(action, _)| action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::Side;
+    use crate::{Battle, BattleOutcome};
+    use characters::{Char, CharFactory, DireWolf, FrostWraith, Ghoul, Kain, Tyr, Vamp};
+    use components::consumable::{Consumable, ConsumableType};
+
+    const ALLY: CombatantId = CombatantId {
+        side: Side::Allies,
+        index: 0,
+    };
+
+    fn enemy(index: usize) -> CombatantId {
+        CombatantId::new(Side::Enemies, index)
+    }
+
+    #[test]
+    fn test_aggressive() {
+        let mut wolf = DireWolf::new();
+        wolf.health_mut().drip(40);
+        let mut tyr = Tyr::new();
+        tyr.mana_mut().restore(100);
+        let arena = Arena::new(
+            vec![Box::new(tyr)],
+            vec![Box::new(Ghoul::new()), Box::new(wolf)],
+        );
+        // Cleave on the wounded wolf.
+        assert_eq!(
+            Aggressive.choose_action(&arena, ALLY),
+            Action::UseAbility {
+                ability: 0,
+                target: enemy(1)
+            }
+        );
+    }
+
+    #[test]
+    fn test_defensive() {
+        let mut kain = Kain::new();
+        kain.health_mut().drip(80);
+        kain.inventory_mut()
+            .put_consumable(Consumable::new(ConsumableType::HealthPotion))
+            .unwrap();
+        let arena = Arena::new(vec![Box::new(kain.clone())], vec![Box::new(Ghoul::new())]);
+        assert_eq!(
+            Defensive::default().choose_action(&arena, ALLY),
+            Action::UseConsumable(0)
+        );
+
+        kain.inventory_mut().take_consumable(0);
+        let arena = Arena::new(vec![Box::new(kain)], vec![Box::new(Ghoul::new())]);
+        assert_eq!(
+            Defensive::default().choose_action(&arena, ALLY),
+            Action::Flee
+        );
+    }
+
+    #[test]
+    fn test_healer() {
+        let mut tyr = Tyr::new();
+        tyr.health_mut().drip(60);
+        let mut vamp = Vamp::new();
+        vamp.mana_mut().restore(100);
+        let arena = Arena::new(
+            vec![Box::new(vamp), Box::new(tyr)],
+            vec![Box::new(Ghoul::new())],
+        );
+        assert_eq!(
+            Healer::default().choose_action(&arena, ALLY),
+            Action::UseAbility {
+                ability: 1,
+                target: CombatantId::new(Side::Allies, 1)
+            }
+        );
+    }
+
+    #[test]
+    fn test_kiting() {
+        let arena = Arena::new(
+            vec![Box::new(Tyr::new()), Box::new(DireWolf::new())],
+            vec![Box::new(FrostWraith::new())],
+        );
+        let actor = enemy(0);
+        // Freeze the hardest hitter.
+        assert_eq!(
+            Kiting.choose_action(&arena, actor),
+            Action::UseAbility {
+                ability: 0,
+                target: ALLY
+            }
+        );
+    }
+
+    #[test]
+    fn test_utility() {
+        let mut kain = Kain::new();
+        kain.health_mut().drip(90);
+        let arena = Arena::new(vec![Box::new(kain)], vec![Box::new(Ghoul::new())]);
+
+        let mut brave = Utility::default();
+        assert_ne!(brave.choose_action(&arena, ALLY), Action::Flee);
+        let mut coward = Utility::new(Weights {
+            flee: 100,
+            ..Weights::default()
+        });
+        assert_eq!(coward.choose_action(&arena, ALLY), Action::Flee);
+
+        for seed in 0..20 {
+            let mut battle = Battle::new(
+                vec![Box::new(Tyr::new()), Box::new(Vamp::new())],
+                vec![Box::new(Ghoul::new()), Box::new(FrostWraith::new())],
+                seed,
+            );
+            let outcome = battle.run(&mut Utility::default(), &mut Kiting).unwrap();
+            assert_ne!(outcome, BattleOutcome::Fled);
+        }
+    }
+}
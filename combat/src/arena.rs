@@ -0,0 +1,766 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::action::{Action, BattleEvent, BattleOutcome, DamageKind};
+use abilites::{Ability, AbilityEffect, AbilityTarget};
+use characters::{Char, ClassPassive, Party, Row};
+use components::{
+    items::MetaData, status::StatusEffects, EventBus, GameEvent, Inventory, Item, ResourceType,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The highest chance percentage a combatant can evade an attack with.
+const MAX_EVASION: u32 = 75;
+/// The base chance percentage of fleeing a battle.
+const FLEE_CHANCE: u32 = 50;
+
+/// The characters fighting on one side of a battle.
+pub(crate) type Characters = Vec<Box<dyn Char>>;
+
+/// The sides of a battle.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Side {
+    Allies,
+    Enemies,
+}
+
+impl Side {
+    /// Returns the opposing side.
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Allies => Side::Enemies,
+            Side::Enemies => Side::Allies,
+        }
+    }
+}
+
+/// Identifies a combatant by its side and position within that side.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CombatantId {
+    pub side: Side,
+    pub index: usize,
+}
+
+impl CombatantId {
+    pub fn new(side: Side, index: usize) -> CombatantId {
+        CombatantId { side, index }
+    }
+}
+
+/// A character taking part in a battle.
+#[derive(Clone)]
+pub struct Combatant {
+    pub(crate) character: Box<dyn Char>,
+    pub(crate) statuses: StatusEffects,
+    pub(crate) cooldowns: Vec<u32>,
+    pub(crate) fled: bool,
+    row: Row,
+    facing: Option<CombatantId>,
+}
+
+impl Combatant {
+    fn new(character: Box<dyn Char>, row: Row) -> Combatant {
+        Combatant {
+            cooldowns: vec![0; character.abilities().len()],
+            character,
+            statuses: StatusEffects::new(),
+            fled: false,
+            row,
+            facing: None,
+        }
+    }
+
+    pub fn character(&self) -> &dyn Char {
+        self.character.as_ref()
+    }
+
+    /// The status effects currently applied to this combatant.
+    pub fn statuses(&self) -> &StatusEffects {
+        &self.statuses
+    }
+
+    /// The remaining cooldown before the ability at the given index can be used again.
+    ///
+    /// This is in turns for turn based battles and in milliseconds for real time simulations.
+    pub fn cooldown(&self, ability: usize) -> u32 {
+        self.cooldowns.get(ability).copied().unwrap_or(0)
+    }
+
+    /// Whether the ability at the given index is off cooldown and affordable.
+    pub fn can_use(&self, ability: usize) -> bool {
+        match self.character.abilities().get(ability) {
+            Some(found) => {
+                self.cooldown(ability) == 0 && self.character.mana().can_spend(found.cost())
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        !self.character.health().is_killed()
+    }
+
+    pub fn has_fled(&self) -> bool {
+        self.fled
+    }
+
+    /// The formation row this combatant stands in.
+    pub fn row(&self) -> Row {
+        self.row
+    }
+
+    /// The opponent this combatant last attacked or cast an ability at.
+    ///
+    /// Attacks from anyone else come from behind.
+    pub fn facing(&self) -> Option<CombatantId> {
+        self.facing
+    }
+
+    /// Whether this combatant is still fighting, i.e., alive and hasn't fled.
+    pub fn is_active(&self) -> bool {
+        self.is_alive() && !self.fled
+    }
+}
+
+/// The combatants of one side and the party they came from, If any.
+#[derive(Clone)]
+struct Team {
+    combatants: Vec<Combatant>,
+    /// The party without its members, Who are fighting as the combatants.
+    party: Option<Party>,
+}
+
+impl Team {
+    fn new(characters: Characters) -> Team {
+        Team {
+            combatants: characters
+                .into_iter()
+                .map(|character| Combatant::new(character, Row::Front))
+                .collect(),
+            party: None,
+        }
+    }
+
+    fn from_party(mut party: Party) -> Team {
+        Team {
+            combatants: party
+                .drain()
+                .into_iter()
+                .map(|member| {
+                    let row = member.row();
+                    Combatant::new(member.into_character(), row)
+                })
+                .collect(),
+            party: Some(party),
+        }
+    }
+
+    fn into_party(self) -> Party {
+        let capacity = self.combatants.len();
+        let mut party = self.party.unwrap_or_else(|| Party::with_capacity(capacity));
+        for combatant in self.combatants {
+            // Everyone fit before the battle.
+            party.join(combatant.character, combatant.row).ok();
+        }
+        party
+    }
+}
+
+/// The combatants of both sides of a battle.
+///
+/// This is shared between battle modes and is what [`crate::Controller`]s see.
+#[derive(Clone)]
+pub struct Arena {
+    allies: Team,
+    enemies: Team,
+}
+
+impl Arena {
+    pub(crate) fn new(allies: Characters, enemies: Characters) -> Arena {
+        Arena {
+            allies: Team::new(allies),
+            enemies: Team::new(enemies),
+        }
+    }
+
+    pub(crate) fn from_parties(allies: Party, enemies: Party) -> Arena {
+        Arena {
+            allies: Team::from_party(allies),
+            enemies: Team::from_party(enemies),
+        }
+    }
+
+    fn team(&self, side: Side) -> &Team {
+        match side {
+            Side::Allies => &self.allies,
+            Side::Enemies => &self.enemies,
+        }
+    }
+
+    /// Returns all combatants of a side.
+    pub fn side(&self, side: Side) -> &[Combatant] {
+        &self.team(side).combatants
+    }
+
+    /// Returns the party a side came from if any, Its members are the side's combatants.
+    pub fn party(&self, side: Side) -> Option<&Party> {
+        self.team(side).party.as_ref()
+    }
+
+    /// The inventory the given combatant uses, Its party's shared inventory if any.
+    pub fn inventory(&self, id: CombatantId) -> &Inventory {
+        self.party(id.side)
+            .and_then(Party::shared_inventory)
+            .unwrap_or_else(|| self.combatant(id).character.inventory())
+    }
+
+    /// The initiative of a combatant including its party's buffs.
+    pub fn initiative(&self, id: CombatantId) -> u32 {
+        let bonus = self.party(id.side).map_or(0, Party::initiative_bonus);
+        self.combatant(id)
+            .character
+            .effective_stats()
+            .initiative()
+            .saturating_add(bonus)
+    }
+
+    /// Whether the actor can reach the target with a basic attack.
+    ///
+    /// Melee attacks can't reach the back row while the target's front row is still fighting.
+    pub fn can_reach(&self, actor: CombatantId, target: CombatantId) -> bool {
+        let ranged = self
+            .combatant(actor)
+            .character
+            .weapon()
+            .is_some_and(|w| w.weapon_type().is_ranged());
+        ranged
+            || self.combatant(target).row == Row::Front
+            || self.active(target.side).all(|(_, c)| c.row != Row::Front)
+    }
+
+    /// Returns the combatant with the given id if any.
+    pub fn get(&self, id: CombatantId) -> Option<&Combatant> {
+        self.side(id.side).get(id.index)
+    }
+
+    /// The gameplay event of a battle event if it has one.
+    pub fn game_event(&self, event: &BattleEvent) -> Option<GameEvent> {
+        let name = |id: CombatantId| self.get(id).map(|c| c.character().event_name());
+        Some(match *event {
+            BattleEvent::Damage {
+                source,
+                target,
+                amount,
+                kind,
+            } => GameEvent::DamageDealt {
+                source: name(source),
+                target: name(target)?,
+                amount,
+                ammo: match kind {
+                    DamageKind::Weapon(_, ammo) => Some(ammo),
+                    DamageKind::Unarmed | DamageKind::Ability(_) => None,
+                },
+            },
+            BattleEvent::StatusDamage { target, amount, .. } => GameEvent::DamageDealt {
+                source: None,
+                target: name(target)?,
+                amount,
+                ammo: None,
+            },
+            BattleEvent::Healed { target, amount, .. }
+            | BattleEvent::Regenerated {
+                target,
+                health: amount,
+                ..
+            } if amount > 0 => GameEvent::Healed {
+                target: name(target)?,
+                amount,
+            },
+            BattleEvent::StatusApplied { target, kind } => GameEvent::StatusApplied {
+                target: name(target)?,
+                kind,
+            },
+            BattleEvent::Died(target) => GameEvent::Died {
+                target: name(target)?,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Emit the gameplay events of the battle events to the event bus.
+    pub fn publish(&self, events: &[BattleEvent], bus: &mut EventBus) {
+        for event in events.iter().filter_map(|e| self.game_event(e)) {
+            bus.emit(event);
+        }
+    }
+
+    /// An iterator over the combatants of a side that are still fighting.
+    pub fn active(&self, side: Side) -> impl Iterator<Item = (CombatantId, &Combatant)> {
+        self.side(side)
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_active())
+            .map(move |(index, c)| (CombatantId::new(side, index), c))
+    }
+
+    /// Returns the outcome of the battle if either side is no longer fighting.
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        if self.active(Side::Enemies).next().is_none() {
+            Some(BattleOutcome::Victory)
+        } else if self.active(Side::Allies).next().is_none() {
+            if self.allies.combatants.iter().any(Combatant::is_alive) {
+                Some(BattleOutcome::Fled)
+            } else {
+                Some(BattleOutcome::Defeat)
+            }
+        } else {
+            None
+        }
+    }
+
+    /// The experience rewarded for the enemies that died so far.
+    pub fn experience_reward(&self) -> u32 {
+        self.enemies
+            .combatants
+            .iter()
+            .filter(|c| !c.is_alive())
+            .map(|c| c.character.experience_reward())
+            .fold(0, u32::saturating_add)
+    }
+
+    pub(crate) fn into_sides(self) -> (Characters, Characters) {
+        (
+            self.allies
+                .combatants
+                .into_iter()
+                .map(|c| c.character)
+                .collect(),
+            self.enemies
+                .combatants
+                .into_iter()
+                .map(|c| c.character)
+                .collect(),
+        )
+    }
+
+    pub(crate) fn into_parties(self) -> (Party, Party) {
+        (self.allies.into_party(), self.enemies.into_party())
+    }
+
+    pub(crate) fn combatant(&self, id: CombatantId) -> &Combatant {
+        &self.side(id.side)[id.index]
+    }
+
+    pub(crate) fn combatant_mut(&mut self, id: CombatantId) -> &mut Combatant {
+        match id.side {
+            Side::Allies => &mut self.allies.combatants[id.index],
+            Side::Enemies => &mut self.enemies.combatants[id.index],
+        }
+    }
+
+    /// All active combatants, Allies first.
+    pub(crate) fn active_ids(&self) -> Vec<CombatantId> {
+        self.active(Side::Allies)
+            .chain(self.active(Side::Enemies))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    pub(crate) fn validate(&self, actor: CombatantId, action: Action) -> anyhow::Result<()> {
+        let combatant = self.combatant(actor);
+
+        match action {
+            Action::Attack(target) => {
+                self.validate_target(target, actor.side.opposite())?;
+                if self.can_reach(actor, target) {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!("{:?} is out of reach", target))
+                }
+            }
+            Action::UseAbility { ability, target } => {
+                let found = combatant
+                    .character
+                    .abilities()
+                    .get(ability)
+                    .ok_or_else(|| anyhow::anyhow!("No ability at index {}", ability))?;
+                if combatant.cooldown(ability) > 0 {
+                    return Err(anyhow::anyhow!("{} is on cooldown", found.name()));
+                }
+                if !combatant.character.mana().can_spend(found.cost()) {
+                    return Err(anyhow::anyhow!(
+                        "Not enough {} for {}",
+                        combatant.character.mana().resource().name(),
+                        found.name()
+                    ));
+                }
+                self.validate_ability_target(actor, found, target)
+            }
+            Action::UseConsumable(index) => {
+                if index < self.inventory(actor).get_consumables().len() {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!("No consumable at index {}", index))
+                }
+            }
+            Action::Flee => Ok(()),
+        }
+    }
+
+    pub(crate) fn validate_ability_target(
+        &self,
+        actor: CombatantId,
+        ability: &Ability,
+        target: CombatantId,
+    ) -> anyhow::Result<()> {
+        match ability.target() {
+            AbilityTarget::Enemy => self.validate_target(target, actor.side.opposite()),
+            AbilityTarget::Ally => self.validate_target(target, actor.side),
+            AbilityTarget::Caster => Ok(()),
+        }
+    }
+
+    fn validate_target(&self, target: CombatantId, side: Side) -> anyhow::Result<()> {
+        if target.side != side {
+            return Err(anyhow::anyhow!("{:?} is on the wrong side", target));
+        }
+        match self.get(target) {
+            Some(c) if c.is_active() => Ok(()),
+            Some(_) => Err(anyhow::anyhow!("{:?} is no longer fighting", target)),
+            None => Err(anyhow::anyhow!("{:?} does not exist", target)),
+        }
+    }
+
+    /// Spend the ability's mana and put it on the given cooldown.
+    pub(crate) fn start_ability(
+        &mut self,
+        actor: CombatantId,
+        index: usize,
+        cooldown: u32,
+    ) -> Ability {
+        let caster = self.combatant_mut(actor);
+        let ability = caster.character.abilities()[index];
+        // Validated before hand.
+        caster.character.mana_mut().spend(ability.cost()).ok();
+        caster.cooldowns[index] = cooldown;
+        ability
+    }
+
+    pub(crate) fn attack<R: Rng>(
+        &mut self,
+        actor: CombatantId,
+        target: CombatantId,
+        rng: &mut R,
+    ) -> Vec<BattleEvent> {
+        // Whether the target was facing someone else when attacked.
+        let from_behind = self.combatant(target).facing != Some(actor);
+        self.combatant_mut(actor).facing = Some(target);
+
+        let evasion = self
+            .combatant(target)
+            .character
+            .effective_stats()
+            .evasion
+            .saturating_add(self.party(target.side).map_or(0, Party::evasion_bonus));
+        if rng.gen_range(0..100) < evasion.min(MAX_EVASION) {
+            return vec![BattleEvent::Evaded {
+                source: actor,
+                target,
+            }];
+        }
+
+        let attacker = self.combatant(actor);
+        let mut damage = self.buffed(
+            actor,
+            attacker.statuses.weaken(attacker.character.attack_damage()),
+        );
+        let weapon = attacker.character.weapon().copied();
+        if let Some(weapon) = weapon {
+            let resistances = self.combatant(target).character.resistances();
+            damage = resistances.mitigate(damage, weapon.ammo_type()).max(1);
+        }
+        let weapon_type = weapon.map(|w| w.weapon_type());
+        let kind = weapon.map_or(DamageKind::Unarmed, |w| {
+            DamageKind::Weapon(w.weapon_type(), w.ammo_type())
+        });
+        let status_chance = attacker
+            .character
+            .talents()
+            .map_or(0, |talents| talents.status_chance());
+
+        let mut events = Vec::new();
+        if let ClassPassive::Backstab { chance, multiplier } = attacker.character.class().passive()
+        {
+            if from_behind && rng.gen_range(0..100) < chance {
+                damage = damage.saturating_mul(multiplier) / 100;
+                events.push(BattleEvent::CriticalHit {
+                    source: actor,
+                    target,
+                });
+            }
+        }
+        if let ClassPassive::Fortitude(reduction) =
+            self.combatant(target).character.class().passive()
+        {
+            damage = (damage.saturating_mul(100 - reduction.min(100)) / 100).max(1);
+        }
+
+        let before = self.combatant(target).character.health().current();
+        events.extend(self.damage(actor, target, damage, kind));
+        let lost = before - self.combatant(target).character.health().current();
+        for weapon in self.combatant_mut(actor).character.equipment_mut().wear(1) {
+            events.push(BattleEvent::WeaponBroke {
+                owner: actor,
+                weapon: weapon.name(),
+            });
+        }
+        self.gain_resource(actor, |resource| resource.on_hit());
+        self.gain_resource(target, |resource| resource.on_hurt());
+
+        if let ClassPassive::Lifesteal(percentage) =
+            self.combatant(actor).character.class().passive()
+        {
+            let amount = self.heal(actor, lost.saturating_mul(percentage) / 100);
+            if amount > 0 {
+                events.push(BattleEvent::Healed {
+                    source: actor,
+                    target: actor,
+                    amount,
+                });
+            }
+        }
+
+        if let Some(weapon_type) = weapon_type {
+            let (effect, chance) = weapon_type.on_hit_status();
+            if self.combatant(target).is_alive()
+                && rng.gen_range(0..100) < chance.saturating_add(status_chance)
+            {
+                self.combatant_mut(target).statuses.apply(effect);
+                events.push(BattleEvent::StatusApplied {
+                    target,
+                    kind: effect.kind,
+                });
+            }
+        }
+        events
+    }
+
+    /// Apply an ability's effect.
+    pub(crate) fn cast(
+        &mut self,
+        actor: CombatantId,
+        ability: Ability,
+        target: CombatantId,
+    ) -> Vec<BattleEvent> {
+        let target = match ability.target() {
+            AbilityTarget::Caster => actor,
+            _ => target,
+        };
+        if target.side != actor.side {
+            self.combatant_mut(actor).facing = Some(target);
+        }
+        let mut events = vec![BattleEvent::AbilityUsed {
+            caster: actor,
+            ability: ability.name(),
+        }];
+
+        match ability.effect() {
+            AbilityEffect::Damage(damage) => {
+                let damage = self.buffed(actor, self.combatant(actor).statuses.weaken(damage));
+                events.extend(self.damage(
+                    actor,
+                    target,
+                    damage,
+                    DamageKind::Ability(ability.name()),
+                ));
+            }
+            AbilityEffect::Heal(amount) => {
+                let amount = self.heal(target, amount);
+                events.push(BattleEvent::Healed {
+                    source: actor,
+                    target,
+                    amount,
+                });
+            }
+            AbilityEffect::Apply(mut effect) => {
+                if let ClassPassive::Affliction(turns) =
+                    self.combatant(actor).character.class().passive()
+                {
+                    effect.duration = effect.duration.saturating_add(turns);
+                }
+                self.combatant_mut(target).statuses.apply(effect);
+                events.push(BattleEvent::StatusApplied {
+                    target,
+                    kind: effect.kind,
+                });
+            }
+        }
+        events
+    }
+
+    pub(crate) fn use_consumable(&mut self, actor: CombatantId, index: usize) -> Vec<BattleEvent> {
+        let consumable_type = match self.inventory_mut(actor).take_consumable(index) {
+            Some(consumable) => consumable.consumable_type(),
+            None => return Vec::new(),
+        };
+        if consumable_type.cures() {
+            self.combatant_mut(actor).statuses.clear();
+        }
+
+        let mut events = vec![BattleEvent::ConsumableUsed {
+            user: actor,
+            consumable: consumable_type,
+        }];
+        if consumable_type.heal_amount() > 0 {
+            let amount = self.heal(actor, consumable_type.heal_amount());
+            events.push(BattleEvent::Healed {
+                source: actor,
+                target: actor,
+                amount,
+            });
+        }
+        events
+    }
+
+    pub(crate) fn flee<R: Rng>(&mut self, actor: CombatantId, rng: &mut R) -> Vec<BattleEvent> {
+        let speed = self
+            .combatant(actor)
+            .character
+            .effective_stats()
+            .movement_speed;
+        let chance = FLEE_CHANCE + speed.min(100 - FLEE_CHANCE);
+        if rng.gen_range(0..100) < chance {
+            self.combatant_mut(actor).fled = true;
+            vec![BattleEvent::Fled(actor)]
+        } else {
+            vec![BattleEvent::FleeFailed(actor)]
+        }
+    }
+
+    /// Advance the combatant's status effects by one tick.
+    pub(crate) fn tick_statuses(&mut self, actor: CombatantId) -> Vec<BattleEvent> {
+        let combatant = self.combatant_mut(actor);
+        let ticks = combatant.statuses.tick(combatant.character.health_mut());
+
+        let mut events: Vec<BattleEvent> = ticks
+            .into_iter()
+            .map(|(kind, amount)| BattleEvent::StatusDamage {
+                target: actor,
+                kind,
+                amount,
+            })
+            .collect();
+        if !combatant.is_alive() {
+            combatant.statuses.clear();
+            events.push(BattleEvent::Died(actor));
+        }
+        events
+    }
+
+    /// Restore the per turn amount of the combatant's resource.
+    pub(crate) fn replenish(&mut self, actor: CombatantId) {
+        self.gain_resource(actor, |resource| resource.per_turn());
+    }
+
+    fn gain_resource(&mut self, id: CombatantId, amount: impl Fn(ResourceType) -> u32) {
+        let mana = self.combatant_mut(id).character.mana_mut();
+        let amount = amount(mana.resource());
+        mana.restore(amount);
+    }
+
+    /// Restore health and mana from the character's `hp5` and `mp5` stats.
+    pub(crate) fn regenerate(&mut self, actor: CombatantId) -> Vec<BattleEvent> {
+        let stats = self.combatant(actor).character.effective_stats();
+        let health = self.heal(actor, stats.hp5);
+        let mana = self
+            .combatant_mut(actor)
+            .character
+            .mana_mut()
+            .restore(stats.mp5);
+
+        if health > 0 || mana > 0 {
+            vec![BattleEvent::Regenerated {
+                target: actor,
+                health,
+                mana,
+            }]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn inventory_mut(&mut self, id: CombatantId) -> &mut Inventory {
+        let team = match id.side {
+            Side::Allies => &mut self.allies,
+            Side::Enemies => &mut self.enemies,
+        };
+        match team.party.as_mut().and_then(Party::shared_inventory_mut) {
+            Some(shared) => shared,
+            None => team.combatants[id.index].character.inventory_mut(),
+        }
+    }
+
+    /// Apply the actor's party damage buffs.
+    fn buffed(&self, actor: CombatantId, damage: u32) -> u32 {
+        let bonus = self.party(actor.side).map_or(0, Party::damage_bonus);
+        damage.saturating_mul(bonus.saturating_add(100)) / 100
+    }
+
+    fn damage(
+        &mut self,
+        source: CombatantId,
+        target: CombatantId,
+        amount: u32,
+        kind: DamageKind,
+    ) -> Vec<BattleEvent> {
+        let combatant = self.combatant_mut(target);
+        let before = combatant.character.health().current();
+        let after = combatant.character.health_mut().drip(amount);
+
+        let mut events = vec![BattleEvent::Damage {
+            source,
+            target,
+            amount: before - after,
+            kind,
+        }];
+        if !combatant.is_alive() {
+            combatant.statuses.clear();
+            events.push(BattleEvent::Died(target));
+        }
+        events
+    }
+
+    fn heal(&mut self, target: CombatantId, amount: u32) -> u32 {
+        let health = self.combatant_mut(target).character.health_mut();
+        let before = health.current();
+        health.incr(amount).unwrap_or(before) - before
+    }
+}
@@ -0,0 +1,657 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    action::{Action, BattleEvent, BattleOutcome, Controller},
+    arena::{Arena, Characters, CombatantId, Side},
+    replay::CountingRng,
+};
+use characters::Party;
+use rand::Rng;
+use std::{cmp::Reverse, collections::VecDeque};
+
+/// The highest bonus random roll added to initiative every round.
+const INITIATIVE_ROLL: u32 = 5;
+/// The default amount of rounds before a battle ends in a draw.
+pub(crate) const MAX_ROUNDS: u32 = 100;
+
+/// A turn based battle between two sides of characters.
+///
+/// Turns are ordered every round by the characters' [`characters::Stats::initiative`].
+/// All random rolls are made from a seeded generator, So the same seed and
+/// the same actions always lead to the same battle.
+///
+/// ```
+/// use characters::{Char, CharFactory, Kain, Tyr};
+/// use combat::{Action, Arena, Battle, CombatantId};
+///
+/// fn attack(arena: &Arena, actor: CombatantId) -> Action {
+///     Action::Attack(arena.active(actor.side.opposite()).next().unwrap().0)
+/// }
+///
+/// let mut battle = Battle::new(vec![Box::new(Tyr::new())], vec![Box::new(Kain::new())], 7);
+/// let outcome = battle.run(&mut attack, &mut attack).unwrap();
+/// println!("{:?}", outcome);
+/// ```
+pub struct Battle {
+    arena: Arena,
+    queue: VecDeque<CombatantId>,
+    round: u32,
+    max_rounds: u32,
+    rng: CountingRng,
+}
+
+impl Battle {
+    /// Creates a new battle between two sides.
+    pub fn new(allies: Characters, enemies: Characters, seed: u64) -> Battle {
+        Battle::with_arena(Arena::new(allies, enemies), seed)
+    }
+
+    /// Creates a new battle between two parties.
+    ///
+    /// Party formations, buffs and shared inventories take effect during the battle.
+    pub fn with_parties(allies: Party, enemies: Party, seed: u64) -> Battle {
+        Battle::with_arena(Arena::from_parties(allies, enemies), seed)
+    }
+
+    fn with_arena(arena: Arena, seed: u64) -> Battle {
+        Battle {
+            arena,
+            queue: VecDeque::new(),
+            round: 0,
+            max_rounds: MAX_ROUNDS,
+            rng: CountingRng::new(seed),
+        }
+    }
+
+    /// Set the amount of rounds before the battle ends in a draw.
+    pub fn set_max_rounds(&mut self, rounds: u32) {
+        self.max_rounds = rounds;
+    }
+
+    /// The current round, Starting at 1 once the first turn is taken.
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// The amount of random values drawn so far.
+    pub fn rolls(&self) -> u64 {
+        self.rng.rolls()
+    }
+
+    /// Returns the combatants of both sides.
+    pub fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    /// Returns the outcome of the battle if it's over.
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        match self.arena.outcome() {
+            Some(outcome) => Some(outcome),
+            None if self.round > self.max_rounds => Some(BattleOutcome::Draw),
+            None => None,
+        }
+    }
+
+    /// Run the battle until it's over.
+    pub fn run(
+        &mut self,
+        allies: &mut dyn Controller,
+        enemies: &mut dyn Controller,
+    ) -> anyhow::Result<BattleOutcome> {
+        loop {
+            if let Some(outcome) = self.outcome() {
+                return Ok(outcome);
+            }
+            self.next_turn(allies, enemies)?;
+        }
+    }
+
+    /// The combatant whose turn is next, `None` if the battle is over.
+    ///
+    /// Starts the next round if needed, Which rolls its turn order just like [`Battle::next_turn`] would.
+    /// Useful for controllers waiting on a player's input.
+    pub fn whose_turn(&mut self) -> Option<CombatantId> {
+        if self.outcome().is_some() {
+            return None;
+        }
+        let actor = self.next_actor()?;
+        self.queue.push_front(actor);
+        Some(actor)
+    }
+
+    /// Take the next combatant's turn.
+    ///
+    /// If the chosen action is invalid an error is returned and
+    /// the same combatant is asked again on the next call.
+    ///
+    /// ## Returns
+    /// [`Vec<BattleEvent>`] Everything that happened during the turn.
+    pub fn next_turn(
+        &mut self,
+        allies: &mut dyn Controller,
+        enemies: &mut dyn Controller,
+    ) -> anyhow::Result<Vec<BattleEvent>> {
+        if self.outcome().is_some() {
+            return Err(anyhow::anyhow!("Battle is already over"));
+        }
+
+        let actor = match self.next_actor() {
+            Some(actor) => actor,
+            // Round limit reached.
+            None => return Ok(Vec::new()),
+        };
+
+        if let Some(kind) = self.arena.combatant(actor).statuses.incapacitated_by() {
+            let mut events = vec![BattleEvent::Incapacitated {
+                target: actor,
+                kind,
+            }];
+            events.extend(self.arena.tick_statuses(actor));
+            self.end_turn(actor);
+            return Ok(events);
+        }
+
+        let action = match actor.side {
+            Side::Allies => allies.choose_action(&self.arena, actor),
+            Side::Enemies => enemies.choose_action(&self.arena, actor),
+        };
+        if let Err(err) = self.arena.validate(actor, action) {
+            self.queue.push_front(actor);
+            return Err(err);
+        }
+
+        let mut events = self.arena.tick_statuses(actor);
+        if self.arena.combatant(actor).is_alive() {
+            events.extend(self.resolve(actor, action));
+        }
+        self.end_turn(actor);
+        Ok(events)
+    }
+
+    /// Consume the battle returning the characters of both sides.
+    pub fn into_sides(self) -> (Characters, Characters) {
+        self.arena.into_sides()
+    }
+
+    /// Consume the battle returning both sides as parties.
+    ///
+    /// Sides that weren't created from parties are returned as new parties.
+    pub fn into_parties(self) -> (Party, Party) {
+        self.arena.into_parties()
+    }
+
+    fn next_actor(&mut self) -> Option<CombatantId> {
+        loop {
+            match self.queue.pop_front() {
+                Some(actor) if self.arena.combatant(actor).is_active() => return Some(actor),
+                Some(_) => continue,
+                None => {
+                    self.round += 1;
+                    if self.round > self.max_rounds {
+                        return None;
+                    }
+                    self.queue = self.turn_order();
+                }
+            }
+        }
+    }
+
+    fn turn_order(&mut self) -> VecDeque<CombatantId> {
+        let mut order: Vec<(u32, CombatantId)> = self
+            .arena
+            .active_ids()
+            .into_iter()
+            .map(|id| {
+                let initiative = self.arena.initiative(id);
+                let roll = self.rng.gen_range(0..=INITIATIVE_ROLL);
+                (initiative.saturating_add(roll), id)
+            })
+            .collect();
+        order.sort_by_key(|(initiative, _)| Reverse(*initiative));
+        order.into_iter().map(|(_, id)| id).collect()
+    }
+
+    fn resolve(&mut self, actor: CombatantId, action: Action) -> Vec<BattleEvent> {
+        match action {
+            Action::Attack(target) => self.arena.attack(actor, target, &mut self.rng),
+            Action::UseAbility { ability, target } => {
+                let cooldown =
+                    self.arena.combatant(actor).character.abilities()[ability].cooldown();
+                // Cooldowns tick down at the end of this turn.
+                let ability = self.arena.start_ability(actor, ability, cooldown + 1);
+                self.arena.cast(actor, ability, target)
+            }
+            Action::UseConsumable(index) => self.arena.use_consumable(actor, index),
+            Action::Flee => self.arena.flee(actor, &mut self.rng),
+        }
+    }
+
+    fn end_turn(&mut self, actor: CombatantId) {
+        for cooldown in self.arena.combatant_mut(actor).cooldowns.iter_mut() {
+            *cooldown = cooldown.saturating_sub(1);
+        }
+        self.arena.replenish(actor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::DamageKind;
+    use characters::{
+        Bestiary, Char, CharFactory, Ghoul, Imp, Kain, Party, PartyBuff, Row, Susanoo, Tyr, Vamp,
+    };
+    use components::consumable::{Consumable, ConsumableType};
+    use components::{status::StatusKind, EquipSlot, Inventory, Item, Weapon, WeaponType};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn attack_first(arena: &Arena, actor: CombatantId) -> Action {
+        Action::Attack(arena.active(actor.side.opposite()).next().unwrap().0)
+    }
+
+    fn duel(seed: u64, allies: (Vamp, Kain), enemies: (Tyr, Susanoo)) -> (BattleOutcome, u32, u32) {
+        let mut battle = Battle::new(
+            vec![Box::new(allies.0), Box::new(allies.1)],
+            vec![Box::new(enemies.0), Box::new(enemies.1)],
+            seed,
+        );
+        let outcome = battle.run(&mut attack_first, &mut attack_first).unwrap();
+        let (allies, enemies) = battle.into_sides();
+        let health = |side: Vec<Box<dyn Char>>| side.iter().map(|c| c.health().current()).sum();
+        (outcome, health(allies), health(enemies))
+    }
+
+    #[test]
+    fn test_simulated_battles() {
+        // Starter weapons roll a random rarity, Keep the same characters for every run.
+        let allies = (Vamp::new(), Kain::new());
+        let enemies = (Tyr::new(), Susanoo::new());
+
+        for seed in 0..200 {
+            let (outcome, ally_health, enemy_health) = duel(seed, allies.clone(), enemies.clone());
+            match outcome {
+                BattleOutcome::Victory => assert_eq!(enemy_health, 0),
+                BattleOutcome::Defeat => assert_eq!(ally_health, 0),
+                _ => panic!("Unexpected outcome {:?}", outcome),
+            }
+            // Same seed, Same battle.
+            assert_eq!(
+                duel(seed, allies.clone(), enemies.clone()),
+                (outcome, ally_health, enemy_health)
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_action() {
+        let mut tyr = Tyr::new();
+        tyr.mana_mut().restore(100);
+        let mut battle = Battle::new(vec![Box::new(tyr)], vec![Box::new(Kain::new())], 0);
+        let tyr = CombatantId::new(Side::Allies, 0);
+        let kain = CombatantId::new(Side::Enemies, 0);
+
+        let mut attack_self = |_: &Arena, actor: CombatantId| Action::Attack(actor);
+        assert!(battle
+            .next_turn(&mut attack_self, &mut attack_first)
+            .and_then(|_| battle.next_turn(&mut attack_self, &mut attack_first))
+            .is_err());
+
+        let mut cleave = |_: &Arena, _: CombatantId| Action::UseAbility {
+            ability: 0,
+            target: kain,
+        };
+        let expected = vec![
+            BattleEvent::AbilityUsed {
+                caster: tyr,
+                ability: "Cleave",
+            },
+            BattleEvent::Damage {
+                source: tyr,
+                target: kain,
+                amount: 22,
+                kind: DamageKind::Ability("Cleave"),
+            },
+        ];
+        while battle.next_turn(&mut cleave, &mut attack_first).unwrap() != expected {}
+        assert_eq!(battle.arena.combatant(tyr).cooldown(0), 2);
+
+        // Still on cooldown.
+        while battle.arena.combatant(tyr).cooldown(0) > 0 {
+            if let Err(err) = battle.next_turn(&mut cleave, &mut attack_first) {
+                assert!(err.to_string().contains("cooldown"));
+                battle
+                    .next_turn(&mut attack_first, &mut attack_first)
+                    .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_whose_turn() {
+        let (tyr, vamp, ghoul, imp) = (Tyr::new(), Vamp::new(), Ghoul::new(), Imp::new());
+        let sides = || -> (Characters, Characters) {
+            (
+                vec![Box::new(tyr.clone()), Box::new(vamp.clone())],
+                vec![Box::new(ghoul.clone()), Box::new(imp.clone())],
+            )
+        };
+        let (allies, enemies) = sides();
+        let mut peeked = Battle::new(allies, enemies, 3);
+        let (allies, enemies) = sides();
+        let mut battle = Battle::new(allies, enemies, 3);
+
+        while let Some(actor) = peeked.whose_turn() {
+            assert_eq!(peeked.whose_turn(), Some(actor));
+            let mut expect = |arena: &Arena, asked: CombatantId| {
+                assert_eq!(asked, actor);
+                attack_first(arena, asked)
+            };
+            // Only incapacitated combatants skip being asked.
+            let events = peeked.next_turn(&mut expect.clone(), &mut expect).unwrap();
+            assert_eq!(
+                battle
+                    .next_turn(&mut attack_first, &mut attack_first)
+                    .unwrap(),
+                events
+            );
+        }
+        assert!(peeked.outcome().is_some());
+        assert_eq!(battle.outcome(), peeked.outcome());
+    }
+
+    #[test]
+    fn test_consumable_and_flee() {
+        let mut vamp = Vamp::new();
+        vamp.health_mut().drip(50);
+        vamp.inventory_mut()
+            .put_consumable(Consumable::new(ConsumableType::HealthPotion))
+            .unwrap();
+
+        let mut battle = Battle::new(vec![Box::new(vamp)], vec![Box::new(Kain::new())], 3);
+        let mut drink = |_: &Arena, _: CombatantId| Action::UseConsumable(0);
+        let mut flee = |_: &Arena, _: CombatantId| Action::Flee;
+
+        let used = (0..10)
+            .flat_map(|_| battle.next_turn(&mut drink, &mut attack_first).unwrap())
+            .any(|event| {
+                event
+                    == BattleEvent::ConsumableUsed {
+                        user: CombatantId::new(Side::Allies, 0),
+                        consumable: ConsumableType::HealthPotion,
+                    }
+            });
+        assert!(used);
+        let vamp = battle.arena.combatant(CombatantId::new(Side::Allies, 0));
+        assert!(vamp.character().inventory().get_consumables().is_empty());
+
+        // Drinking again fails, The potion is gone.
+        assert!((0..2).any(|_| battle.next_turn(&mut drink, &mut attack_first).is_err()));
+
+        assert_eq!(
+            battle.run(&mut flee, &mut attack_first).unwrap(),
+            BattleOutcome::Fled
+        );
+    }
+
+    #[test]
+    fn test_monsters() {
+        let bestiary = Bestiary::default();
+        let enemies = vec![
+            bestiary.spawn("dire_wolf").unwrap(),
+            bestiary.spawn("ghoul").unwrap(),
+        ];
+        let mut battle = Battle::new(vec![Box::new(Tyr::new())], enemies, 11);
+        let outcome = battle.run(&mut attack_first, &mut attack_first).unwrap();
+
+        let reward = battle.arena().experience_reward();
+        match outcome {
+            BattleOutcome::Victory => assert_eq!(reward, 65),
+            _ => assert!(reward <= 65),
+        }
+    }
+
+    #[test]
+    fn test_parties() {
+        let mut allies = Party::new();
+        allies.join(Box::new(Tyr::new()), Row::Front).unwrap();
+        allies.join(Box::new(Vamp::new()), Row::Back).unwrap();
+        allies.set_leader(1).unwrap();
+        allies.add_buff(PartyBuff::Initiative(100));
+        let mut shared = Inventory::new();
+        shared
+            .put_consumable(Consumable::new(ConsumableType::HealthPotion))
+            .unwrap();
+        allies.share_inventory(shared);
+
+        let mut enemies = Party::new();
+        enemies.join(Box::new(Ghoul::new()), Row::Front).unwrap();
+        enemies.join(Box::new(Imp::new()), Row::Back).unwrap();
+
+        let mut battle = Battle::with_parties(allies, enemies, 5);
+        let tyr = CombatantId::new(Side::Allies, 0);
+        let vamp = CombatantId::new(Side::Allies, 1);
+        let imp = CombatantId::new(Side::Enemies, 1);
+        assert!(!battle.arena().can_reach(tyr, imp));
+        assert!(battle.arena().initiative(tyr) >= 100);
+
+        // Buffed allies act first, Their melee weapons can't reach the back row.
+        let mut melee_imp = |_: &Arena, _: CombatantId| Action::Attack(imp);
+        assert!(battle.next_turn(&mut melee_imp, &mut attack_first).is_err());
+
+        // Drinking from the shared inventory empties it for everyone.
+        let mut drink = |_: &Arena, _: CombatantId| Action::UseConsumable(0);
+        let events = battle.next_turn(&mut drink, &mut attack_first).unwrap();
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, BattleEvent::ConsumableUsed { user, .. } if user.side == Side::Allies)));
+        assert!(battle.arena().inventory(vamp).get_consumables().is_empty());
+        assert!(battle.arena().inventory(tyr).get_consumables().is_empty());
+
+        let (allies, enemies) = battle.into_parties();
+        assert_eq!(allies.len(), 2);
+        assert_eq!(allies.leader_index(), 1);
+        assert_eq!(allies.buffs(), &[PartyBuff::Initiative(100)]);
+        assert!(allies.shared_inventory().is_some());
+        assert_eq!(enemies.get(1).unwrap().row(), Row::Back);
+    }
+
+    #[test]
+    fn test_class_passives() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut vamp = Vamp::new();
+        vamp.health_mut().drip(50);
+        let mut arena = Arena::new(
+            vec![Box::new(vamp), Box::new(Susanoo::new())],
+            vec![Box::new(Tyr::new()), Box::new(Kain::new())],
+        );
+        let vamp = CombatantId::new(Side::Allies, 0);
+        let susanoo = CombatantId::new(Side::Allies, 1);
+        let tyr = CombatantId::new(Side::Enemies, 0);
+        let kain = CombatantId::new(Side::Enemies, 1);
+
+        // Vampires heal from their hits, Warriors take less damage and build rage.
+        let damage = arena.combatant(vamp).character().attack_damage() * 90 / 100;
+        let claw = *arena.combatant(vamp).character().weapon().unwrap();
+        let events = arena.attack(vamp, tyr, &mut rng);
+        assert!(events.contains(&BattleEvent::Damage {
+            source: vamp,
+            target: tyr,
+            amount: damage.max(1),
+            kind: DamageKind::Weapon(claw.weapon_type(), claw.ammo_type()),
+        }));
+        assert!(events.contains(&BattleEvent::Healed {
+            source: vamp,
+            target: vamp,
+            amount: damage.max(1) / 4,
+        }));
+        assert_eq!(arena.combatant(tyr).character().mana().current(), 10);
+        assert_eq!(arena.combatant(vamp).character().mana().current(), 10);
+
+        // Assassins crit targets facing someone else.
+        let crits = |arena: &mut Arena, rng: &mut StdRng| {
+            (0..50)
+                .flat_map(|_| arena.attack(susanoo, kain, rng))
+                .any(|e| matches!(e, BattleEvent::CriticalHit { .. }))
+        };
+        assert!(crits(&mut arena, &mut rng));
+        arena
+            .combatant_mut(kain)
+            .character
+            .health_mut()
+            .revive()
+            .ok();
+        arena.attack(kain, susanoo, &mut rng);
+        assert_eq!(arena.combatant(kain).facing(), Some(susanoo));
+        assert!(!crits(&mut arena, &mut rng));
+
+        // Warlock curses last longer.
+        let hellfire = arena.combatant(kain).character().abilities()[1];
+        arena.cast(kain, hellfire, vamp);
+        let burn = arena
+            .combatant(vamp)
+            .statuses()
+            .get(StatusKind::Burn)
+            .unwrap();
+        assert_eq!(burn.duration, 5);
+    }
+
+    #[test]
+    fn test_game_events() {
+        use components::{EventBus, GameEvent};
+        use std::sync::{Arc, Mutex};
+
+        let deaths = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = EventBus::new();
+        let sink = deaths.clone();
+        bus.subscribe(move |event| {
+            if let GameEvent::Died { target } = event {
+                sink.lock().unwrap().push(*target);
+            }
+        });
+
+        let mut battle = Battle::new(vec![Box::new(Tyr::new())], vec![Box::new(Kain::new())], 3);
+        while battle.outcome().is_none() {
+            let events = battle
+                .next_turn(&mut attack_first, &mut attack_first)
+                .unwrap();
+            battle.arena().publish(&events, &mut bus);
+        }
+
+        let expected = match battle.outcome().unwrap() {
+            BattleOutcome::Victory => vec!["Kain"],
+            BattleOutcome::Defeat => vec!["Tyr"],
+            _ => Vec::new(),
+        };
+        assert_eq!(*deaths.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_damage_events() {
+        use components::{GameEvent, Health};
+
+        let kain = Kain::build(
+            &Inventory::new(),
+            &Default::default(),
+            &Health::new(Some(10)),
+        );
+        let mut arena = Arena::new(vec![Box::new(Tyr::new())], vec![Box::new(kain)]);
+        let tyr = CombatantId::new(Side::Allies, 0);
+        let kain = CombatantId::new(Side::Enemies, 0);
+
+        // Cleave hits for 22 but Kain only has 10 health left.
+        let cleave = arena.combatant(tyr).character().abilities()[0];
+        let events = arena.cast(tyr, cleave, kain);
+        let damage = BattleEvent::Damage {
+            source: tyr,
+            target: kain,
+            amount: 10,
+            kind: DamageKind::Ability("Cleave"),
+        };
+        assert_eq!(events[1], damage);
+        assert_eq!(
+            arena.game_event(&damage),
+            Some(GameEvent::DamageDealt {
+                source: Some("Tyr"),
+                target: "Kain",
+                amount: 10,
+                ammo: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_lifesteal_overkill() {
+        use components::Health;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut vamp = Vamp::new();
+        vamp.health_mut().drip(50);
+        let kain = Kain::build(
+            &Inventory::new(),
+            &Default::default(),
+            &Health::new(Some(8)),
+        );
+        let mut arena = Arena::new(vec![Box::new(vamp)], vec![Box::new(kain)]);
+        let vamp = CombatantId::new(Side::Allies, 0);
+        let kain = CombatantId::new(Side::Enemies, 0);
+        assert!(arena.combatant(vamp).character().attack_damage() > 8);
+
+        // Vamp heals from the 8 health Kain lost, Not from the overkill.
+        let events = arena.attack(vamp, kain, &mut rng);
+        assert!(events.contains(&BattleEvent::Healed {
+            source: vamp,
+            target: vamp,
+            amount: 2,
+        }));
+        assert_eq!(arena.combatant(vamp).character().health().current(), 52);
+    }
+
+    #[test]
+    fn test_durability() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut tyr = Tyr::new();
+        tyr.equip(
+            Weapon::new(WeaponType::Mace).with_durability(1),
+            EquipSlot::MainHand,
+        )
+        .unwrap();
+        let mut arena = Arena::new(vec![Box::new(tyr)], vec![Box::new(Kain::new())]);
+        let tyr = CombatantId::new(Side::Allies, 0);
+        let kain = CombatantId::new(Side::Enemies, 0);
+
+        let events = arena.attack(tyr, kain, &mut rng);
+        assert!(events.contains(&BattleEvent::WeaponBroke {
+            owner: tyr,
+            weapon: arena.combatant(tyr).character().weapon().unwrap().name(),
+        }));
+        assert_eq!(arena.combatant(tyr).character().attack_damage(), 1);
+    }
+}
@@ -0,0 +1,494 @@
+//! Recording battles to combat logs and replaying them to verify they're deterministic.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    action::{Action, BattleEvent, BattleOutcome, Controller},
+    arena::{Arena, Characters, CombatantId, Side},
+    battle::Battle,
+};
+use characters::{CharacterRegistry, CharacterSave};
+use components::status::StatusKind;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{cell::Cell, fmt, fs, path::Path};
+
+/// The current combat log format version.
+pub const COMBAT_LOG_VERSION: u32 = 1;
+
+/// A seeded random generator that counts the values drawn from it.
+pub(crate) struct CountingRng {
+    rng: StdRng,
+    rolls: u64,
+}
+
+impl CountingRng {
+    pub(crate) fn new(seed: u64) -> CountingRng {
+        CountingRng {
+            rng: StdRng::seed_from_u64(seed),
+            rolls: 0,
+        }
+    }
+
+    pub(crate) fn rolls(&self) -> u64 {
+        self.rolls
+    }
+}
+
+impl RngCore for CountingRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rolls += 1;
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rolls += 1;
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rolls += 1;
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rolls += 1;
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// The state of a combatant after a turn.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct CombatantState {
+    pub id: CombatantId,
+    pub health: u32,
+    pub mana: u32,
+    pub statuses: Vec<StatusKind>,
+    pub fled: bool,
+}
+
+/// Everything that happened during a turn.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct TurnRecord {
+    pub round: u32,
+    /// `None` if the round limit was reached.
+    pub actor: Option<CombatantId>,
+    /// `None` if the actor was incapacitated.
+    pub action: Option<Action>,
+    /// The battle events in the order they happened.
+    pub events: Vec<serde_json::Value>,
+    /// The amount of random values drawn during the turn.
+    pub rolls: u64,
+    /// The state of every combatant at the end of the turn.
+    pub state: Vec<CombatantState>,
+}
+
+/// A recorded battle, Stored as JSON.
+///
+/// Holds the starting state of every character and the seed,
+/// So replaying the recorded actions reproduces the battle, See [`CombatLog::verify`].
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct CombatLog {
+    pub version: u32,
+    pub seed: u64,
+    pub max_rounds: u32,
+    pub allies: Vec<CharacterSave>,
+    pub enemies: Vec<CharacterSave>,
+    pub turns: Vec<TurnRecord>,
+    pub outcome: Option<BattleOutcome>,
+}
+
+/// Where a replay stopped matching its combat log.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Divergence {
+    /// The index of the turn that diverged.
+    pub turn: usize,
+    /// What diverged, i.e., `events`.
+    pub field: &'static str,
+    pub expected: String,
+    pub found: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Turn {} diverged at {}, Expected {} but found {}",
+            self.turn, self.field, self.expected, self.found
+        )
+    }
+}
+
+/// Wraps a side's controller, Capturing the action it chose.
+struct Capture<'a> {
+    controller: &'a mut dyn Controller,
+    choice: &'a Cell<Option<(CombatantId, Action)>>,
+}
+
+impl Controller for Capture<'_> {
+    fn choose_action(&mut self, arena: &Arena, actor: CombatantId) -> Action {
+        let action = self.controller.choose_action(arena, actor);
+        self.choice.set(Some((actor, action)));
+        action
+    }
+}
+
+/// Take the next turn of the battle and record it, Counting the rolls made since the given amount.
+fn record_turn(
+    battle: &mut Battle,
+    rolls: u64,
+    allies: &mut dyn Controller,
+    enemies: &mut dyn Controller,
+) -> anyhow::Result<(TurnRecord, Vec<BattleEvent>)> {
+    let choice = Cell::new(None);
+    let events = battle.next_turn(
+        &mut Capture {
+            controller: allies,
+            choice: &choice,
+        },
+        &mut Capture {
+            controller: enemies,
+            choice: &choice,
+        },
+    )?;
+    let choice = choice.get();
+
+    let incapacitated = events.iter().find_map(|event| match event {
+        BattleEvent::Incapacitated { target, .. } => Some(*target),
+        _ => None,
+    });
+    let actor = choice.map(|(actor, _)| actor).or(incapacitated);
+    let record = TurnRecord {
+        round: battle.round(),
+        actor,
+        action: choice.map(|(_, action)| action),
+        events: events
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<_, _>>()?,
+        rolls: battle.rolls() - rolls,
+        state: state(battle.arena()),
+    };
+    Ok((record, events))
+}
+
+fn state(arena: &Arena) -> Vec<CombatantState> {
+    [Side::Allies, Side::Enemies]
+        .iter()
+        .flat_map(|side| {
+            arena
+                .side(*side)
+                .iter()
+                .enumerate()
+                .map(move |(index, c)| CombatantState {
+                    id: CombatantId::new(*side, index),
+                    health: c.character().health().current(),
+                    mana: c.character().mana().current(),
+                    statuses: c.statuses.iter().map(|s| s.kind).collect(),
+                    fled: c.fled,
+                })
+        })
+        .collect()
+}
+
+/// A battle that records every turn to a [`CombatLog`].
+///
+/// Every character must have a [`characters::Char::kind`] so it can be spawned again on replay.
+///
+/// ```
+/// use characters::{CharFactory, Kain, Tyr};
+/// use combat::{ai::Aggressive, CombatLog, Recorder};
+///
+/// let mut recorder = Recorder::new(vec![Box::new(Tyr::new())], vec![Box::new(Kain::new())], 5).unwrap();
+/// recorder.run(&mut Aggressive, &mut Aggressive).unwrap();
+///
+/// let log = recorder.into_log();
+/// let json = log.to_json().unwrap();
+/// assert!(CombatLog::from_json(&json).unwrap().verify().unwrap().is_none());
+/// ```
+pub struct Recorder {
+    battle: Battle,
+    log: CombatLog,
+    /// The rolls made by the end of the last recorded turn.
+    rolls: u64,
+}
+
+impl Recorder {
+    /// Creates a new recorded battle between two sides.
+    pub fn new(allies: Characters, enemies: Characters, seed: u64) -> anyhow::Result<Recorder> {
+        let save = |side: &Characters| {
+            side.iter()
+                .map(|c| CharacterSave::new(c.as_ref()))
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+        let log = CombatLog {
+            version: COMBAT_LOG_VERSION,
+            seed,
+            max_rounds: crate::battle::MAX_ROUNDS,
+            allies: save(&allies)?,
+            enemies: save(&enemies)?,
+            turns: Vec::new(),
+            outcome: None,
+        };
+        Ok(Recorder {
+            battle: Battle::new(allies, enemies, seed),
+            log,
+            rolls: 0,
+        })
+    }
+
+    /// Set the amount of rounds before the battle ends in a draw.
+    pub fn set_max_rounds(&mut self, rounds: u32) {
+        self.battle.set_max_rounds(rounds);
+        self.log.max_rounds = rounds;
+    }
+
+    pub fn battle(&self) -> &Battle {
+        &self.battle
+    }
+
+    /// The combat log recorded so far.
+    pub fn log(&self) -> &CombatLog {
+        &self.log
+    }
+
+    /// Take and record the next combatant's turn, See [`Battle::next_turn`].
+    ///
+    /// Invalid actions aren't recorded, Rolls made before they were rejected count towards the next turn.
+    pub fn next_turn(
+        &mut self,
+        allies: &mut dyn Controller,
+        enemies: &mut dyn Controller,
+    ) -> anyhow::Result<Vec<BattleEvent>> {
+        let (record, events) = record_turn(&mut self.battle, self.rolls, allies, enemies)?;
+        self.rolls = self.battle.rolls();
+        self.log.turns.push(record);
+        self.log.outcome = self.battle.outcome();
+        Ok(events)
+    }
+
+    /// Run and record the battle until it's over.
+    pub fn run(
+        &mut self,
+        allies: &mut dyn Controller,
+        enemies: &mut dyn Controller,
+    ) -> anyhow::Result<BattleOutcome> {
+        loop {
+            if let Some(outcome) = self.battle.outcome() {
+                return Ok(outcome);
+            }
+            self.next_turn(allies, enemies)?;
+        }
+    }
+
+    pub fn into_log(self) -> CombatLog {
+        self.log
+    }
+}
+
+impl CombatLog {
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a combat log, Fails if it was recorded by an unsupported version.
+    pub fn from_json(json: &str) -> anyhow::Result<CombatLog> {
+        let log: CombatLog = serde_json::from_str(json)?;
+        if log.version > COMBAT_LOG_VERSION {
+            return Err(anyhow::anyhow!(
+                "Combat log version {} is newer than the supported version {}",
+                log.version,
+                COMBAT_LOG_VERSION
+            ));
+        }
+        Ok(log)
+    }
+
+    /// Write the combat log to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Read a combat log from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<CombatLog> {
+        CombatLog::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Replay the recorded actions against the same seed, Spawning builtin characters and bestiary monsters.
+    ///
+    /// ## Returns
+    /// The first divergence from the recorded battle if any.
+    pub fn verify(&self) -> anyhow::Result<Option<Divergence>> {
+        self.verify_with(&CharacterRegistry::default())
+    }
+
+    /// Replay the recorded actions spawning characters from the given registry, See [`CombatLog::verify`].
+    pub fn verify_with(&self, registry: &CharacterRegistry) -> anyhow::Result<Option<Divergence>> {
+        let restore = |side: &[CharacterSave]| {
+            side.iter()
+                .map(|c| c.restore(|kind| registry.spawn(kind)))
+                .collect::<anyhow::Result<Characters>>()
+        };
+        let mut battle = Battle::new(restore(&self.allies)?, restore(&self.enemies)?, self.seed);
+        battle.set_max_rounds(self.max_rounds);
+
+        for (index, expected) in self.turns.iter().enumerate() {
+            let diverged = |field, expected: &dyn fmt::Debug, found: &dyn fmt::Debug| {
+                Some(Divergence {
+                    turn: index,
+                    field,
+                    expected: format!("{:?}", expected),
+                    found: format!("{:?}", found),
+                })
+            };
+            if battle.outcome().is_some() {
+                return Ok(diverged(
+                    "outcome",
+                    &None::<BattleOutcome>,
+                    &battle.outcome(),
+                ));
+            }
+
+            let mut replayed = |_: &Arena, _: CombatantId| expected.action.unwrap_or(Action::Flee);
+            let rolls = battle.rolls();
+            let found = match record_turn(&mut battle, rolls, &mut replayed.clone(), &mut replayed)
+            {
+                Ok((found, _)) => found,
+                Err(err) => return Ok(diverged("action", &expected.action, &err)),
+            };
+
+            if found.round != expected.round {
+                return Ok(diverged("round", &expected.round, &found.round));
+            }
+            if found.actor != expected.actor {
+                return Ok(diverged("actor", &expected.actor, &found.actor));
+            }
+            if found.action != expected.action {
+                return Ok(diverged("action", &expected.action, &found.action));
+            }
+            if found.rolls != expected.rolls {
+                return Ok(diverged("rolls", &expected.rolls, &found.rolls));
+            }
+            if found.events != expected.events {
+                return Ok(diverged("events", &expected.events, &found.events));
+            }
+            if found.state != expected.state {
+                return Ok(diverged("state", &expected.state, &found.state));
+            }
+        }
+
+        if battle.outcome() != self.outcome {
+            return Ok(Some(Divergence {
+                turn: self.turns.len(),
+                field: "outcome",
+                expected: format!("{:?}", self.outcome),
+                found: format!("{:?}", battle.outcome()),
+            }));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{Aggressive, Defensive};
+    use characters::{CharFactory, Ghoul, Imp, Kain, Tyr, Vamp};
+
+    fn record(seed: u64) -> CombatLog {
+        let mut recorder = Recorder::new(
+            vec![Box::new(Tyr::new()), Box::new(Vamp::new())],
+            vec![Box::new(Ghoul::new()), Box::new(Imp::new())],
+            seed,
+        )
+        .unwrap();
+        recorder
+            .run(&mut Aggressive, &mut Defensive::default())
+            .unwrap();
+        recorder.into_log()
+    }
+
+    #[test]
+    fn test_replay() {
+        let log = record(11);
+        assert!(log.outcome.is_some());
+        assert!(log.turns.iter().any(|t| t.rolls > 0));
+
+        let path = std::env::temp_dir().join("combat_log_test_replay.json");
+        log.save(&path).unwrap();
+        let loaded = CombatLog::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, log);
+        assert_eq!(loaded.verify().unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_action() {
+        let mut recorder =
+            Recorder::new(vec![Box::new(Tyr::new())], vec![Box::new(Kain::new())], 7).unwrap();
+        let mut invalid = |arena: &Arena, actor: CombatantId| Action::UseAbility {
+            ability: 99,
+            target: arena.active(actor.side.opposite()).next().unwrap().0,
+        };
+        assert!(recorder
+            .next_turn(&mut invalid.clone(), &mut invalid)
+            .is_err());
+        assert!(recorder.log().turns.is_empty());
+
+        recorder
+            .run(&mut Aggressive, &mut Defensive::default())
+            .unwrap();
+        let log = recorder.into_log();
+        assert!(log.turns[0].rolls > 0);
+        assert_eq!(log.verify().unwrap(), None);
+    }
+
+    #[test]
+    fn test_divergence() {
+        let mut log = record(4);
+        let last = log.turns.len() - 1;
+        log.turns[last].state[0].health += 1;
+        let divergence = log.verify().unwrap().unwrap();
+        assert_eq!((divergence.turn, divergence.field), (last, "state"));
+
+        let mut log = record(4);
+        log.seed += 1;
+        assert!(log.verify().unwrap().is_some());
+
+        let mut log = record(4);
+        log.turns.pop();
+        assert_eq!(log.verify().unwrap().unwrap().field, "outcome");
+
+        let mut future = record(1);
+        future.version = COMBAT_LOG_VERSION + 1;
+        assert!(CombatLog::from_json(&future.to_json().unwrap()).is_err());
+    }
+}
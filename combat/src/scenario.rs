@@ -0,0 +1,582 @@
+//! Scripted battle scenarios, A world and the steps to play in it with their expected outcomes.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    action::{Action, BattleEvent, BattleOutcome},
+    ai::Aggressive,
+    arena::{Arena, CombatantId},
+    battle::Battle,
+};
+use characters::{Char, CharacterRegistry, Level};
+use components::{
+    consumable::{Consumable, ConsumableType},
+    material::{Material, MaterialType},
+    status::StatusKind,
+    EquipSlot, Equipment, Health, Inventory, Item, ItemRarity, MetaData, Weapon, WeaponAmmoType,
+    WeaponType,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// A battle to play and the outcomes expected along the way, Stored as JSON.
+///
+/// The same scenario always plays out the same, Every random roll comes from its seed.
+///
+/// ```
+/// use combat::Scenario;
+///
+/// let scenario = Scenario::from_json(r#"{
+///     "name": "A potion heals",
+///     "seed": 1,
+///     "allies": [{
+///         "kind": "Vamp",
+///         "health": 40,
+///         "inventory": [{"consumable": {"type": "HealthPotion", "quantity": 2}}]
+///     }],
+///     "enemies": [{"kind": "skeleton_archer"}],
+///     "steps": [
+///         {"turn": {"actor": {"side": "Allies", "index": 0}, "action": {"UseConsumable": 0}}},
+///         {"expect": {
+///             "combatant": {"side": "Allies", "index": 0},
+///             "health": 70,
+///             "inventory": [{"item": "Health Potion", "quantity": 1}]
+///         }}
+///     ]
+/// }"#).unwrap();
+/// scenario.run().unwrap();
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub seed: u64,
+    /// The amount of rounds before the battle ends in a draw, The battle's default if `None`.
+    #[serde(default)]
+    pub max_rounds: Option<u32>,
+    pub allies: Vec<CharacterSpec>,
+    pub enemies: Vec<CharacterSpec>,
+    pub steps: Vec<Step>,
+}
+
+/// A character of a scenario, Spawned by its kind and changed by the other fields.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CharacterSpec {
+    /// The name the character is registered by, i.e., `Tyr` or `ghoul`.
+    pub kind: String,
+    /// The health the character starts with, It keeps the max health it spawned with.
+    #[serde(default)]
+    pub health: Option<u32>,
+    #[serde(default)]
+    pub mana: Option<u32>,
+    #[serde(default)]
+    pub level: Option<u32>,
+    /// Talents learned in order after the level is set.
+    #[serde(default)]
+    pub talents: Vec<String>,
+    /// Replaces the weapons the character spawned with, An empty list leaves it unarmed.
+    /// Weapons its class can't wield fail the scenario.
+    ///
+    /// If `None` the spawned weapons are rolled again from the scenario's seed keeping their types.
+    #[serde(default)]
+    pub equipment: Option<Vec<WeaponSpec>>,
+    /// Items put in the character's inventory.
+    #[serde(default)]
+    pub inventory: Vec<ItemSpec>,
+    #[serde(default)]
+    pub gold: u64,
+}
+
+/// A weapon of a scenario, Anything not given is rolled from the scenario's seed.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponSpec {
+    #[serde(rename = "type")]
+    pub weapon_type: WeaponType,
+    #[serde(default)]
+    pub ammo_type: Option<WeaponAmmoType>,
+    #[serde(default)]
+    pub rarity: Option<ItemRarity>,
+    #[serde(default)]
+    pub durability: Option<u32>,
+    /// Whether it's held in the off hand.
+    #[serde(default)]
+    pub off_hand: bool,
+}
+
+/// An item of a scenario's inventory.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum ItemSpec {
+    Weapon(WeaponSpec),
+    Consumable {
+        #[serde(rename = "type")]
+        consumable_type: ConsumableType,
+        #[serde(default = "one")]
+        quantity: u32,
+    },
+    Material {
+        #[serde(rename = "type")]
+        material_type: MaterialType,
+        #[serde(default = "one")]
+        quantity: u32,
+    },
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// A step of a scenario.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    /// Take the next turn.
+    Turn {
+        /// Fails unless it's this combatant's turn.
+        #[serde(default)]
+        actor: Option<CombatantId>,
+        /// The action taken, Chosen by [`Aggressive`] if `None`.
+        #[serde(default)]
+        action: Option<Action>,
+    },
+    /// Let [`Aggressive`] take the turns until the battle is over or the given amount of turns were taken.
+    Run {
+        #[serde(default)]
+        turns: Option<u32>,
+    },
+    /// Check the state of a combatant.
+    Expect(Expectation),
+    /// Check the events of the last turn, In the same shape combat logs record them.
+    Events(Vec<serde_json::Value>),
+    /// Check the battle's outcome, `None` while it's going on.
+    Outcome(Option<BattleOutcome>),
+    /// Check the battle's round.
+    Round(u32),
+}
+
+/// The expected state of a combatant, Fields left out aren't checked.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectation {
+    pub combatant: CombatantId,
+    #[serde(default)]
+    pub health: Option<u32>,
+    #[serde(default)]
+    pub mana: Option<u32>,
+    #[serde(default)]
+    pub alive: Option<bool>,
+    #[serde(default)]
+    pub fled: Option<bool>,
+    /// The status effects in the order they were applied.
+    #[serde(default)]
+    pub statuses: Option<Vec<StatusKind>>,
+    /// The whole inventory, Weapons are named by their weapon type and the rest by their item name.
+    #[serde(default)]
+    pub inventory: Option<Vec<ItemCount>>,
+    #[serde(default)]
+    pub gold: Option<u64>,
+}
+
+/// An item and its amount in an inventory.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemCount {
+    pub item: String,
+    pub quantity: u32,
+}
+
+impl WeaponSpec {
+    fn weapon(&self, rng: &mut StdRng) -> Weapon {
+        let mut weapon = Weapon::generate(self.weapon_type, rng);
+        if let Some(ammo_type) = self.ammo_type {
+            weapon = weapon.with_ammo_type(ammo_type);
+        }
+        if let Some(rarity) = self.rarity {
+            weapon = weapon.with_rarity(rarity);
+        }
+        if let Some(durability) = self.durability {
+            weapon = weapon.with_durability(durability);
+        }
+        weapon
+    }
+}
+
+impl CharacterSpec {
+    /// Spawn the character from the registry and set it up.
+    pub fn spawn(
+        &self,
+        registry: &CharacterRegistry,
+        rng: &mut StdRng,
+    ) -> anyhow::Result<Box<dyn Char>> {
+        let mut character = registry
+            .spawn(&self.kind)
+            .ok_or_else(|| anyhow::anyhow!("Unknown character {}", self.kind))?;
+
+        if let Some(health) = self.health {
+            let max = character.health().max();
+            let bar = character.health_mut();
+            *bar = Health::new(Some(health));
+            bar.set_max(max);
+        }
+        if let Some(mana) = self.mana {
+            let bar = character.mana_mut();
+            bar.spend(bar.current())?;
+            bar.restore(mana);
+        }
+        if let Some(level) = self.level {
+            *character.level_mut() = Level::new(level);
+        }
+        for talent in self.talents.iter() {
+            character.learn_talent(talent)?;
+        }
+        if let Some(weapons) = self.equipment.as_ref() {
+            *character.equipment_mut() = Equipment::new();
+            for spec in weapons {
+                let slot = if spec.off_hand {
+                    EquipSlot::OffHand
+                } else {
+                    EquipSlot::MainHand
+                };
+                character.equip(spec.weapon(rng), slot)?;
+            }
+        } else {
+            for slot in [EquipSlot::MainHand, EquipSlot::OffHand] {
+                if let Some(weapon) = character.equipment_mut().get_mut(slot) {
+                    *weapon = WeaponSpec {
+                        weapon_type: weapon.weapon_type(),
+                        ammo_type: None,
+                        rarity: None,
+                        durability: weapon.durability().map(|d| d.max()),
+                        off_hand: slot == EquipSlot::OffHand,
+                    }
+                    .weapon(rng);
+                }
+            }
+        }
+        for item in self.inventory.iter() {
+            let inventory = character.inventory_mut();
+            match *item {
+                ItemSpec::Weapon(spec) => inventory.put_weapon(spec.weapon(rng))?,
+                ItemSpec::Consumable {
+                    consumable_type,
+                    quantity,
+                } => {
+                    for _ in 0..quantity {
+                        inventory.put_consumable(Consumable::new(consumable_type))?;
+                    }
+                }
+                ItemSpec::Material {
+                    material_type,
+                    quantity,
+                } => {
+                    for _ in 0..quantity {
+                        inventory.put_material(Material::new(material_type))?;
+                    }
+                }
+            }
+        }
+        character.wallet_mut().deposit(self.gold)?;
+        Ok(character)
+    }
+}
+
+impl Scenario {
+    pub fn from_json(json: &str) -> anyhow::Result<Scenario> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Read a scenario from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Scenario> {
+        Scenario::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Play the scenario with builtin characters and bestiary monsters.
+    ///
+    /// Fails at the first step that didn't go as expected.
+    pub fn run(&self) -> anyhow::Result<()> {
+        self.run_with(&CharacterRegistry::default())
+    }
+
+    /// Play the scenario spawning characters from the given registry, See [`Scenario::run`].
+    pub fn run_with(&self, registry: &CharacterRegistry) -> anyhow::Result<()> {
+        let mut battle = self
+            .battle(registry)
+            .map_err(|err| anyhow::anyhow!("Scenario {}: {}", self.name, err))?;
+        let mut events = Vec::new();
+        for (index, step) in self.steps.iter().enumerate() {
+            play(&mut battle, step, &mut events)
+                .map_err(|err| anyhow::anyhow!("Scenario {} step {}: {}", self.name, index, err))?;
+        }
+        Ok(())
+    }
+
+    /// Set up the scenario's battle.
+    pub fn battle(&self, registry: &CharacterRegistry) -> anyhow::Result<Battle> {
+        // Rolls the weapons that aren't fully specified, The battle rolls from its own generator.
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut spawn = |side: &[CharacterSpec]| {
+            side.iter()
+                .map(|spec| spec.spawn(registry, &mut rng))
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+        let allies = spawn(&self.allies)?;
+        let enemies = spawn(&self.enemies)?;
+        let mut battle = Battle::new(allies, enemies, self.seed);
+        if let Some(rounds) = self.max_rounds {
+            battle.set_max_rounds(rounds);
+        }
+        Ok(battle)
+    }
+}
+
+/// Play a step, Keeping the events of the last turn taken.
+fn play(battle: &mut Battle, step: &Step, events: &mut Vec<BattleEvent>) -> anyhow::Result<()> {
+    match step {
+        Step::Turn { actor, action } => {
+            let next = battle
+                .whose_turn()
+                .ok_or_else(|| anyhow::anyhow!("The battle is already over"))?;
+            if let Some(actor) = actor {
+                check("actor", actor, &next)?;
+            }
+            *events = match *action {
+                Some(action) => {
+                    let mut chosen = |_: &Arena, _: CombatantId| action;
+                    battle.next_turn(&mut chosen.clone(), &mut chosen)?
+                }
+                None => battle.next_turn(&mut Aggressive, &mut Aggressive)?,
+            };
+        }
+        Step::Run { turns } => {
+            let mut taken = 0;
+            while battle.outcome().is_none() && turns.is_none_or(|turns| taken < turns) {
+                *events = battle.next_turn(&mut Aggressive, &mut Aggressive)?;
+                taken += 1;
+            }
+        }
+        Step::Expect(expected) => expect(battle.arena(), expected)?,
+        Step::Events(expected) => {
+            let expected = serde_json::Value::Array(expected.clone());
+            let found = serde_json::to_value(&*events)?;
+            if expected != found {
+                return Err(anyhow::anyhow!(
+                    "Expected events {} but found {}",
+                    expected,
+                    found
+                ));
+            }
+        }
+        Step::Outcome(expected) => check("outcome", expected, &battle.outcome())?,
+        Step::Round(expected) => check("round", expected, &battle.round())?,
+    }
+    Ok(())
+}
+
+fn expect(arena: &Arena, expected: &Expectation) -> anyhow::Result<()> {
+    let id = expected.combatant;
+    let combatant = arena
+        .get(id)
+        .ok_or_else(|| anyhow::anyhow!("There's no combatant {:?}", id))?;
+    let character = combatant.character();
+
+    if let Some(health) = expected.health.as_ref() {
+        check("health", health, &character.health().current())?;
+    }
+    if let Some(mana) = expected.mana.as_ref() {
+        check("mana", mana, &character.mana().current())?;
+    }
+    if let Some(alive) = expected.alive.as_ref() {
+        check("alive", alive, &combatant.is_alive())?;
+    }
+    if let Some(fled) = expected.fled.as_ref() {
+        check("fled", fled, &combatant.has_fled())?;
+    }
+    if let Some(statuses) = expected.statuses.as_ref() {
+        let found: Vec<_> = combatant.statuses().iter().map(|s| s.kind).collect();
+        check("statuses", statuses, &found)?;
+    }
+    if let Some(inventory) = expected.inventory.as_ref() {
+        let mut items = BTreeMap::new();
+        for item in inventory {
+            *items.entry(item.item.clone()).or_insert(0) += item.quantity;
+        }
+        check("inventory", &items, &contents(arena.inventory(id)))?;
+    }
+    if let Some(gold) = expected.gold.as_ref() {
+        check("gold", gold, &character.wallet().gold())?;
+    }
+    Ok(())
+}
+
+/// The amount of every item in an inventory by name, Weapons by their weapon type.
+fn contents(inventory: &Inventory) -> BTreeMap<String, u32> {
+    let mut items = BTreeMap::new();
+    let weapons = inventory
+        .get_weapons()
+        .iter()
+        .map(|s| (MetaData::name(&s.item().weapon_type()), s.quantity()));
+    let consumables = inventory
+        .get_consumables()
+        .iter()
+        .map(|s| (s.item().name(), s.quantity()));
+    let materials = inventory
+        .get_materials()
+        .iter()
+        .map(|s| (s.item().name(), s.quantity()));
+    for (name, quantity) in weapons.chain(consumables).chain(materials) {
+        *items.entry(name.to_string()).or_insert(0) += quantity;
+    }
+    items
+}
+
+fn check<T: PartialEq + std::fmt::Debug>(
+    what: &str,
+    expected: &T,
+    found: &T,
+) -> anyhow::Result<()> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Expected {} {:?} but found {:?}",
+            what,
+            expected,
+            found
+        ))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenarios() -> Vec<(String, Scenario)> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+        let mut scenarios: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .map(|path| {
+                let json = fs::read_to_string(&path).unwrap();
+                (json, Scenario::load(&path).unwrap())
+            })
+            .collect();
+        scenarios.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+        scenarios
+    }
+
+    #[test]
+    fn test_scenarios() {
+        let scenarios = scenarios();
+        assert!(scenarios.len() >= 4);
+        for (_, scenario) in scenarios.iter() {
+            // Twice, Scenarios always play out the same.
+            scenario.run().unwrap();
+            scenario.run().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_failures() {
+        let (json, _) = scenarios()
+            .into_iter()
+            .find(|(_, s)| s.name == "cleave")
+            .unwrap();
+
+        let wrong = Scenario::from_json(&json.replace("\"health\": 48", "\"health\": 47")).unwrap();
+        let err = wrong.run().unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Scenario cleave step 2: Expected health 47 but found 48"
+        );
+
+        let wrong = Scenario::from_json(&json.replace("\"amount\": 22", "\"amount\": 21")).unwrap();
+        assert!(wrong
+            .run()
+            .unwrap_err()
+            .to_string()
+            .starts_with("Scenario cleave step 1: Expected events"));
+
+        // It's the archer's turn after Tyr's.
+        let wrong = Scenario::from_json(&json.replace(
+            "{\"round\": 1}",
+            "{\"turn\": {\"actor\": {\"side\": \"Allies\", \"index\": 0}}}",
+        ))
+        .unwrap();
+        assert!(wrong
+            .run()
+            .unwrap_err()
+            .to_string()
+            .contains("Expected actor"));
+
+        let unknown =
+            Scenario::from_json(&json.replace("\"skeleton_archer\"", "\"dragon\"")).unwrap();
+        assert_eq!(
+            unknown.run().unwrap_err().to_string(),
+            "Scenario cleave: Unknown character dragon"
+        );
+
+        // Typos aren't ignored.
+        assert!(Scenario::from_json(&json.replace("\"alive\"", "\"alvie\"")).is_err());
+    }
+
+    #[test]
+    fn test_spawn() {
+        let registry = CharacterRegistry::default();
+        let mut rng = StdRng::seed_from_u64(0);
+        let spec = |json: &str| serde_json::from_str::<CharacterSpec>(json).unwrap();
+
+        let wraith = spec(r#"{"kind": "frost_wraith", "health": 50}"#)
+            .spawn(&registry, &mut rng)
+            .unwrap();
+        assert_eq!(wraith.health().current(), 50);
+        assert_eq!(wraith.health().max(), 120);
+
+        let kain = spec(r#"{"kind": "Kain", "health": 150}"#)
+            .spawn(&registry, &mut rng)
+            .unwrap();
+        assert_eq!(kain.health().current(), 100);
+
+        // Warlocks can't wield maces.
+        let mace = spec(r#"{"kind": "Kain", "equipment": [{"type": "Mace"}]}"#);
+        match mace.spawn(&registry, &mut rng) {
+            Err(err) => assert_eq!(err.to_string(), "Warlock cannot wield Mace"),
+            Ok(_) => panic!("Kain equipped a mace"),
+        }
+        let rod = spec(r#"{"kind": "Kain", "equipment": [{"type": "Rod"}]}"#)
+            .spawn(&registry, &mut rng)
+            .unwrap();
+        assert_eq!(rod.weapon().unwrap().weapon_type(), WeaponType::Rod);
+    }
+}
@@ -0,0 +1,499 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    action::{Action, BattleEvent, BattleOutcome, Controller},
+    arena::{Arena, Characters, CombatantId, Side},
+};
+use abilites::Ability;
+use characters::{Party, Stats};
+use components::items::MetaData;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{cmp::Reverse, fmt};
+
+/// The length of a simulation tick in milliseconds.
+pub const TICK_MS: u32 = 100;
+/// The length of a turn in milliseconds.
+///
+/// A character with no attack speed acts once a turn, Status effects tick once a turn
+/// and ability cooldowns last this long for every turn of cooldown.
+pub const TURN_MS: u32 = 2000;
+/// How often `hp5` and `mp5` are restored in milliseconds.
+const REGEN_MS: u64 = 5000;
+/// The default length of a simulation before it ends in a draw.
+const MAX_DURATION_MS: u64 = 5 * 60 * 1000;
+
+/// A single combat log line.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct LogEntry {
+    /// The simulation time in milliseconds this event happened at.
+    pub time: u64,
+    pub event: BattleEvent,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>8}ms {:?}", self.time, self.event)
+    }
+}
+
+/// An ability that's being cast.
+#[derive(Clone, Copy)]
+struct Cast {
+    ability: Ability,
+    target: CombatantId,
+    completes_at: u64,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Timer {
+    next_action_at: u64,
+    cast: Option<Cast>,
+}
+
+/// A real time battle between two sides of characters that advances in fixed ticks.
+///
+/// Combatants act whenever their attack timer allows it, Which is faster the higher
+/// their attack speed is. Abilities with a cast time resolve once their cast completes
+/// and are interrupted if the caster gets incapacitated.
+///
+/// All random rolls are made from a seeded generator and combatants are processed in a fixed order,
+/// So the same seed and the same controllers always produce the same [`Simulation::log`].
+pub struct Simulation {
+    arena: Arena,
+    ally_timers: Vec<Timer>,
+    enemy_timers: Vec<Timer>,
+    time: u64,
+    max_duration: u64,
+    rng: StdRng,
+    log: Vec<LogEntry>,
+}
+
+impl Simulation {
+    /// Creates a new simulation between two sides.
+    pub fn new(allies: Characters, enemies: Characters, seed: u64) -> Simulation {
+        Simulation::with_arena(Arena::new(allies, enemies), seed)
+    }
+
+    /// Creates a new simulation between two parties.
+    ///
+    /// Party formations, buffs and shared inventories take effect during the simulation.
+    pub fn with_parties(allies: Party, enemies: Party, seed: u64) -> Simulation {
+        Simulation::with_arena(Arena::from_parties(allies, enemies), seed)
+    }
+
+    fn with_arena(arena: Arena, seed: u64) -> Simulation {
+        Simulation {
+            ally_timers: vec![Timer::default(); arena.side(Side::Allies).len()],
+            enemy_timers: vec![Timer::default(); arena.side(Side::Enemies).len()],
+            arena,
+            time: 0,
+            max_duration: MAX_DURATION_MS,
+            rng: StdRng::seed_from_u64(seed),
+            log: Vec::new(),
+        }
+    }
+
+    /// Set how long in milliseconds the simulation runs before ending in a draw.
+    pub fn set_max_duration(&mut self, duration: u64) {
+        self.max_duration = duration;
+    }
+
+    /// The elapsed simulation time in milliseconds.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Returns the combatants of both sides.
+    pub fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    /// Everything that happened so far in order.
+    pub fn log(&self) -> &[LogEntry] {
+        &self.log
+    }
+
+    /// Returns the outcome of the simulation if it's over.
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        match self.arena.outcome() {
+            Some(outcome) => Some(outcome),
+            None if self.time >= self.max_duration => Some(BattleOutcome::Draw),
+            None => None,
+        }
+    }
+
+    /// Run the simulation until it's over.
+    pub fn run(
+        &mut self,
+        allies: &mut dyn Controller,
+        enemies: &mut dyn Controller,
+    ) -> anyhow::Result<BattleOutcome> {
+        loop {
+            if let Some(outcome) = self.outcome() {
+                return Ok(outcome);
+            }
+            self.step(allies, enemies)?;
+        }
+    }
+
+    /// Advance the simulation by one tick.
+    ///
+    /// ## Returns
+    /// [`Vec<BattleEvent>`] Everything that happened during the tick.
+    pub fn step(
+        &mut self,
+        allies: &mut dyn Controller,
+        enemies: &mut dyn Controller,
+    ) -> anyhow::Result<Vec<BattleEvent>> {
+        if self.outcome().is_some() {
+            return Err(anyhow::anyhow!("Simulation is already over"));
+        }
+
+        // A controller can choose an invalid action after others already acted,
+        // Roll the tick back so it can be retried.
+        let snapshot = (
+            self.arena.clone(),
+            self.ally_timers.clone(),
+            self.enemy_timers.clone(),
+            self.rng.clone(),
+        );
+        let start = self.log.len();
+        match self.tick(allies, enemies) {
+            Ok(()) => Ok(self.log[start..].iter().map(|entry| entry.event).collect()),
+            Err(err) => {
+                (self.arena, self.ally_timers, self.enemy_timers, self.rng) = snapshot;
+                self.time -= TICK_MS as u64;
+                self.log.truncate(start);
+                Err(err)
+            }
+        }
+    }
+
+    fn tick(
+        &mut self,
+        allies: &mut dyn Controller,
+        enemies: &mut dyn Controller,
+    ) -> anyhow::Result<()> {
+        self.time += TICK_MS as u64;
+        let order = self.order();
+
+        for &id in &order {
+            for cooldown in self.arena.combatant_mut(id).cooldowns.iter_mut() {
+                *cooldown = cooldown.saturating_sub(TICK_MS);
+            }
+            if self.time.is_multiple_of(TURN_MS as u64) {
+                let events = self.arena.tick_statuses(id);
+                self.record(events);
+                self.arena.replenish(id);
+            }
+            if self.time.is_multiple_of(REGEN_MS) && self.arena.combatant(id).is_alive() {
+                let events = self.arena.regenerate(id);
+                self.record(events);
+            }
+        }
+
+        for id in order {
+            if self.arena.outcome().is_some() {
+                break;
+            }
+            if self.arena.combatant(id).is_active() {
+                let events = match id.side {
+                    Side::Allies => self.act(id, allies)?,
+                    Side::Enemies => self.act(id, enemies)?,
+                };
+                self.record(events);
+            }
+        }
+        Ok(())
+    }
+
+    /// Consume the simulation returning the characters of both sides.
+    pub fn into_sides(self) -> (Characters, Characters) {
+        self.arena.into_sides()
+    }
+
+    /// Consume the simulation returning both sides as parties.
+    ///
+    /// Sides that weren't created from parties are returned as new parties.
+    pub fn into_parties(self) -> (Party, Party) {
+        self.arena.into_parties()
+    }
+
+    fn act(
+        &mut self,
+        actor: CombatantId,
+        controller: &mut dyn Controller,
+    ) -> anyhow::Result<Vec<BattleEvent>> {
+        let now = self.time;
+        let interval = attack_interval(&self.arena.combatant(actor).character.effective_stats());
+        let timer = *self.timer_mut(actor);
+
+        if self
+            .arena
+            .combatant(actor)
+            .statuses
+            .incapacitated_by()
+            .is_some()
+        {
+            return Ok(match timer.cast {
+                Some(cast) => {
+                    self.timer_mut(actor).cast = None;
+                    vec![BattleEvent::CastInterrupted {
+                        caster: actor,
+                        ability: cast.ability.name(),
+                    }]
+                }
+                None => Vec::new(),
+            });
+        }
+
+        if let Some(cast) = timer.cast {
+            if now < cast.completes_at {
+                return Ok(Vec::new());
+            }
+            *self.timer_mut(actor) = Timer {
+                next_action_at: now + interval,
+                cast: None,
+            };
+            return Ok(
+                match self
+                    .arena
+                    .validate_ability_target(actor, &cast.ability, cast.target)
+                {
+                    Ok(_) => self.arena.cast(actor, cast.ability, cast.target),
+                    Err(_) => vec![BattleEvent::CastInterrupted {
+                        caster: actor,
+                        ability: cast.ability.name(),
+                    }],
+                },
+            );
+        }
+
+        if now < timer.next_action_at {
+            return Ok(Vec::new());
+        }
+        let action = controller.choose_action(&self.arena, actor);
+        self.arena.validate(actor, action)?;
+        self.timer_mut(actor).next_action_at = now + interval;
+
+        Ok(match action {
+            Action::Attack(target) => self.arena.attack(actor, target, &mut self.rng),
+            Action::UseAbility { ability, target } => {
+                let cooldown =
+                    self.arena.combatant(actor).character.abilities()[ability].cooldown();
+                let ability = self.arena.start_ability(actor, ability, cooldown * TURN_MS);
+                if ability.cast_time() == 0 {
+                    self.arena.cast(actor, ability, target)
+                } else {
+                    self.timer_mut(actor).cast = Some(Cast {
+                        ability,
+                        target,
+                        completes_at: now + ability.cast_time() as u64,
+                    });
+                    vec![BattleEvent::CastStarted {
+                        caster: actor,
+                        ability: ability.name(),
+                    }]
+                }
+            }
+            Action::UseConsumable(index) => self.arena.use_consumable(actor, index),
+            Action::Flee => self.arena.flee(actor, &mut self.rng),
+        })
+    }
+
+    /// Active combatants ordered by initiative, Allies first on ties.
+    fn order(&self) -> Vec<CombatantId> {
+        let mut order = self.arena.active_ids();
+        order.sort_by_key(|&id| Reverse(self.arena.initiative(id)));
+        order
+    }
+
+    fn timer_mut(&mut self, id: CombatantId) -> &mut Timer {
+        match id.side {
+            Side::Allies => &mut self.ally_timers[id.index],
+            Side::Enemies => &mut self.enemy_timers[id.index],
+        }
+    }
+
+    fn record(&mut self, events: Vec<BattleEvent>) {
+        let time = self.time;
+        self.log
+            .extend(events.into_iter().map(|event| LogEntry { time, event }));
+    }
+}
+
+/// How often a character acts in milliseconds, Based on its attack speed.
+fn attack_interval(stats: &Stats) -> u64 {
+    let speed = stats.attack_speed as u64;
+    (TURN_MS as u64 * 100 / (100 + speed)).max(TICK_MS as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use characters::{CharFactory, Kain, Tyr};
+    use components::Health;
+
+    fn attack_first(arena: &Arena, actor: CombatantId) -> Action {
+        Action::Attack(arena.active(actor.side.opposite()).next().unwrap().0)
+    }
+
+    fn cast_first(arena: &Arena, actor: CombatantId) -> Action {
+        let target = arena.active(actor.side.opposite()).next().unwrap().0;
+        if arena.get(actor).unwrap().can_use(0) {
+            Action::UseAbility { ability: 0, target }
+        } else {
+            Action::Attack(target)
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let tyr = Tyr::new();
+        let kain = Kain::new();
+        let simulate = |seed| {
+            let mut simulation = Simulation::new(
+                vec![Box::new(tyr.clone())],
+                vec![Box::new(kain.clone())],
+                seed,
+            );
+            let outcome = simulation.run(&mut cast_first, &mut cast_first).unwrap();
+            (outcome, simulation.log().to_vec())
+        };
+
+        for seed in 0..20 {
+            let (outcome, log) = simulate(seed);
+            assert_ne!(outcome, BattleOutcome::Draw);
+            assert_eq!(simulate(seed), (outcome, log));
+        }
+    }
+
+    #[test]
+    fn test_invalid_action() {
+        let tyr = Tyr::new();
+        let kain = Kain::new();
+        let simulation =
+            || Simulation::new(vec![Box::new(tyr.clone())], vec![Box::new(kain.clone())], 7);
+
+        let mut expected = simulation();
+        expected.run(&mut cast_first, &mut cast_first).unwrap();
+
+        let mut actual = simulation();
+        let mut calls = 0;
+        let mut invalid_once = |arena: &Arena, actor: CombatantId| {
+            calls += 1;
+            if calls == 3 {
+                Action::Attack(actor)
+            } else {
+                cast_first(arena, actor)
+            }
+        };
+        let mut failed = 0;
+        while actual.outcome().is_none() {
+            if actual.step(&mut cast_first, &mut invalid_once).is_err() {
+                failed += 1;
+            }
+        }
+        assert_eq!(failed, 1);
+        assert_eq!(actual.log(), expected.log());
+    }
+
+    #[test]
+    fn test_cast_time() {
+        let mut simulation =
+            Simulation::new(vec![Box::new(Kain::new())], vec![Box::new(Tyr::new())], 0);
+        let kain = CombatantId::new(Side::Allies, 0);
+
+        while simulation.log().is_empty() {
+            simulation.step(&mut cast_first, &mut attack_first).unwrap();
+        }
+        let started = simulation
+            .log()
+            .iter()
+            .find(|entry| {
+                entry.event
+                    == BattleEvent::CastStarted {
+                        caster: kain,
+                        ability: "Void Bolt",
+                    }
+            })
+            .copied()
+            .unwrap();
+        assert_eq!(
+            simulation
+                .arena()
+                .get(kain)
+                .unwrap()
+                .character()
+                .mana()
+                .current(),
+            75
+        );
+
+        while simulation.time() < started.time + 1500 {
+            simulation.step(&mut cast_first, &mut attack_first).unwrap();
+        }
+        assert!(simulation
+            .log()
+            .iter()
+            .any(|entry| entry.time == started.time + 1500
+                && entry.event
+                    == BattleEvent::AbilityUsed {
+                        caster: kain,
+                        ability: "Void Bolt",
+                    }));
+    }
+
+    #[test]
+    fn test_regen() {
+        let stats = Stats {
+            hp5: 5,
+            mp5: 10,
+            ..Stats::default()
+        };
+        let tyr = Tyr::build(&Default::default(), &stats, &Health::new(Some(80)));
+        let mut simulation = Simulation::new(vec![Box::new(tyr)], vec![Box::new(Kain::new())], 0);
+        simulation.set_max_duration(REGEN_MS);
+        simulation
+            .run(&mut attack_first, &mut attack_first)
+            .unwrap();
+
+        assert!(simulation.log().iter().any(|entry| entry.time == REGEN_MS
+            && matches!(
+                entry.event,
+                BattleEvent::Regenerated {
+                    health: 5,
+                    mana: 10,
+                    ..
+                }
+            )));
+    }
+}
@@ -0,0 +1,175 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::items::{Item, ItemRarity, ItemType, MetaData};
+use serde::{Deserialize, Serialize};
+
+use rand::{random, Rng};
+use std::fmt;
+
+/// ## Consumable item types.
+/// - [`ConsumableType::HealthPotion`]
+///     - Restores health.
+/// - [`ConsumableType::Antidote`]
+///     - Cures all status effects.
+/// - [`ConsumableType::Elixir`]
+///     - Restores some health and cures all status effects.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ConsumableType {
+    HealthPotion,
+    Antidote,
+    Elixir,
+}
+
+impl ConsumableType {
+    /// The amount of health this consumable restores.
+    pub fn heal_amount(&self) -> u32 {
+        match self {
+            ConsumableType::HealthPotion => 30,
+            ConsumableType::Antidote => 0,
+            ConsumableType::Elixir => 15,
+        }
+    }
+
+    /// The gold consumables of this type are worth.
+    pub fn value(&self) -> u64 {
+        match self {
+            ConsumableType::HealthPotion => 25,
+            ConsumableType::Antidote => 20,
+            ConsumableType::Elixir => 60,
+        }
+    }
+
+    /// How many consumables of this type stack.
+    pub fn max_stack(&self) -> u32 {
+        match self {
+            ConsumableType::HealthPotion | ConsumableType::Antidote => 10,
+            ConsumableType::Elixir => 5,
+        }
+    }
+
+    /// Whether this consumable cures status effects.
+    pub fn cures(&self) -> bool {
+        matches!(self, ConsumableType::Antidote | ConsumableType::Elixir)
+    }
+}
+
+impl MetaData for ConsumableType {
+    fn name(&self) -> &'static str {
+        match self {
+            ConsumableType::HealthPotion => "Health Potion",
+            ConsumableType::Antidote => "Antidote",
+            ConsumableType::Elixir => "Elixir",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ConsumableType::HealthPotion => "A red potion that restores health.",
+            ConsumableType::Antidote => "Cures burns, bleeds, toxins and every other ailment.",
+            ConsumableType::Elixir => {
+                "A rare brew that restores some health and cures all ailments."
+            }
+        }
+    }
+}
+
+/// A consumable item.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Consumable {
+    consumable_type: ConsumableType,
+    id: u8,
+}
+
+impl fmt::Debug for Consumable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Consumable(name: {}, hash: {}, description: {})",
+            self.name(),
+            self.id,
+            self.consumable_type.description(),
+        )
+    }
+}
+
+impl fmt::Display for Consumable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Consumable(name: {}, hash: {})", self.name(), self.id)
+    }
+}
+
+impl Item for Consumable {
+    fn rarity(&self) -> ItemRarity {
+        ItemRarity::Rare
+    }
+
+    fn name(&self) -> &'static str {
+        self.consumable_type.name()
+    }
+
+    fn id(&self) -> u8 {
+        self.id
+    }
+
+    fn item_type(&self) -> ItemType {
+        ItemType::Consumable
+    }
+
+    fn value(&self) -> u64 {
+        self.consumable_type.value()
+    }
+
+    fn max_stack(&self) -> u32 {
+        self.consumable_type.max_stack()
+    }
+}
+
+impl Consumable {
+    pub fn new(consumable_type: ConsumableType) -> Consumable {
+        Consumable {
+            consumable_type,
+            id: random::<u8>(),
+        }
+    }
+
+    /// Generate a consumable rolling its id from the given random generator.
+    pub fn generate<R: Rng + ?Sized>(consumable_type: ConsumableType, rng: &mut R) -> Consumable {
+        Consumable {
+            consumable_type,
+            id: rng.gen(),
+        }
+    }
+
+    /// Returns this consumable's type.
+    pub fn consumable_type(&self) -> ConsumableType {
+        self.consumable_type
+    }
+}
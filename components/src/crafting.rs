@@ -0,0 +1,542 @@
+//! Crafting items from recipes, Upgrading weapon rarities and salvaging items into materials.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    consumable::ConsumableType,
+    equipment::{EquipSlot, Equipment},
+    inventory::Inventory,
+    items::{Item, ItemRarity},
+    loot::{Loot, LootItem},
+    material::{Material, MaterialType},
+    weapon::{Weapon, WeaponType},
+};
+use rand::Rng;
+
+/// A recipe that turns input items from an inventory into an output item.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Recipe {
+    id: &'static str,
+    name: &'static str,
+    inputs: &'static [(LootItem, u32)],
+    output: LootItem,
+    chance: u32,
+}
+
+impl Recipe {
+    /// Creates a new recipe.
+    ///
+    /// ## Parameters
+    /// chance: [u32] The chance percentage of crafting succeeding.
+    pub const fn new(
+        id: &'static str,
+        name: &'static str,
+        inputs: &'static [(LootItem, u32)],
+        output: LootItem,
+        chance: u32,
+    ) -> Recipe {
+        Recipe {
+            id,
+            name,
+            inputs,
+            output,
+            chance,
+        }
+    }
+
+    /// Returns the builtin recipe with the given id if any.
+    pub fn get(id: &str) -> Option<&'static Recipe> {
+        RECIPES.iter().find(|recipe| recipe.id == id)
+    }
+
+    /// Returns all builtin recipes.
+    pub fn all() -> &'static [Recipe] {
+        &RECIPES
+    }
+
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The items and amounts this recipe consumes.
+    pub fn inputs(&self) -> &'static [(LootItem, u32)] {
+        self.inputs
+    }
+
+    pub fn output(&self) -> LootItem {
+        self.output
+    }
+
+    /// The chance percentage of crafting succeeding.
+    pub fn chance(&self) -> u32 {
+        self.chance
+    }
+
+    /// Whether the inventory has every input of this recipe.
+    pub fn can_craft(&self, inventory: &Inventory) -> bool {
+        self.inputs
+            .iter()
+            .all(|(item, amount)| inventory.count(*item) >= *amount)
+    }
+
+    /// Craft this recipe consuming its inputs from the inventory.
+    ///
+    /// The inputs are consumed even if crafting fails, Crafted weapons are always [`ItemRarity::Rare`].
+    /// Nothing is changed if the inventory is missing inputs or can't hold the output.
+    ///
+    /// ## Returns
+    /// [`Option<Loot>`] The crafted item, `None` if crafting failed.
+    pub fn craft<R: Rng + ?Sized>(
+        &self,
+        inventory: &mut Inventory,
+        rng: &mut R,
+    ) -> anyhow::Result<Option<Loot>> {
+        let mut crafted = inventory.clone();
+        for (item, amount) in self.inputs {
+            crafted.remove(*item, *amount)?;
+        }
+
+        let mut output = None;
+        if rng.gen_range(0..100) < self.chance {
+            let loot = match self.output.generate(rng) {
+                Loot::Weapon(weapon) => Loot::Weapon(weapon.with_rarity(ItemRarity::Rare)),
+                loot => loot,
+            };
+            crafted.put_loot(loot)?;
+            output = Some(loot);
+        }
+        *inventory = crafted;
+        Ok(output)
+    }
+}
+
+/// The material weapons of the given type are made of.
+pub fn base_material(weapon_type: WeaponType) -> MaterialType {
+    match weapon_type {
+        WeaponType::Mace | WeaponType::Daggers => MaterialType::Scrap,
+        WeaponType::Bow | WeaponType::Claw => MaterialType::Leather,
+        WeaponType::Rod => MaterialType::Crystal,
+    }
+}
+
+/// The materials needed to upgrade the weapon to its next rarity and the chance percentage
+/// of the upgrade succeeding, `None` if it can't be upgraded.
+pub fn upgrade_cost(weapon: &Weapon) -> Option<([(MaterialType, u32); 2], u32)> {
+    let material = base_material(weapon.weapon_type());
+    match weapon.rarity().next()? {
+        ItemRarity::Legendry => Some(([(material, 3), (MaterialType::Essence, 1)], 70)),
+        _ => Some(([(material, 5), (MaterialType::Essence, 3)], 40)),
+    }
+}
+
+/// Upgrade the rarity of the weapon at the given inventory index, i.e., Rare to Legendry to Exotic.
+///
+/// The materials are consumed even if the upgrade fails.
+/// Nothing is changed if there's no weapon at the index, It's already Exotic or materials are missing.
+///
+/// ## Returns
+/// [`bool`] Whether the upgrade succeeded.
+pub fn upgrade<R: Rng + ?Sized>(
+    inventory: &mut Inventory,
+    index: usize,
+    rng: &mut R,
+) -> anyhow::Result<bool> {
+    let weapon = inventory
+        .weapon(index)
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("No weapon at inventory index {}", index))?;
+    let (cost, chance) = upgrade_cost(&weapon)
+        .ok_or_else(|| anyhow::anyhow!("{} can't be upgraded any further", weapon.name()))?;
+
+    let mut upgraded = inventory.clone();
+    for (material, amount) in cost {
+        upgraded.remove(LootItem::Material(material), amount)?;
+    }
+    let success = rng.gen_range(0..100) < chance;
+    if success {
+        if let (Some(weapon), Some(rarity)) = (upgraded.weapon_mut(index), weapon.rarity().next()) {
+            *weapon = weapon.with_rarity(rarity);
+        }
+    }
+    *inventory = upgraded;
+    Ok(success)
+}
+
+/// The durability restored by every material spent on repairs.
+pub const REPAIR_PER_MATERIAL: u32 = 20;
+
+/// The material and amount needed to fully repair the weapon,
+/// `None` if it has no durability or isn't damaged.
+pub fn repair_cost(weapon: &Weapon) -> Option<(MaterialType, u32)> {
+    let missing = weapon.durability()?.missing();
+    if missing == 0 {
+        return None;
+    }
+    Some((
+        base_material(weapon.weapon_type()),
+        missing.div_ceil(REPAIR_PER_MATERIAL),
+    ))
+}
+
+/// Fully repair the weapon at the given inventory index, Consuming materials from the same inventory.
+///
+/// Nothing is changed if there's no damaged weapon at the index or materials are missing.
+///
+/// ## Returns
+/// [`u32`] The durability restored.
+pub fn repair(inventory: &mut Inventory, index: usize) -> anyhow::Result<u32> {
+    let mut repaired = inventory.clone();
+    let weapon = repaired
+        .weapon_mut(index)
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("No weapon at inventory index {}", index))?;
+    let (weapon, restored) = repair_weapon(weapon, &mut repaired)?;
+    if let Some(slot) = repaired.weapon_mut(index) {
+        *slot = weapon;
+    }
+    *inventory = repaired;
+    Ok(restored)
+}
+
+/// Fully repair the weapon equipped in the given slot, Consuming materials from the inventory.
+///
+/// Nothing is changed if there's no damaged weapon in the slot or materials are missing.
+///
+/// ## Returns
+/// [`u32`] The durability restored.
+pub fn repair_equipped(
+    equipment: &mut Equipment,
+    slot: EquipSlot,
+    inventory: &mut Inventory,
+) -> anyhow::Result<u32> {
+    let weapon = equipment
+        .get_mut(slot)
+        .ok_or_else(|| anyhow::anyhow!("No weapon equipped in {:?}", slot))?;
+    let mut repaired = inventory.clone();
+    let (repaired_weapon, restored) = repair_weapon(*weapon, &mut repaired)?;
+    *weapon = repaired_weapon;
+    *inventory = repaired;
+    Ok(restored)
+}
+
+fn repair_weapon(mut weapon: Weapon, inventory: &mut Inventory) -> anyhow::Result<(Weapon, u32)> {
+    let (material, amount) = repair_cost(&weapon)
+        .ok_or_else(|| anyhow::anyhow!("{} doesn't need repairs", weapon.name()))?;
+    inventory.remove(LootItem::Material(material), amount)?;
+    let restored = weapon
+        .durability_mut()
+        .map_or(0, |durability| durability.repair(u32::MAX));
+    Ok((weapon, restored))
+}
+
+/// The materials salvaging the weapon yields, Rarer weapons yield more.
+pub fn salvage_yield(weapon: &Weapon) -> [(MaterialType, u32); 2] {
+    let material = base_material(weapon.weapon_type());
+    match weapon.rarity() {
+        ItemRarity::Rare => [(material, 2), (MaterialType::Essence, 0)],
+        ItemRarity::Legendry => [(material, 3), (MaterialType::Essence, 1)],
+        ItemRarity::Exotic => [(material, 4), (MaterialType::Essence, 2)],
+    }
+}
+
+/// Dismantle the weapon at the given inventory index into materials.
+///
+/// Nothing is changed if there's no weapon at the index or the inventory can't hold the materials.
+pub fn salvage_weapon<R: Rng + ?Sized>(
+    inventory: &mut Inventory,
+    index: usize,
+    rng: &mut R,
+) -> anyhow::Result<Vec<Material>> {
+    let mut salvaged = inventory.clone();
+    let weapon = salvaged
+        .take_weapon(index)
+        .ok_or_else(|| anyhow::anyhow!("No weapon at inventory index {}", index))?;
+    let materials = put_materials(&mut salvaged, &salvage_yield(&weapon), rng)?;
+    *inventory = salvaged;
+    Ok(materials)
+}
+
+/// Dismantle the consumable at the given inventory index into [`MaterialType::Essence`].
+///
+/// Nothing is changed if there's no consumable at the index or the inventory can't hold the materials.
+pub fn salvage_consumable<R: Rng + ?Sized>(
+    inventory: &mut Inventory,
+    index: usize,
+    rng: &mut R,
+) -> anyhow::Result<Vec<Material>> {
+    let mut salvaged = inventory.clone();
+    let consumable = salvaged
+        .take_consumable(index)
+        .ok_or_else(|| anyhow::anyhow!("No consumable at inventory index {}", index))?;
+    let amount = match consumable.consumable_type() {
+        ConsumableType::Elixir => 2,
+        _ => 1,
+    };
+    let materials = put_materials(&mut salvaged, &[(MaterialType::Essence, amount)], rng)?;
+    *inventory = salvaged;
+    Ok(materials)
+}
+
+fn put_materials<R: Rng + ?Sized>(
+    inventory: &mut Inventory,
+    materials: &[(MaterialType, u32)],
+    rng: &mut R,
+) -> anyhow::Result<Vec<Material>> {
+    let mut put = Vec::new();
+    for (material_type, amount) in materials {
+        for _ in 0..*amount {
+            let material = Material::generate(*material_type, rng);
+            inventory.put_material(material)?;
+            put.push(material);
+        }
+    }
+    Ok(put)
+}
+
+const fn material(material_type: MaterialType) -> LootItem {
+    LootItem::Material(material_type)
+}
+
+static RECIPES: [Recipe; 9] = [
+    Recipe::new(
+        "health_potion",
+        "Health Potion",
+        &[(material(MaterialType::Essence), 1)],
+        LootItem::Consumable(ConsumableType::HealthPotion),
+        100,
+    ),
+    Recipe::new(
+        "antidote",
+        "Antidote",
+        &[
+            (material(MaterialType::Essence), 1),
+            (material(MaterialType::Leather), 1),
+        ],
+        LootItem::Consumable(ConsumableType::Antidote),
+        100,
+    ),
+    Recipe::new(
+        "elixir",
+        "Elixir",
+        &[
+            (LootItem::Consumable(ConsumableType::HealthPotion), 1),
+            (LootItem::Consumable(ConsumableType::Antidote), 1),
+            (material(MaterialType::Essence), 2),
+        ],
+        LootItem::Consumable(ConsumableType::Elixir),
+        80,
+    ),
+    Recipe::new(
+        "crystal",
+        "Crystal",
+        &[(material(MaterialType::Essence), 3)],
+        material(MaterialType::Crystal),
+        100,
+    ),
+    Recipe::new(
+        "mace",
+        "Mace",
+        &[(material(MaterialType::Scrap), 6)],
+        LootItem::Weapon(WeaponType::Mace),
+        90,
+    ),
+    Recipe::new(
+        "daggers",
+        "Daggers",
+        &[
+            (material(MaterialType::Scrap), 4),
+            (material(MaterialType::Leather), 1),
+        ],
+        LootItem::Weapon(WeaponType::Daggers),
+        90,
+    ),
+    Recipe::new(
+        "bow",
+        "Bow",
+        &[(material(MaterialType::Leather), 5)],
+        LootItem::Weapon(WeaponType::Bow),
+        90,
+    ),
+    Recipe::new(
+        "claw",
+        "Claw",
+        &[
+            (material(MaterialType::Leather), 3),
+            (material(MaterialType::Scrap), 3),
+        ],
+        LootItem::Weapon(WeaponType::Claw),
+        90,
+    ),
+    Recipe::new(
+        "rod",
+        "Rod",
+        &[
+            (material(MaterialType::Crystal), 4),
+            (material(MaterialType::Essence), 1),
+        ],
+        LootItem::Weapon(WeaponType::Rod),
+        85,
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consumable::Consumable;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn stocked(material_type: MaterialType, amount: u32) -> Inventory {
+        let mut inventory = Inventory::new();
+        for _ in 0..amount {
+            inventory
+                .put_material(Material::new(material_type))
+                .unwrap();
+        }
+        inventory
+    }
+
+    #[test]
+    fn test_craft() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut inventory = stocked(MaterialType::Scrap, 8);
+        let recipe = Recipe::get("mace").unwrap();
+        assert!(recipe.can_craft(&inventory));
+        assert!(!Recipe::get("bow").unwrap().can_craft(&inventory));
+
+        let weapon = match recipe.craft(&mut inventory, &mut rng).unwrap() {
+            Some(Loot::Weapon(weapon)) => weapon,
+            crafted => panic!("Expected a mace but crafted {:?}", crafted),
+        };
+        assert_eq!(weapon.weapon_type(), WeaponType::Mace);
+        assert_eq!(weapon.rarity(), ItemRarity::Rare);
+        assert_eq!(inventory.count(material(MaterialType::Scrap)), 2);
+        assert_eq!(inventory.count(LootItem::Weapon(WeaponType::Mace)), 1);
+
+        // Missing inputs change nothing.
+        assert!(recipe.craft(&mut inventory, &mut rng).is_err());
+        assert_eq!(inventory.count(material(MaterialType::Scrap)), 2);
+    }
+
+    #[test]
+    fn test_elixir() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut inventory = stocked(MaterialType::Essence, 2);
+        inventory
+            .put_consumable(Consumable::new(ConsumableType::HealthPotion))
+            .unwrap();
+        inventory
+            .put_consumable(Consumable::new(ConsumableType::Antidote))
+            .unwrap();
+
+        let recipe = Recipe::get("elixir").unwrap();
+        let crafted = recipe.craft(&mut inventory, &mut rng).unwrap();
+        assert_eq!(inventory.len(), usize::from(crafted.is_some()));
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut inventory = Inventory::new();
+        inventory
+            .put_weapon(Weapon::new(WeaponType::Bow).with_rarity(ItemRarity::Rare))
+            .unwrap();
+
+        while inventory.weapon(0).unwrap().rarity() != ItemRarity::Exotic {
+            let (cost, _) = upgrade_cost(inventory.weapon(0).unwrap()).unwrap();
+            for (material_type, amount) in cost {
+                for _ in 0..amount {
+                    inventory
+                        .put_material(Material::new(material_type))
+                        .unwrap();
+                }
+            }
+            upgrade(&mut inventory, 0, &mut rng).unwrap();
+            assert_eq!(inventory.len(), 1);
+        }
+        assert!(upgrade(&mut inventory, 0, &mut rng).is_err());
+        assert!(upgrade(&mut inventory, 1, &mut rng).is_err());
+
+        let mut poor = Inventory::new();
+        poor.put_weapon(Weapon::new(WeaponType::Rod).with_rarity(ItemRarity::Rare))
+            .unwrap();
+        assert!(upgrade(&mut poor, 0, &mut rng).is_err());
+        assert_eq!(poor.len(), 1);
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut inventory = stocked(MaterialType::Leather, 2);
+        let mut bow = Weapon::new(WeaponType::Bow).with_durability(100);
+        bow.wear(100);
+        assert!(bow.is_broken());
+        assert_eq!(repair_cost(&bow), Some((MaterialType::Leather, 5)));
+
+        inventory.put_weapon(bow).unwrap();
+        assert!(repair(&mut inventory, 0).is_err());
+        assert!(inventory.weapon(0).unwrap().is_broken());
+
+        let mut equipment = Equipment::new();
+        let mut claw = Weapon::new(WeaponType::Claw).with_durability(100);
+        claw.wear(30);
+        equipment.equip(claw, EquipSlot::MainHand).unwrap();
+        assert_eq!(
+            repair_equipped(&mut equipment, EquipSlot::MainHand, &mut inventory).unwrap(),
+            30
+        );
+        assert_eq!(inventory.count(material(MaterialType::Leather)), 0);
+        assert!(repair_equipped(&mut equipment, EquipSlot::MainHand, &mut inventory).is_err());
+        assert!(repair_cost(&Weapon::new(WeaponType::Claw)).is_none());
+    }
+
+    #[test]
+    fn test_salvage() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut inventory = Inventory::new();
+        inventory
+            .put_weapon(Weapon::new(WeaponType::Mace).with_rarity(ItemRarity::Exotic))
+            .unwrap();
+        inventory
+            .put_consumable(Consumable::new(ConsumableType::Elixir))
+            .unwrap();
+
+        let materials = salvage_weapon(&mut inventory, 0, &mut rng).unwrap();
+        assert_eq!(materials.len(), 6);
+        assert!(inventory.get_weapons().is_empty());
+        assert_eq!(inventory.count(material(MaterialType::Scrap)), 4);
+
+        salvage_consumable(&mut inventory, 0, &mut rng).unwrap();
+        assert_eq!(inventory.count(material(MaterialType::Essence)), 4);
+        assert!(salvage_weapon(&mut inventory, 0, &mut rng).is_err());
+    }
+}
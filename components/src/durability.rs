@@ -0,0 +1,183 @@
+//! Durability of items that wear down with use.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt};
+
+/// Durability below this percentage of the max is low, Halving the item's effectiveness.
+pub const LOW_DURABILITY: u32 = 25;
+
+/// The saved state of a durability, Validated when loaded.
+#[derive(Serialize, Deserialize)]
+struct DurabilityRecord {
+    current: u32,
+    max: u32,
+}
+
+/// Core durability component of items that wear down with use and break at zero.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "DurabilityRecord", into = "DurabilityRecord")]
+pub struct Durability {
+    current: u32,
+    max: u32,
+}
+
+impl TryFrom<DurabilityRecord> for Durability {
+    type Error = anyhow::Error;
+
+    fn try_from(record: DurabilityRecord) -> anyhow::Result<Durability> {
+        if record.max == 0 || record.current > record.max {
+            return Err(anyhow::anyhow!(
+                "Durability {}/{} is out of range",
+                record.current,
+                record.max
+            ));
+        }
+        Ok(Durability {
+            current: record.current,
+            max: record.max,
+        })
+    }
+}
+
+impl From<Durability> for DurabilityRecord {
+    fn from(durability: Durability) -> DurabilityRecord {
+        DurabilityRecord {
+            current: durability.current,
+            max: durability.max,
+        }
+    }
+}
+
+impl fmt::Display for Durability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Durability({}/{})", self.current, self.max)
+    }
+}
+
+impl Durability {
+    /// Creates a new full durability, The max is at least `1`.
+    pub fn new(max: u32) -> Durability {
+        let max = max.max(1);
+        Durability { current: max, max }
+    }
+
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    /// The durability lost that can be repaired.
+    pub fn missing(&self) -> u32 {
+        self.max.saturating_sub(self.current)
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.current == 0
+    }
+
+    /// Whether the durability is below [`LOW_DURABILITY`] percent of the max.
+    pub fn is_low(&self) -> bool {
+        self.current.saturating_mul(100) < self.max.saturating_mul(LOW_DURABILITY)
+    }
+
+    /// How effective the item is as a percentage, `50` when low and `0` when broken.
+    pub fn effectiveness(&self) -> u32 {
+        if self.is_broken() {
+            0
+        } else if self.is_low() {
+            50
+        } else {
+            100
+        }
+    }
+
+    /// Wear down the durability.
+    ///
+    /// ## Returns
+    /// [`bool`] Whether this broke the item.
+    pub fn wear(&mut self, amount: u32) -> bool {
+        let was_broken = self.is_broken();
+        self.current = self.current.saturating_sub(amount);
+        !was_broken && self.is_broken()
+    }
+
+    /// Restore the durability up to the max.
+    ///
+    /// ## Returns
+    /// [`u32`] The durability restored.
+    pub fn repair(&mut self, amount: u32) -> u32 {
+        let amount = amount.min(self.missing());
+        self.current += amount;
+        amount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wear() {
+        let mut durability = Durability::new(20);
+        assert_eq!(durability.effectiveness(), 100);
+
+        assert!(!durability.wear(16));
+        assert!(durability.is_low());
+        assert_eq!(durability.effectiveness(), 50);
+
+        assert!(durability.wear(10));
+        assert!(durability.is_broken());
+        assert!(!durability.wear(1));
+        assert_eq!(durability.effectiveness(), 0);
+
+        assert_eq!(durability.repair(50), 20);
+        assert_eq!(durability.to_string(), "Durability(20/20)");
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut durability = Durability::new(50);
+        durability.wear(20);
+        let json = serde_json::to_string(&durability).unwrap();
+        assert_eq!(json, r#"{"current":30,"max":50}"#);
+        assert_eq!(
+            serde_json::from_str::<Durability>(&json).unwrap(),
+            durability
+        );
+
+        assert!(serde_json::from_str::<Durability>(r#"{"current":80,"max":50}"#).is_err());
+        assert!(serde_json::from_str::<Durability>(r#"{"current":0,"max":0}"#).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{items::Item, weapon::Weapon};
+use serde::{Deserialize, Serialize};
+
+/// The hand slots a weapon can be equipped in.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum EquipSlot {
+    MainHand,
+    OffHand,
+}
+
+/// Core equipment component.
+///
+/// Holds the weapons an object is currently wielding.
+/// Two handed and paired weapons occupy both slots and always live in the main hand,
+/// While two one handed weapons can be dual wielded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Equipment {
+    main_hand: Option<Weapon>,
+    off_hand: Option<Weapon>,
+}
+
+impl std::fmt::Display for Equipment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Equipment(main hand: {:?}, off hand: {:?})",
+            self.main_hand.map(|w| w.weapon_type()),
+            self.off_hand.map(|w| w.weapon_type())
+        )
+    }
+}
+
+impl Equipment {
+    /// Creates a new empty equipment object.
+    pub fn new() -> Equipment {
+        Self::default()
+    }
+
+    /// Returns the weapon in the main hand if any.
+    pub fn main_hand(&self) -> Option<&Weapon> {
+        self.main_hand.as_ref()
+    }
+
+    /// Returns the weapon in the off hand if any.
+    pub fn off_hand(&self) -> Option<&Weapon> {
+        self.off_hand.as_ref()
+    }
+
+    /// Returns the weapon in the given slot if any.
+    pub fn get(&self, slot: EquipSlot) -> Option<&Weapon> {
+        match slot {
+            EquipSlot::MainHand => self.main_hand(),
+            EquipSlot::OffHand => self.off_hand(),
+        }
+    }
+
+    /// Returns a mutable reference to the weapon in the given slot if any.
+    pub fn get_mut(&mut self, slot: EquipSlot) -> Option<&mut Weapon> {
+        match slot {
+            EquipSlot::MainHand => self.main_hand.as_mut(),
+            EquipSlot::OffHand => self.off_hand.as_mut(),
+        }
+    }
+
+    /// Whether a weapon is held in both hands.
+    pub fn is_dual_wielding(&self) -> bool {
+        self.main_hand.is_some() && self.off_hand.is_some()
+    }
+
+    /// An iterator over all equipped weapons, Main hand first.
+    pub fn weapons(&self) -> impl Iterator<Item = &Weapon> {
+        self.main_hand.iter().chain(self.off_hand.iter())
+    }
+
+    /// The total damage of a hit with the equipped weapons.
+    ///
+    /// Off hand weapons deal half of their damage.
+    pub fn damage(&self) -> u32 {
+        let main = self.main_hand.map_or(0, |w| w.damage());
+        let off = self.off_hand.map_or(0, |w| w.damage() / 2);
+        main + off
+    }
+
+    /// Wear down the durability of the equipped weapons.
+    ///
+    /// ## Returns
+    /// [`Vec<Weapon>`] The weapons this broke, They stay equipped.
+    pub fn wear(&mut self, amount: u32) -> Vec<Weapon> {
+        self.main_hand
+            .iter_mut()
+            .chain(self.off_hand.iter_mut())
+            .filter_map(|weapon| weapon.wear(amount).then_some(*weapon))
+            .collect()
+    }
+
+    /// Equip a weapon in the given slot.
+    ///
+    /// Weapons that occupy both hands can only be equipped in the main hand and
+    /// will unequip the off hand, Broken weapons can't be equipped.
+    ///
+    /// ## Returns
+    /// [`Vec<Weapon>`] The weapons that were unequipped to make room for this weapon.
+    pub fn equip(&mut self, weapon: Weapon, slot: EquipSlot) -> anyhow::Result<Vec<Weapon>> {
+        if weapon.is_broken() {
+            return Err(anyhow::anyhow!("{} is broken", weapon.name()));
+        }
+        let mut displaced = Vec::new();
+
+        match slot {
+            EquipSlot::MainHand => {
+                if weapon.handedness().is_both_hands() {
+                    displaced.extend(self.off_hand.take());
+                }
+                displaced.extend(self.main_hand.replace(weapon));
+            }
+            EquipSlot::OffHand => {
+                if weapon.handedness().is_both_hands() {
+                    return Err(anyhow::anyhow!(
+                        "{} requires both hands and can only be equipped in the main hand",
+                        weapon
+                    ));
+                }
+                if self.occupies_both_hands() {
+                    return Err(anyhow::anyhow!("Main hand weapon requires both hands"));
+                }
+                displaced.extend(self.off_hand.replace(weapon));
+            }
+        }
+        Ok(displaced)
+    }
+
+    /// Unequip the weapon in the given slot.
+    pub fn unequip(&mut self, slot: EquipSlot) -> Option<Weapon> {
+        match slot {
+            EquipSlot::MainHand => self.main_hand.take(),
+            EquipSlot::OffHand => self.off_hand.take(),
+        }
+    }
+
+    /// Swap the main hand and off hand weapons.
+    pub fn swap_hands(&mut self) -> anyhow::Result<()> {
+        if self.occupies_both_hands() {
+            return Err(anyhow::anyhow!(
+                "Cannot swap hands while wielding a weapon that requires both hands"
+            ));
+        }
+        std::mem::swap(&mut self.main_hand, &mut self.off_hand);
+        Ok(())
+    }
+
+    fn occupies_both_hands(&self) -> bool {
+        self.main_hand
+            .is_some_and(|w| w.handedness().is_both_hands())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weapon::WeaponType;
+
+    #[test]
+    fn test_dual_wield() {
+        let mut equipment = Equipment::new();
+        equipment
+            .equip(Weapon::new(WeaponType::Mace), EquipSlot::MainHand)
+            .unwrap();
+        equipment
+            .equip(Weapon::new(WeaponType::Claw), EquipSlot::OffHand)
+            .unwrap();
+        assert!(equipment.is_dual_wielding());
+
+        equipment.swap_hands().unwrap();
+        assert_eq!(
+            equipment.main_hand().unwrap().weapon_type(),
+            WeaponType::Claw
+        );
+        assert_eq!(
+            equipment.off_hand().unwrap().weapon_type(),
+            WeaponType::Mace
+        );
+    }
+
+    #[test]
+    fn test_two_handed() {
+        let mut equipment = Equipment::new();
+        equipment
+            .equip(Weapon::new(WeaponType::Mace), EquipSlot::MainHand)
+            .unwrap();
+        equipment
+            .equip(Weapon::new(WeaponType::Rod), EquipSlot::OffHand)
+            .unwrap();
+
+        let displaced = equipment
+            .equip(Weapon::new(WeaponType::Bow), EquipSlot::MainHand)
+            .unwrap();
+        assert_eq!(displaced.len(), 2);
+        assert!(equipment.off_hand().is_none());
+
+        assert!(equipment
+            .equip(Weapon::new(WeaponType::Claw), EquipSlot::OffHand)
+            .is_err());
+        assert!(equipment
+            .equip(Weapon::new(WeaponType::Daggers), EquipSlot::OffHand)
+            .is_err());
+        assert!(equipment.swap_hands().is_err());
+    }
+
+    #[test]
+    fn test_broken() {
+        let mut equipment = Equipment::new();
+        let mut mace = Weapon::new(WeaponType::Mace).with_durability(8);
+        equipment.equip(mace, EquipSlot::MainHand).unwrap();
+        let damage = equipment.damage();
+
+        assert!(equipment.wear(7).is_empty());
+        assert_eq!(equipment.damage(), damage / 2);
+        assert_eq!(equipment.wear(1).len(), 1);
+        assert_eq!(equipment.damage(), 0);
+
+        mace.wear(8);
+        assert!(equipment.equip(mace, EquipSlot::MainHand).is_err());
+    }
+}
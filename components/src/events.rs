@@ -0,0 +1,305 @@
+//! Typed gameplay events and a bus to subscribe to them.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    equipment::EquipSlot,
+    loot::Loot,
+    status::StatusKind,
+    weapon::{Weapon, WeaponAmmoType},
+};
+use std::{collections::VecDeque, fmt};
+
+/// ## Gameplay events.
+///
+/// Characters are referred to by name, See `Char::event_name` in the characters crate.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum GameEvent {
+    /// Damage was dealt to a character, `source` is `None` for damage over time and the like.
+    ///
+    /// `ammo` is the ammo of the source's main hand weapon if it's known.
+    DamageDealt {
+        source: Option<&'static str>,
+        target: &'static str,
+        amount: u32,
+        ammo: Option<WeaponAmmoType>,
+    },
+    Healed {
+        target: &'static str,
+        amount: u32,
+    },
+    Died {
+        target: &'static str,
+    },
+    Revived {
+        target: &'static str,
+    },
+    ItemPickedUp {
+        owner: &'static str,
+        item: Loot,
+    },
+    ItemEquipped {
+        owner: &'static str,
+        weapon: Weapon,
+        slot: EquipSlot,
+    },
+    /// A character reached a new level, One event per level gained.
+    LevelUp {
+        target: &'static str,
+        level: u32,
+    },
+    StatusApplied {
+        target: &'static str,
+        kind: StatusKind,
+    },
+}
+
+/// The kinds of [`GameEvent`]s, Used to subscribe to one kind of event.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum EventKind {
+    DamageDealt,
+    Healed,
+    Died,
+    Revived,
+    ItemPickedUp,
+    ItemEquipped,
+    LevelUp,
+    StatusApplied,
+}
+
+impl GameEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            GameEvent::DamageDealt { .. } => EventKind::DamageDealt,
+            GameEvent::Healed { .. } => EventKind::Healed,
+            GameEvent::Died { .. } => EventKind::Died,
+            GameEvent::Revived { .. } => EventKind::Revived,
+            GameEvent::ItemPickedUp { .. } => EventKind::ItemPickedUp,
+            GameEvent::ItemEquipped { .. } => EventKind::ItemEquipped,
+            GameEvent::LevelUp { .. } => EventKind::LevelUp,
+            GameEvent::StatusApplied { .. } => EventKind::StatusApplied,
+        }
+    }
+}
+
+/// Identifies a subscriber of an [`EventBus`], Used to unsubscribe.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct SubscriberId(usize);
+
+struct Subscriber {
+    id: SubscriberId,
+    kind: Option<EventKind>,
+    handler: Box<dyn FnMut(&GameEvent) + Send>,
+}
+
+/// Dispatches gameplay events to subscribers.
+///
+/// Events are either emitted and handled right away, Or queued and handled on the next [`EventBus::dispatch`].
+/// Subscribers are called in the order they subscribed.
+///
+/// ```
+/// use components::{EventBus, EventKind, GameEvent};
+/// use std::sync::{Arc, Mutex};
+///
+/// let deaths = Arc::new(Mutex::new(0));
+/// let mut events = EventBus::new();
+/// let counter = deaths.clone();
+/// events.subscribe_to(EventKind::Died, move |_| *counter.lock().unwrap() += 1);
+///
+/// events.emit(GameEvent::Died { target: "Vamp" });
+/// events.queue(GameEvent::Revived { target: "Vamp" });
+/// events.queue(GameEvent::Died { target: "Vamp" });
+/// assert_eq!(*deaths.lock().unwrap(), 1);
+///
+/// assert_eq!(events.dispatch(), 2);
+/// assert_eq!(*deaths.lock().unwrap(), 2);
+/// ```
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Subscriber>,
+    queue: VecDeque<GameEvent>,
+    next_id: usize,
+}
+
+impl EventBus {
+    /// Creates a new event bus with no subscribers.
+    pub fn new() -> EventBus {
+        Self::default()
+    }
+
+    /// Subscribe to every event.
+    pub fn subscribe<F>(&mut self, handler: F) -> SubscriberId
+    where
+        F: FnMut(&GameEvent) + Send + 'static,
+    {
+        self.add(None, Box::new(handler))
+    }
+
+    /// Subscribe to events of the given kind.
+    pub fn subscribe_to<F>(&mut self, kind: EventKind, handler: F) -> SubscriberId
+    where
+        F: FnMut(&GameEvent) + Send + 'static,
+    {
+        self.add(Some(kind), Box::new(handler))
+    }
+
+    fn add(
+        &mut self,
+        kind: Option<EventKind>,
+        handler: Box<dyn FnMut(&GameEvent) + Send>,
+    ) -> SubscriberId {
+        let id = SubscriberId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.push(Subscriber { id, kind, handler });
+        id
+    }
+
+    /// Remove a subscriber, Returns whether it was subscribed.
+    pub fn unsubscribe(&mut self, id: SubscriberId) -> bool {
+        let len = self.subscribers.len();
+        self.subscribers.retain(|s| s.id != id);
+        self.subscribers.len() != len
+    }
+
+    pub fn subscribers(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Handle an event right away.
+    pub fn emit(&mut self, event: GameEvent) {
+        for subscriber in &mut self.subscribers {
+            if subscriber.kind.is_none_or(|kind| kind == event.kind()) {
+                (subscriber.handler)(&event);
+            }
+        }
+    }
+
+    /// Queue an event to be handled on the next [`EventBus::dispatch`].
+    pub fn queue(&mut self, event: GameEvent) {
+        self.queue.push_back(event);
+    }
+
+    /// The queued events in the order they were queued.
+    pub fn pending(&self) -> impl Iterator<Item = &GameEvent> {
+        self.queue.iter()
+    }
+
+    /// Handle every queued event in the order they were queued.
+    ///
+    /// ## Returns
+    /// [`usize`] The amount of events handled.
+    pub fn dispatch(&mut self) -> usize {
+        let mut handled = 0;
+        while let Some(event) = self.queue.pop_front() {
+            self.emit(event);
+            handled += 1;
+        }
+        handled
+    }
+}
+
+impl Extend<GameEvent> for EventBus {
+    /// Queue every event.
+    fn extend<T: IntoIterator<Item = GameEvent>>(&mut self, events: T) {
+        self.queue.extend(events);
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.subscribers.len())
+            .field("queue", &self.queue)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn recorder(
+        events: &mut EventBus,
+        kind: Option<EventKind>,
+    ) -> (Arc<Mutex<Vec<GameEvent>>>, SubscriberId) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = log.clone();
+        let handler = move |event: &GameEvent| sink.lock().unwrap().push(*event);
+        let id = match kind {
+            Some(kind) => events.subscribe_to(kind, handler),
+            None => events.subscribe(handler),
+        };
+        (log, id)
+    }
+
+    #[test]
+    fn test_subscribers() {
+        let mut events = EventBus::new();
+        let (all, id) = recorder(&mut events, None);
+        let (healed, _) = recorder(&mut events, Some(EventKind::Healed));
+
+        let heal = GameEvent::Healed {
+            target: "Tyr",
+            amount: 10,
+        };
+        events.emit(heal);
+        events.emit(GameEvent::LevelUp {
+            target: "Tyr",
+            level: 2,
+        });
+        assert_eq!(all.lock().unwrap().len(), 2);
+        assert_eq!(*healed.lock().unwrap(), vec![heal]);
+
+        assert!(events.unsubscribe(id));
+        assert!(!events.unsubscribe(id));
+        assert_eq!(events.subscribers(), 1);
+        events.emit(heal);
+        assert_eq!(all.lock().unwrap().len(), 2);
+        assert_eq!(healed.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_queue() {
+        let mut events = EventBus::new();
+        let (log, _) = recorder(&mut events, None);
+
+        let died = GameEvent::Died { target: "Kain" };
+        let revived = GameEvent::Revived { target: "Kain" };
+        events.queue(died);
+        events.extend(vec![revived]);
+        assert!(log.lock().unwrap().is_empty());
+        assert_eq!(events.pending().count(), 2);
+
+        assert_eq!(events.dispatch(), 2);
+        assert_eq!(*log.lock().unwrap(), vec![died, revived]);
+        assert_eq!(events.dispatch(), 0);
+    }
+}
@@ -28,18 +28,29 @@
 // OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
 use std::{thread, time};
 
+/// The max health of a health bar unless it starts higher.
+const DEFAULT_MAX_HEALTH: u32 = 100;
+
+fn default_max_health() -> u32 {
+    DEFAULT_MAX_HEALTH
+}
+
 /// Core health component for objects that can live.
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Health {
     current_health: u32,
+    #[serde(default = "default_max_health")]
+    max_health: u32,
 }
 
 impl Default for Health {
     fn default() -> Self {
         Health {
             current_health: 100,
+            max_health: DEFAULT_MAX_HEALTH,
         }
     }
 }
@@ -74,15 +85,19 @@ impl std::fmt::Display for Health {
 
 impl Health {
     /// Creates a new health bar for an object.
+    ///
+    /// The max health is 100, Or the starting health if it's higher.
     pub fn new(starter: Option<u32>) -> Health {
         // Default start health is 100
+        let current_health = starter.unwrap_or(100);
         Self {
-            current_health: starter.unwrap_or(100),
+            current_health,
+            max_health: current_health.max(DEFAULT_MAX_HEALTH),
         }
     }
 
     pub fn regen(&mut self) {
-        let mut slf = self.clone();
+        let mut slf = *self;
 
         thread::spawn(move || loop {
             if slf.ok() {
@@ -99,7 +114,7 @@ impl Health {
     /// ## Returns
     /// [`u32`] The current dripped health bar.
     pub fn drip(&mut self, by: u32) -> u32 {
-        self.current_health -= by;
+        self.current_health = self.current_health.saturating_sub(by);
         self.current_health
     }
 
@@ -126,7 +141,7 @@ impl Health {
         }
 
         if self.validate() {
-            self.current_health += by;
+            self.current_health = self.current_health.saturating_add(by).min(self.max_health);
         }
         Ok(self.current_health)
     }
@@ -142,7 +157,7 @@ impl Health {
         // Check if we're not dead nor at max health bar.
         if self.validate() {
             let range: u32 = thread_rng().gen_range(1..=curr_health / 2);
-            curr_health += range;
+            curr_health = (curr_health + range).min(self.max_health);
 
             self.current_health = curr_health;
         }
@@ -154,14 +169,14 @@ impl Health {
         self.set_health(0);
     }
 
-    /// Check if the health bar is at >=100.
+    /// Check if the health bar is at max health.
     pub fn ok(&self) -> bool {
-        self.current_health >= 100
+        self.current_health >= self.max_health
     }
 
     pub fn revive(&mut self) -> anyhow::Result<bool> {
         if self.is_killed() {
-            self.set_health(100);
+            self.set_health(self.max_health);
             Ok(true)
         } else {
             Err(anyhow::anyhow!("Character is already alive!"))
@@ -181,13 +196,24 @@ impl Health {
         self.current_health
     }
 
+    /// The highest this health bar can be healed to.
+    pub fn max(&self) -> u32 {
+        self.max_health
+    }
+
+    /// Set the max health, Lowering the current health if it's above it.
+    pub fn set_max(&mut self, max: u32) {
+        self.max_health = max;
+        self.current_health = self.current_health.min(max);
+    }
+
     fn set_health(&mut self, health: u32) {
         self.current_health = health;
     }
 
     fn validate(&self) -> bool {
         let curr_health = self.current();
-        !curr_health >= 100 || !curr_health <= 0
+        curr_health > 0 && curr_health < self.max_health
     }
 }
 
@@ -196,6 +222,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::manual_range_contains)]
     fn test_health_regen() {
         let mut health = Health::default();
         assert_eq!(health.current(), 100);
@@ -240,4 +267,25 @@ mod tests {
         health.incr(10).unwrap_or(0);
         assert_eq!(health.current(), 0);
     }
+
+    #[test]
+    fn test_health_incr_overflow() {
+        let mut health = Health::new(Some(90));
+        assert_eq!(health.incr(u32::MAX), Ok(100));
+    }
+
+    #[test]
+    fn test_max_health() {
+        let mut health = Health::new(Some(120));
+        assert_eq!(health.max(), 120);
+
+        health.drip(30);
+        health.incr(50).unwrap();
+        assert_eq!(health.current(), 120);
+        assert!(health.ok());
+
+        health.set_max(100);
+        assert_eq!(health.current(), 100);
+        assert_eq!(Health::new(Some(80)).max(), 100);
+    }
 }
@@ -30,22 +30,159 @@
 
 //! A crate includes all components a character can have. i.e., Inventory, Health, etc.
 
-use crate::weapon::Weapon;
+use crate::{
+    consumable::Consumable,
+    items::{Item, ItemType, MetaData},
+    loot::{Loot, LootItem},
+    material::Material,
+    query::{Entry, ItemRef, Query},
+    weapon::Weapon,
+};
+use serde::{Deserialize, Serialize};
+
+/// ## How encumbered a character is by the weight it carries.
+/// - [`Encumbrance::Light`]
+///     - Carrying less than half of the max weight.
+/// - [`Encumbrance::Burdened`]
+///     - Carrying at least half of the max weight, Movement speed is reduced by a quarter.
+/// - [`Encumbrance::Overloaded`]
+///     - Carrying at least 80% of the max weight, Movement speed is halved.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Encumbrance {
+    #[default]
+    Light,
+    Burdened,
+    Overloaded,
+}
+
+impl Encumbrance {
+    /// The movement speed percentage kept at this encumbrance.
+    pub fn movement_speed(&self) -> u32 {
+        match self {
+            Encumbrance::Light => 100,
+            Encumbrance::Burdened => 75,
+            Encumbrance::Overloaded => 50,
+        }
+    }
+}
+
+/// The width and height of an inventory grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grid {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A stack of items of the same type.
+///
+/// Items stack up to their [`Item::max_stack`], Stacked items share the id of the first item in the stack.
+///
+/// Stackable items of the same type are interchangeable and only the first id is kept,
+/// See [`Loot::is_same`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StackRecord<T>")]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub struct Stack<T> {
+    item: T,
+    quantity: u32,
+    acquired: u64,
+}
+
+/// A deserialized stack, Items saved before stacking existed are stacks of one.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StackRecord<T> {
+    Stack {
+        item: T,
+        quantity: u32,
+        #[serde(default)]
+        acquired: u64,
+    },
+    Item(T),
+}
+
+impl<T> From<StackRecord<T>> for Stack<T> {
+    fn from(record: StackRecord<T>) -> Stack<T> {
+        match record {
+            StackRecord::Stack {
+                item,
+                quantity,
+                acquired,
+            } => Stack {
+                item,
+                quantity: quantity.max(1),
+                acquired,
+            },
+            StackRecord::Item(item) => Stack::new(item),
+        }
+    }
+}
+
+impl<T> Stack<T> {
+    /// A stack of one item.
+    pub fn new(item: T) -> Stack<T> {
+        Stack {
+            item,
+            quantity: 1,
+            acquired: 0,
+        }
+    }
+}
+
+impl<T: Item> Stack<T> {
+    pub fn item(&self) -> &T {
+        &self.item
+    }
+
+    /// The number of items in this stack, Never `0`.
+    pub fn quantity(&self) -> u32 {
+        self.quantity
+    }
+
+    /// When this stack was put in its inventory, Counting up from `0` for each new stack.
+    pub fn acquired(&self) -> u64 {
+        self.acquired
+    }
+
+    /// Whether this stack holds as many items as the item stacks up to.
+    pub fn is_full(&self) -> bool {
+        self.quantity >= self.item.max_stack()
+    }
+}
 
 /// Core object inventory component.
 ///
 /// This includes weapons items it ownns, cosmetics, etc.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// The capacity is limited by the item count where a stack counts as one item,
+/// And optionally by the total weight and a grid.
+/// Items in a grid are auto arranged, An item fits if every item can be arranged in the grid with it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Inventory {
-    weapons: Vec<Weapon>,
+    #[serde(default)]
+    weapons: Vec<Stack<Weapon>>,
+    consumables: Vec<Stack<Consumable>>,
+    #[serde(default)]
+    materials: Vec<Stack<Material>>,
     max_size: u32,
+    #[serde(default)]
+    max_weight: Option<u32>,
+    #[serde(default)]
+    grid: Option<Grid>,
+    #[serde(default)]
+    acquired: u64,
 }
 
 impl Default for Inventory {
     fn default() -> Self {
         Self {
             weapons: Vec::new(),
+            consumables: Vec::new(),
+            materials: Vec::new(),
             max_size: 50,
+            max_weight: None,
+            grid: None,
+            acquired: 0,
         }
     }
 }
@@ -53,12 +190,20 @@ impl Default for Inventory {
 impl Drop for Inventory {
     fn drop(&mut self) {
         self.weapons.clear();
+        self.consumables.clear();
+        self.materials.clear();
     }
 }
 
 impl std::fmt::Display for Inventory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Inventory(weapons: {})", self.weapons.len())
+        write!(
+            f,
+            "Inventory(weapons: {}, consumables: {}, materials: {})",
+            self.weapons.len(),
+            self.consumables.len(),
+            self.materials.len()
+        )
     }
 }
 
@@ -68,23 +213,803 @@ impl Inventory {
         Self::default()
     }
 
+    /// Creates a new inventory holding at most the given amount of items.
+    pub fn with_capacity(max_size: u32) -> Inventory {
+        let mut inventory = Self::default();
+        inventory.max_size = max_size;
+        inventory
+    }
+
+    /// Limit the total weight of the items this inventory holds.
+    pub fn with_max_weight(mut self, max_weight: u32) -> Inventory {
+        self.max_weight = Some(max_weight);
+        self
+    }
+
+    /// Arrange the items this inventory holds in a grid of the given size.
+    pub fn with_grid(mut self, width: u32, height: u32) -> Inventory {
+        self.grid = Some(Grid { width, height });
+        self
+    }
+
     pub fn is_empty(&self) -> bool {
-        !self.is_full()
+        self.len() == 0
     }
 
+    /// Whether this inventory holds as many items as it can.
     pub fn is_full(&self) -> bool {
-        self.max_size == (self.weapons.len() + 1) as u32
+        self.len() >= self.max_size as usize
+    }
+
+    /// The number of items in this inventory, A stack counts as one item.
+    pub fn len(&self) -> usize {
+        self.weapons.len() + self.consumables.len() + self.materials.len()
+    }
+
+    /// The most items this inventory can hold.
+    pub fn max_size(&self) -> u32 {
+        self.max_size
+    }
+
+    /// The most weight this inventory can hold if limited.
+    pub fn max_weight(&self) -> Option<u32> {
+        self.max_weight
+    }
+
+    pub fn grid(&self) -> Option<Grid> {
+        self.grid
+    }
+
+    /// An iterator over every item in this inventory, Weapons first then consumables then materials.
+    ///
+    /// Stacks are returned once with their quantity.
+    pub fn items(&self) -> impl Iterator<Item = (&dyn Item, u32)> {
+        self.weapons
+            .iter()
+            .map(|s| (&s.item as &dyn Item, s.quantity))
+            .chain(
+                self.consumables
+                    .iter()
+                    .map(|s| (&s.item as &dyn Item, s.quantity)),
+            )
+            .chain(
+                self.materials
+                    .iter()
+                    .map(|s| (&s.item as &dyn Item, s.quantity)),
+            )
     }
 
-    pub fn get_weapons(&self) -> Vec<Weapon> {
-        self.weapons.to_vec()
+    /// The total weight of the items in this inventory.
+    pub fn weight(&self) -> u32 {
+        self.items().fold(0u32, |weight, (item, quantity)| {
+            weight.saturating_add(item.weight().saturating_mul(quantity))
+        })
+    }
+
+    /// How encumbered the weight of the items makes its carrier, Always light without a max weight.
+    pub fn encumbrance(&self) -> Encumbrance {
+        let max = match self.max_weight {
+            Some(max) => u64::from(max),
+            None => return Encumbrance::Light,
+        };
+        let weight = u64::from(self.weight()) * 100;
+        if weight >= max * 80 {
+            Encumbrance::Overloaded
+        } else if weight >= max * 50 {
+            Encumbrance::Burdened
+        } else {
+            Encumbrance::Light
+        }
+    }
+
+    /// Check whether the item fits in this inventory.
+    ///
+    /// ## Returns
+    /// Why the item doesn't fit if it doesn't.
+    pub fn check_fit(&self, item: &Loot) -> anyhow::Result<()> {
+        self.check_fit_all(std::slice::from_ref(item))
+    }
+
+    /// Whether the item fits in this inventory.
+    pub fn can_fit(&self, item: &Loot) -> bool {
+        self.check_fit(item).is_ok()
+    }
+
+    /// Check whether all of the items fit in this inventory together.
+    pub fn check_fit_all(&self, items: &[Loot]) -> anyhow::Result<()> {
+        // The room left for each kind of item, Starting with the room in its stacks.
+        let mut room: Vec<(Loot, u32)> = Vec::new();
+        let mut sizes = Vec::new();
+        let mut weight = 0u32;
+        for loot in items {
+            let item = loot.item();
+            weight = weight.saturating_add(item.weight());
+            if item.max_stack() <= 1 {
+                sizes.push(item.size());
+                continue;
+            }
+            let index = match room.iter().position(|(other, _)| other.is_same(loot)) {
+                Some(index) => index,
+                None => {
+                    room.push((*loot, self.stack_room(loot)));
+                    room.len() - 1
+                }
+            };
+            let left = &mut room[index].1;
+            if *left > 0 {
+                *left -= 1;
+            } else {
+                sizes.push(item.size());
+                *left = item.max_stack().saturating_sub(1);
+            }
+        }
+        self.check_capacity(weight, &sizes)
+    }
+
+    /// Borrow every item in this inventory with where it is, Weapons first then consumables then materials.
+    pub fn entries(&self) -> impl Iterator<Item = Entry<'_>> {
+        fn entries<'a, T>(
+            stacks: &'a [Stack<T>],
+            item: impl Fn(&'a T) -> ItemRef<'a>,
+        ) -> impl Iterator<Item = Entry<'a>> {
+            stacks
+                .iter()
+                .enumerate()
+                .map(move |(index, s)| Entry::new(item(&s.item), index, s.quantity, s.acquired))
+        }
+        entries(&self.weapons, ItemRef::Weapon)
+            .chain(entries(&self.consumables, ItemRef::Consumable))
+            .chain(entries(&self.materials, ItemRef::Material))
+    }
+
+    /// A query over the items in this inventory.
+    pub fn query(&self) -> Query<'_> {
+        Query::new(self)
+    }
+
+    /// The positions of the items in the grid in the order of [`Inventory::items`], `None` without a grid.
+    pub fn layout(&self) -> Option<Vec<(u32, u32)>> {
+        let sizes: Vec<_> = self.items().map(|(item, _)| item.size()).collect();
+        arrange(self.grid?, &sizes)
+    }
+
+    /// The weapons in this inventory, Each is a stack of one.
+    pub fn get_weapons(&self) -> &[Stack<Weapon>] {
+        &self.weapons
+    }
+
+    /// Returns the weapon at the given index if any.
+    pub fn weapon(&self, index: usize) -> Option<&Weapon> {
+        self.weapons.get(index).map(|s| &s.item)
     }
 
     pub fn put_weapon(&mut self, weapon: Weapon) -> anyhow::Result<()> {
-        if self.is_full() {
+        self.put_loot(Loot::Weapon(weapon))
+    }
+
+    /// Returns a mutable reference to the weapon at the given index if any.
+    pub fn weapon_mut(&mut self, index: usize) -> Option<&mut Weapon> {
+        self.weapons.get_mut(index).map(|s| &mut s.item)
+    }
+
+    /// Take the weapon at the given index out of the inventory.
+    pub fn take_weapon(&mut self, index: usize) -> Option<Weapon> {
+        take_one(&mut self.weapons, index)
+    }
+
+    pub fn get_consumables(&self) -> &[Stack<Consumable>] {
+        &self.consumables
+    }
+
+    /// Put a consumable in the first stack of its type with room, Or in a new stack.
+    pub fn put_consumable(&mut self, consumable: Consumable) -> anyhow::Result<()> {
+        self.put_loot(Loot::Consumable(consumable))
+    }
+
+    /// Take one consumable out of the stack at the given index.
+    pub fn take_consumable(&mut self, index: usize) -> Option<Consumable> {
+        take_one(&mut self.consumables, index)
+    }
+
+    pub fn get_materials(&self) -> &[Stack<Material>] {
+        &self.materials
+    }
+
+    /// Put a material in the first stack of its type with room, Or in a new stack.
+    pub fn put_material(&mut self, material: Material) -> anyhow::Result<()> {
+        self.put_loot(Loot::Material(material))
+    }
+
+    /// Take one material out of the stack at the given index.
+    pub fn take_material(&mut self, index: usize) -> Option<Material> {
+        take_one(&mut self.materials, index)
+    }
+
+    /// Put a looted item in its place in the inventory, Nothing is changed if it doesn't fit.
+    pub fn put_loot(&mut self, loot: Loot) -> anyhow::Result<()> {
+        self.check_fit(&loot)?;
+        self.insert(loot);
+        Ok(())
+    }
+
+    /// Move the whole stack of the given type at the given index into another inventory.
+    ///
+    /// Nothing moves if the stack doesn't fit in the other inventory.
+    ///
+    /// ## Returns
+    /// [`u32`] The amount of items moved.
+    pub fn transfer(
+        &mut self,
+        to: &mut Inventory,
+        item_type: ItemType,
+        index: usize,
+    ) -> anyhow::Result<u32> {
+        let quantity = self
+            .entries()
+            .find(|e| e.item_type() == item_type && e.index() == index)
+            .map(|e| e.quantity())
+            .ok_or_else(|| anyhow::anyhow!("No {:?} at inventory index {}", item_type, index))?;
+        let items: Vec<_> = (0..quantity)
+            .filter_map(|_| self.get(item_type, index))
+            .collect();
+        to.check_fit_all(&items)?;
+
+        for _ in 0..quantity {
+            if let Some(loot) = self.take(item_type, index) {
+                to.insert(loot);
+            }
+        }
+        Ok(quantity)
+    }
+
+    /// Split an amount off the stack of the given type at the given index into a new stack.
+    ///
+    /// Fails if the amount isn't less than the stack's quantity or there's no room for another stack.
+    pub fn split_stack(
+        &mut self,
+        item_type: ItemType,
+        index: usize,
+        amount: u32,
+    ) -> anyhow::Result<()> {
+        if !matches!(item_type, ItemType::Consumable | ItemType::Material) {
+            return Err(anyhow::anyhow!("{:?} items don't stack", item_type));
+        }
+        let size = self
+            .get(item_type, index)
+            .ok_or_else(|| anyhow::anyhow!("No stack at inventory index {}", index))?
+            .item()
+            .size();
+        check_split(self.stack_quantity(item_type, index), amount)?;
+        self.check_capacity(0, &[size])?;
+        match item_type {
+            ItemType::Consumable => split(&mut self.consumables, index, amount),
+            _ => split(&mut self.materials, index, amount),
+        }
+        Ok(())
+    }
+
+    /// Move as many items as fit from one stack of the given type into another stack of the same item.
+    ///
+    /// Emptied stacks are removed.
+    ///
+    /// ## Returns
+    /// [`u32`] The amount of items moved.
+    pub fn merge_stacks(
+        &mut self,
+        item_type: ItemType,
+        from: usize,
+        into: usize,
+    ) -> anyhow::Result<u32> {
+        match item_type {
+            ItemType::Consumable => merge(&mut self.consumables, from, into, |a, b| {
+                a.consumable_type() == b.consumable_type()
+            }),
+            ItemType::Material => merge(&mut self.materials, from, into, |a, b| {
+                a.material_type() == b.material_type()
+            }),
+            _ => Err(anyhow::anyhow!("{:?} items don't stack", item_type)),
+        }
+    }
+
+    /// Returns a copy of the item of the given type at the given index if any.
+    ///
+    /// For stacks this is one item of the stack.
+    pub fn get(&self, item_type: ItemType, index: usize) -> Option<Loot> {
+        match item_type {
+            ItemType::Weapon => self.weapon(index).copied().map(Loot::Weapon),
+            ItemType::Consumable => self
+                .consumables
+                .get(index)
+                .map(|s| Loot::Consumable(s.item)),
+            ItemType::Material => self.materials.get(index).map(|s| Loot::Material(s.item)),
+            ItemType::Container | ItemType::Armor => None,
+        }
+    }
+
+    /// Take the item of the given type at the given index out of the inventory.
+    ///
+    /// For stacks this takes one item of the stack.
+    pub fn take(&mut self, item_type: ItemType, index: usize) -> Option<Loot> {
+        match item_type {
+            ItemType::Weapon => self.take_weapon(index).map(Loot::Weapon),
+            ItemType::Consumable => self.take_consumable(index).map(Loot::Consumable),
+            ItemType::Material => self.take_material(index).map(Loot::Material),
+            ItemType::Container | ItemType::Armor => None,
+        }
+    }
+
+    /// The number of items in this inventory that are the same as the given item, See [`Loot::is_same`].
+    pub fn count_loot(&self, loot: &Loot) -> usize {
+        let count = match loot {
+            Loot::Weapon(weapon) => quantity(&self.weapons, |w| w == weapon),
+            Loot::Consumable(consumable) => quantity(&self.consumables, |c| {
+                c.consumable_type() == consumable.consumable_type()
+            }),
+            Loot::Material(material) => quantity(&self.materials, |m| {
+                m.material_type() == material.material_type()
+            }),
+        };
+        count as usize
+    }
+
+    /// Take an item that's the same as the given item out of the inventory, See [`Loot::is_same`].
+    ///
+    /// Stackable items are taken from the last stack of their type first.
+    pub fn take_loot(&mut self, loot: &Loot) -> Option<Loot> {
+        match loot {
+            Loot::Weapon(weapon) => {
+                let index = self.weapons.iter().position(|s| s.item == *weapon)?;
+                self.take_weapon(index).map(Loot::Weapon)
+            }
+            Loot::Consumable(consumable) => {
+                let index = self
+                    .consumables
+                    .iter()
+                    .rposition(|s| s.item.consumable_type() == consumable.consumable_type())?;
+                self.take_consumable(index).map(Loot::Consumable)
+            }
+            Loot::Material(material) => {
+                let index = self
+                    .materials
+                    .iter()
+                    .rposition(|s| s.item.material_type() == material.material_type())?;
+                self.take_material(index).map(Loot::Material)
+            }
+        }
+    }
+
+    /// The number of items of the given type in this inventory, Counting every item of a stack.
+    pub fn count(&self, item: LootItem) -> u32 {
+        match item {
+            LootItem::Weapon(weapon_type) => {
+                quantity(&self.weapons, |w| w.weapon_type() == weapon_type)
+            }
+            LootItem::Consumable(consumable_type) => quantity(&self.consumables, |c| {
+                c.consumable_type() == consumable_type
+            }),
+            LootItem::Material(material_type) => {
+                quantity(&self.materials, |m| m.material_type() == material_type)
+            }
+        }
+    }
+
+    /// Remove an amount of items of the given type, Nothing is removed if there aren't enough.
+    pub fn remove(&mut self, item: LootItem, amount: u32) -> anyhow::Result<()> {
+        if self.count(item) < amount {
+            return Err(anyhow::anyhow!(
+                "Not enough {}, {} of {} needed",
+                item.name(),
+                self.count(item),
+                amount
+            ));
+        }
+
+        match item {
+            LootItem::Weapon(weapon_type) => remove(&mut self.weapons, amount, |w| {
+                w.weapon_type() == weapon_type
+            }),
+            LootItem::Consumable(consumable_type) => remove(&mut self.consumables, amount, |c| {
+                c.consumable_type() == consumable_type
+            }),
+            LootItem::Material(material_type) => remove(&mut self.materials, amount, |m| {
+                m.material_type() == material_type
+            }),
+        }
+        Ok(())
+    }
+
+    /// Put an item without checking the capacity.
+    fn insert(&mut self, loot: Loot) {
+        match loot {
+            Loot::Weapon(weapon) => {
+                stack(&mut self.weapons, weapon, &mut self.acquired, |_, _| false)
+            }
+            Loot::Consumable(consumable) => stack(
+                &mut self.consumables,
+                consumable,
+                &mut self.acquired,
+                |a, b| a.consumable_type() == b.consumable_type(),
+            ),
+            Loot::Material(material) => {
+                stack(&mut self.materials, material, &mut self.acquired, |a, b| {
+                    a.material_type() == b.material_type()
+                })
+            }
+        }
+    }
+
+    /// The room left in the stacks the item would go in, `0` for items that don't stack.
+    fn stack_room(&self, loot: &Loot) -> u32 {
+        fn room<T: Item>(stacks: &[Stack<T>], same: impl Fn(&T) -> bool) -> u32 {
+            stacks
+                .iter()
+                .filter(|s| same(&s.item))
+                .fold(0u32, |room, s| {
+                    room.saturating_add(s.item.max_stack().saturating_sub(s.quantity))
+                })
+        }
+
+        match loot {
+            Loot::Weapon(_) => 0,
+            Loot::Consumable(consumable) => room(&self.consumables, |c| {
+                c.consumable_type() == consumable.consumable_type()
+            }),
+            Loot::Material(material) => room(&self.materials, |m| {
+                m.material_type() == material.material_type()
+            }),
+        }
+    }
+
+    /// The quantity of the stack of the given type at the given index, `0` if there's none.
+    fn stack_quantity(&self, item_type: ItemType, index: usize) -> u32 {
+        self.entries()
+            .find(|e| e.item_type() == item_type && e.index() == index)
+            .map_or(0, |e| e.quantity())
+    }
+
+    /// Check the capacity can take more weight and new stacks of the given sizes.
+    ///
+    /// Grid items are auto arranged so new stacks are checked against a new arrangement.
+    fn check_capacity(&self, weight: u32, sizes: &[(u32, u32)]) -> anyhow::Result<()> {
+        if self.len() + sizes.len() > self.max_size as usize {
             return Err(anyhow::anyhow!("Inventory is full"));
         }
-        self.weapons.push(weapon);
+        if let Some(max_weight) = self.max_weight {
+            let weight = self.weight().saturating_add(weight);
+            if weight > max_weight {
+                return Err(anyhow::anyhow!(
+                    "Inventory can't hold {} weight, {} is the max",
+                    weight,
+                    max_weight
+                ));
+            }
+        }
+        if let (Some(grid), false) = (self.grid, sizes.is_empty()) {
+            let all: Vec<_> = self
+                .items()
+                .map(|(item, _)| item.size())
+                .chain(sizes.iter().copied())
+                .collect();
+            if arrange(grid, &all).is_none() {
+                return Err(anyhow::anyhow!("Inventory grid has no room"));
+            }
+        }
         Ok(())
     }
 }
+
+/// Put an item in the first stack of the same item with room, Or in a new stack acquired next.
+fn stack<T: Item + Copy>(
+    stacks: &mut Vec<Stack<T>>,
+    item: T,
+    acquired: &mut u64,
+    same: impl Fn(&T, &T) -> bool,
+) {
+    match stacks
+        .iter_mut()
+        .find(|s| same(&s.item, &item) && !s.is_full())
+    {
+        Some(stack) => stack.quantity += 1,
+        None => {
+            stacks.push(Stack {
+                item,
+                quantity: 1,
+                acquired: *acquired,
+            });
+            *acquired += 1;
+        }
+    }
+}
+
+fn take_one<T: Copy>(stacks: &mut Vec<Stack<T>>, index: usize) -> Option<T> {
+    let stack = stacks.get_mut(index)?;
+    let item = stack.item;
+    stack.quantity -= 1;
+    if stack.quantity == 0 {
+        stacks.remove(index);
+    }
+    Some(item)
+}
+
+fn quantity<T>(stacks: &[Stack<T>], matches: impl Fn(&T) -> bool) -> u32 {
+    stacks
+        .iter()
+        .filter(|s| matches(&s.item))
+        .fold(0u32, |count, s| count.saturating_add(s.quantity))
+}
+
+/// Remove an amount of items from the last matching stacks first.
+fn remove<T>(stacks: &mut Vec<Stack<T>>, amount: u32, matches: impl Fn(&T) -> bool) {
+    let mut left = amount;
+    for stack in stacks.iter_mut().rev().filter(|s| matches(&s.item)) {
+        let taken = left.min(stack.quantity);
+        stack.quantity -= taken;
+        left -= taken;
+    }
+    stacks.retain(|s| s.quantity > 0);
+}
+
+fn check_split(quantity: u32, amount: u32) -> anyhow::Result<()> {
+    if amount == 0 || amount >= quantity {
+        return Err(anyhow::anyhow!(
+            "Can't split {} off a stack of {}",
+            amount,
+            quantity
+        ));
+    }
+    Ok(())
+}
+
+/// Split an amount off the stack at the given index, See [`check_split`].
+fn split<T: Copy>(stacks: &mut Vec<Stack<T>>, index: usize, amount: u32) {
+    let stack = &mut stacks[index];
+    stack.quantity -= amount;
+    let split = Stack {
+        quantity: amount,
+        ..*stack
+    };
+    stacks.push(split);
+}
+
+fn merge<T: Item + Copy>(
+    stacks: &mut Vec<Stack<T>>,
+    from: usize,
+    into: usize,
+    same: impl Fn(&T, &T) -> bool,
+) -> anyhow::Result<u32> {
+    if from == into || from >= stacks.len() || into >= stacks.len() {
+        return Err(anyhow::anyhow!(
+            "Can't merge the stack at index {} into index {}",
+            from,
+            into
+        ));
+    }
+    if !same(&stacks[from].item, &stacks[into].item) {
+        return Err(anyhow::anyhow!(
+            "Can't merge {} into {}",
+            stacks[from].item.name(),
+            stacks[into].item.name()
+        ));
+    }
+    let room = stacks[into]
+        .item
+        .max_stack()
+        .saturating_sub(stacks[into].quantity);
+    let moved = room.min(stacks[from].quantity);
+    stacks[into].quantity += moved;
+    stacks[from].quantity -= moved;
+    if stacks[from].quantity == 0 {
+        stacks.remove(from);
+    }
+    Ok(moved)
+}
+
+/// Arrange items of the given sizes in the grid, Largest items first, Each at the first free spot
+/// scanning rows top to bottom.
+///
+/// ## Returns
+/// The top left position of each item in the given order, `None` if they don't all fit.
+fn arrange(grid: Grid, sizes: &[(u32, u32)]) -> Option<Vec<(u32, u32)>> {
+    let (width, height) = (grid.width as usize, grid.height as usize);
+    let mut taken = vec![false; width * height];
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|index| std::cmp::Reverse(sizes[*index].0 * sizes[*index].1));
+
+    let mut positions = vec![(0, 0); sizes.len()];
+    for index in order {
+        let (w, h) = (sizes[index].0 as usize, sizes[index].1 as usize);
+        if w > width || h > height {
+            return None;
+        }
+        let free = |x: usize, y: usize| {
+            (y..y + h).all(|row| (x..x + w).all(|column| !taken[row * width + column]))
+        };
+        let (x, y) = (0..=height - h)
+            .flat_map(|y| (0..=width - w).map(move |x| (x, y)))
+            .find(|(x, y)| free(*x, *y))?;
+        for row in y..y + h {
+            for column in x..x + w {
+                taken[row * width + column] = true;
+            }
+        }
+        positions[index] = (x as u32, y as u32);
+    }
+    Some(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{consumable::ConsumableType, material::MaterialType, weapon::WeaponType};
+
+    #[test]
+    fn test_capacity() {
+        let mut inventory = Inventory::new();
+        assert!(inventory.is_empty());
+        for _ in 0..50 {
+            inventory
+                .put_weapon(Weapon::new(WeaponType::Daggers))
+                .unwrap();
+        }
+        assert!(inventory.is_full());
+        assert!(!inventory.is_empty());
+        assert!(inventory
+            .put_material(Material::new(MaterialType::Scrap))
+            .is_err());
+    }
+
+    #[test]
+    fn test_weight() {
+        let mut inventory = Inventory::new().with_max_weight(20);
+        assert_eq!(inventory.encumbrance(), Encumbrance::Light);
+
+        inventory.put_weapon(Weapon::new(WeaponType::Mace)).unwrap();
+        inventory
+            .put_weapon(Weapon::new(WeaponType::Daggers))
+            .unwrap();
+        assert_eq!(inventory.weight(), 10);
+        assert_eq!(inventory.encumbrance(), Encumbrance::Burdened);
+
+        inventory.put_weapon(Weapon::new(WeaponType::Rod)).unwrap();
+        inventory
+            .put_weapon(Weapon::new(WeaponType::Daggers))
+            .unwrap();
+        assert_eq!(inventory.encumbrance(), Encumbrance::Overloaded);
+        assert_eq!(inventory.encumbrance().movement_speed(), 50);
+
+        let mace = Weapon::new(WeaponType::Mace);
+        assert!(!inventory.can_fit(&Loot::Weapon(mace)));
+        assert!(inventory.put_weapon(mace).is_err());
+        assert_eq!(inventory.len(), 4);
+    }
+
+    #[test]
+    fn test_grid() {
+        let mut inventory = Inventory::new().with_grid(4, 4);
+        // Two handed bows take 2x4, One handed maces take 1x3.
+        inventory.put_weapon(Weapon::new(WeaponType::Bow)).unwrap();
+        inventory.put_weapon(Weapon::new(WeaponType::Mace)).unwrap();
+        inventory.put_weapon(Weapon::new(WeaponType::Rod)).unwrap();
+        assert!(!inventory.can_fit(&Loot::Weapon(Weapon::new(WeaponType::Daggers))));
+
+        let potion = Consumable::new(ConsumableType::HealthPotion);
+        let scrap = Loot::Material(Material::new(MaterialType::Scrap));
+        let leather = Loot::Material(Material::new(MaterialType::Leather));
+        assert!(inventory
+            .check_fit_all(&[Loot::Consumable(potion), scrap, scrap])
+            .is_ok());
+        assert!(inventory
+            .check_fit_all(&[Loot::Consumable(potion), scrap, leather])
+            .is_err());
+
+        inventory.put_consumable(potion).unwrap();
+        let layout = inventory.layout().unwrap();
+        assert_eq!(layout[0], (0, 0));
+        assert!(layout.iter().all(|(x, y)| *x < 4 && *y < 4));
+        assert!(Inventory::new().layout().is_none());
+    }
+
+    #[test]
+    fn test_stacks() {
+        let mut inventory = Inventory::with_capacity(2);
+        for _ in 0..12 {
+            inventory
+                .put_consumable(Consumable::new(ConsumableType::HealthPotion))
+                .unwrap();
+        }
+        // Potions stack by 10, The capacity counts stacks.
+        assert_eq!(inventory.len(), 2);
+        assert!(inventory.is_full());
+        assert_eq!(inventory.get_consumables()[0].quantity(), 10);
+        assert_eq!(inventory.get_consumables()[1].quantity(), 2);
+        assert_eq!(
+            inventory.count(LootItem::Consumable(ConsumableType::HealthPotion)),
+            12
+        );
+        assert_eq!(inventory.weight(), 12);
+        assert!(inventory
+            .put_consumable(Consumable::new(ConsumableType::Antidote))
+            .is_err());
+
+        assert!(inventory.take_consumable(1).is_some());
+        assert!(inventory.take_consumable(1).is_some());
+        assert_eq!(inventory.len(), 1);
+        inventory
+            .remove(LootItem::Consumable(ConsumableType::HealthPotion), 4)
+            .unwrap();
+        assert_eq!(inventory.get_consumables()[0].quantity(), 6);
+    }
+
+    #[test]
+    fn test_fit_stacks() {
+        let mut inventory = Inventory::with_capacity(2).with_max_weight(30);
+        let potion = Loot::Consumable(Consumable::new(ConsumableType::HealthPotion));
+        for _ in 0..9 {
+            inventory.put_loot(potion).unwrap();
+        }
+        inventory
+            .put_weapon(Weapon::new(WeaponType::Daggers))
+            .unwrap();
+
+        // The first potion fills the stack, The second needs a new one.
+        assert!(inventory.check_fit(&potion).is_ok());
+        assert_eq!(
+            inventory
+                .check_fit_all(&[potion, potion])
+                .unwrap_err()
+                .to_string(),
+            "Inventory is full"
+        );
+
+        let mut other = Inventory::new();
+        inventory
+            .transfer(&mut other, ItemType::Consumable, 0)
+            .unwrap();
+        assert_eq!(other.get_consumables()[0].quantity(), 9);
+        assert_eq!(inventory.len(), 1);
+    }
+
+    #[test]
+    fn test_split_merge() {
+        let mut inventory = Inventory::with_capacity(2);
+        for _ in 0..5 {
+            inventory
+                .put_material(Material::new(MaterialType::Scrap))
+                .unwrap();
+        }
+        assert!(inventory.split_stack(ItemType::Material, 0, 5).is_err());
+        inventory.split_stack(ItemType::Material, 0, 2).unwrap();
+        assert_eq!(inventory.get_materials()[0].quantity(), 3);
+        assert_eq!(inventory.get_materials()[1].quantity(), 2);
+        // No room for a third stack.
+        assert!(inventory.split_stack(ItemType::Material, 0, 1).is_err());
+        assert!(inventory.split_stack(ItemType::Weapon, 0, 1).is_err());
+
+        assert_eq!(inventory.merge_stacks(ItemType::Material, 1, 0).unwrap(), 2);
+        assert_eq!(inventory.len(), 1);
+        assert_eq!(inventory.get_materials()[0].quantity(), 5);
+
+        inventory
+            .put_material(Material::new(MaterialType::Leather))
+            .unwrap();
+        assert!(inventory.merge_stacks(ItemType::Material, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_stack_serde() {
+        let potion = Consumable::new(ConsumableType::Elixir);
+        let old = format!(
+            "{{\"weapons\":[],\"consumables\":[{}],\"max_size\":50}}",
+            serde_json::to_string(&potion).unwrap()
+        );
+        let inventory: Inventory = serde_json::from_str(&old).unwrap();
+        assert_eq!(inventory.get_consumables(), &[Stack::new(potion)]);
+
+        let mut inventory = Inventory::new();
+        inventory.put_consumable(potion).unwrap();
+        inventory.put_consumable(potion).unwrap();
+        let json = serde_json::to_string(&inventory).unwrap();
+        let loaded: Inventory = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.get_consumables()[0].quantity(), 2);
+    }
+}
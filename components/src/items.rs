@@ -28,18 +28,49 @@
 // OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::material::MaterialType;
 use rand::{
     distributions::{Distribution, Standard},
-    Rng,
+    random, Rng,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum ItemRarity {
     Rare,
     Legendry,
     Exotic,
 }
 
+impl ItemRarity {
+    /// The damage multiplier of this rarity as a percentage.
+    pub fn damage_multiplier(&self) -> u32 {
+        match self {
+            ItemRarity::Rare => 100,
+            ItemRarity::Legendry => 125,
+            ItemRarity::Exotic => 150,
+        }
+    }
+
+    /// The value multiplier of items of this rarity.
+    pub fn value_multiplier(&self) -> u64 {
+        match self {
+            ItemRarity::Rare => 1,
+            ItemRarity::Legendry => 3,
+            ItemRarity::Exotic => 8,
+        }
+    }
+
+    /// The rarity this rarity upgrades to, `None` for [`ItemRarity::Exotic`].
+    pub fn next(&self) -> Option<ItemRarity> {
+        match self {
+            ItemRarity::Rare => Some(ItemRarity::Legendry),
+            ItemRarity::Legendry => Some(ItemRarity::Exotic),
+            ItemRarity::Exotic => None,
+        }
+    }
+}
+
 impl Distribution<ItemRarity> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ItemRarity {
         match rng.gen_range(0..=2) {
@@ -50,11 +81,12 @@ impl Distribution<ItemRarity> for Standard {
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub enum ItemType {
     Weapon,
     Container,
     Consumable,
+    Material,
     Armor,
 }
 
@@ -64,6 +96,7 @@ impl Generator for ItemType {
             ItemType::Weapon => todo!(),
             ItemType::Container => todo!(),
             ItemType::Consumable => todo!(),
+            ItemType::Material => MaterialType::all().iter().map(MetaData::name).collect(),
             ItemType::Armor => todo!(),
         }
     }
@@ -74,6 +107,7 @@ impl Generator for ItemType {
             ItemType::Weapon => todo!(),
             ItemType::Container => todo!(),
             ItemType::Consumable => todo!(),
+            ItemType::Material => random::<MaterialType>().name(),
             ItemType::Armor => todo!(),
         }
     }
@@ -96,4 +130,55 @@ pub trait Item {
     fn id(&self) -> u8;
     fn item_type(&self) -> ItemType;
     fn rarity(&self) -> ItemRarity;
+    /// The gold this item is worth, Vendors price items from it.
+    /// Defaults to `10` times the rarity's value multiplier.
+    fn value(&self) -> u64 {
+        10 * self.rarity().value_multiplier()
+    }
+
+    /// How many of this item stack in one inventory slot. Defaults to `1`.
+    fn max_stack(&self) -> u32 {
+        1
+    }
+
+    /// How heavy this item is. Defaults to `1`.
+    fn weight(&self) -> u32 {
+        1
+    }
+
+    /// The width and height this item takes in an inventory grid. Defaults to `(1, 1)`.
+    fn size(&self) -> (u32, u32) {
+        (1, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Trinket;
+
+    impl Item for Trinket {
+        fn name(&self) -> &'static str {
+            "Trinket"
+        }
+
+        fn id(&self) -> u8 {
+            0
+        }
+
+        fn item_type(&self) -> ItemType {
+            ItemType::Container
+        }
+
+        fn rarity(&self) -> ItemRarity {
+            ItemRarity::Legendry
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        assert_eq!(Trinket.value(), 30);
+        assert_eq!(Trinket.max_stack(), 1);
+    }
 }
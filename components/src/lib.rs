@@ -29,15 +29,41 @@
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod armor;
+pub mod consumable;
+pub mod crafting;
+pub mod durability;
+pub mod equipment;
+pub mod events;
 pub mod health;
 pub mod inventory;
 pub mod items;
+pub mod loot;
+pub mod mana;
+pub mod material;
+pub mod query;
+pub mod stash;
+pub mod status;
+pub mod vendor;
+pub mod wallet;
 pub mod weapon;
 
 pub use self::{
     armor::ArmorType,
+    consumable::{Consumable, ConsumableType},
+    crafting::Recipe,
+    durability::Durability,
+    equipment::{EquipSlot, Equipment},
+    events::{EventBus, EventKind, GameEvent, SubscriberId},
     health::Health,
-    inventory::Inventory,
+    inventory::{Encumbrance, Grid, Inventory, Stack},
     items::{Generator, Item, ItemRarity, ItemType, MetaData},
-    weapon::{Weapon, WeaponAmmoType, WeaponType},
+    loot::{Loot, LootDrop, LootItem, LootTable},
+    mana::{Mana, ResourceType},
+    material::{Material, MaterialType},
+    query::{Entry, Filter, Query, SortBy},
+    stash::{Stash, StashTab},
+    status::{StatusEffect, StatusEffects, StatusKind},
+    vendor::{PriceModifier, Vendor},
+    wallet::Wallet,
+    weapon::{Handedness, Weapon, WeaponAmmoType, WeaponType},
 };
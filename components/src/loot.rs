@@ -0,0 +1,225 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    consumable::{Consumable, ConsumableType},
+    items::{Item, MetaData},
+    material::{Material, MaterialType},
+    weapon::{Weapon, WeaponType},
+};
+use rand::Rng;
+
+/// The type of item a loot drop generates.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LootItem {
+    Weapon(WeaponType),
+    Consumable(ConsumableType),
+    Material(MaterialType),
+}
+
+impl MetaData for LootItem {
+    fn name(&self) -> &'static str {
+        match self {
+            LootItem::Weapon(weapon_type) => weapon_type.name(),
+            LootItem::Consumable(consumable_type) => consumable_type.name(),
+            LootItem::Material(material_type) => material_type.name(),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            LootItem::Weapon(weapon_type) => weapon_type.description(),
+            LootItem::Consumable(consumable_type) => consumable_type.description(),
+            LootItem::Material(material_type) => material_type.description(),
+        }
+    }
+}
+
+impl LootItem {
+    /// Generate an item of this type from the given random generator.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Loot {
+        match *self {
+            LootItem::Weapon(weapon_type) => Loot::Weapon(Weapon::generate(weapon_type, rng)),
+            LootItem::Consumable(consumable_type) => {
+                Loot::Consumable(Consumable::generate(consumable_type, rng))
+            }
+            LootItem::Material(material_type) => {
+                Loot::Material(Material::generate(material_type, rng))
+            }
+        }
+    }
+}
+
+/// A generated item dropped from a loot table.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Loot {
+    Weapon(Weapon),
+    Consumable(Consumable),
+    Material(Material),
+}
+
+impl Loot {
+    /// Returns the looted item.
+    pub fn item(&self) -> &dyn Item {
+        match self {
+            Loot::Weapon(weapon) => weapon,
+            Loot::Consumable(consumable) => consumable,
+            Loot::Material(material) => material,
+        }
+    }
+
+    /// Whether the other item is the same item.
+    ///
+    /// Weapons are only the same as themselves, Stackable items are the same as every item of their type
+    /// since stacks don't keep the ids of their items.
+    pub fn is_same(&self, other: &Loot) -> bool {
+        match (self, other) {
+            (Loot::Weapon(a), Loot::Weapon(b)) => a == b,
+            (Loot::Consumable(a), Loot::Consumable(b)) => {
+                a.consumable_type() == b.consumable_type()
+            }
+            (Loot::Material(a), Loot::Material(b)) => a.material_type() == b.material_type(),
+            _ => false,
+        }
+    }
+}
+
+/// A single possible drop of a loot table.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct LootDrop {
+    pub item: LootItem,
+    /// The chance percentage of this drop.
+    pub chance: u32,
+}
+
+impl LootDrop {
+    pub const fn new(item: LootItem, chance: u32) -> LootDrop {
+        LootDrop { item, chance }
+    }
+}
+
+/// A table of possible item drops, Referenced by its id.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct LootTable {
+    id: &'static str,
+    drops: &'static [LootDrop],
+}
+
+impl LootTable {
+    pub const fn new(id: &'static str, drops: &'static [LootDrop]) -> LootTable {
+        LootTable { id, drops }
+    }
+
+    /// Returns the builtin loot table with the given id if any.
+    pub fn get(id: &str) -> Option<&'static LootTable> {
+        LOOT_TABLES.iter().find(|table| table.id == id)
+    }
+
+    /// Returns all builtin loot tables.
+    pub fn all() -> &'static [LootTable] {
+        &LOOT_TABLES
+    }
+
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn drops(&self) -> &'static [LootDrop] {
+        self.drops
+    }
+
+    /// Roll every drop of this table, Generating the items that dropped.
+    pub fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<Loot> {
+        let mut loot = Vec::new();
+        for drop in self.drops {
+            if rng.gen_range(0..100) >= drop.chance {
+                continue;
+            }
+            loot.push(drop.item.generate(rng));
+        }
+        loot
+    }
+}
+
+static LOOT_TABLES: [LootTable; 4] = [
+    LootTable::new(
+        "undead",
+        &[
+            LootDrop::new(LootItem::Consumable(ConsumableType::Antidote), 40),
+            LootDrop::new(LootItem::Weapon(WeaponType::Mace), 10),
+            LootDrop::new(LootItem::Weapon(WeaponType::Bow), 10),
+            LootDrop::new(LootItem::Material(MaterialType::Scrap), 30),
+        ],
+    ),
+    LootTable::new(
+        "beast",
+        &[
+            LootDrop::new(LootItem::Consumable(ConsumableType::HealthPotion), 50),
+            LootDrop::new(LootItem::Weapon(WeaponType::Claw), 15),
+            LootDrop::new(LootItem::Material(MaterialType::Leather), 40),
+        ],
+    ),
+    LootTable::new(
+        "demon",
+        &[
+            LootDrop::new(LootItem::Consumable(ConsumableType::Elixir), 25),
+            LootDrop::new(LootItem::Weapon(WeaponType::Rod), 15),
+            LootDrop::new(LootItem::Weapon(WeaponType::Daggers), 15),
+            LootDrop::new(LootItem::Material(MaterialType::Essence), 30),
+        ],
+    ),
+    LootTable::new(
+        "elemental",
+        &[
+            LootDrop::new(LootItem::Consumable(ConsumableType::Elixir), 40),
+            LootDrop::new(LootItem::Weapon(WeaponType::Rod), 25),
+            LootDrop::new(LootItem::Material(MaterialType::Crystal), 30),
+        ],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_roll() {
+        let table = LootTable::get("beast").unwrap();
+        let roll = |seed| table.roll(&mut StdRng::seed_from_u64(seed));
+
+        for seed in 0..50 {
+            let loot = roll(seed);
+            assert!(loot.len() <= table.drops().len());
+            assert_eq!(loot, roll(seed));
+        }
+        assert!(LootTable::get("dragon").is_none());
+    }
+}
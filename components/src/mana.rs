@@ -0,0 +1,218 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::items::MetaData;
+use serde::{Deserialize, Serialize};
+
+/// ## The resources abilities are paid with.
+/// - [`ResourceType::Mana`]
+///     - Starts full and is restored by `mp5`.
+/// - [`ResourceType::Rage`]
+///     - Starts empty and builds up by hitting and getting hit.
+/// - [`ResourceType::Blood`]
+///     - Starts empty and is drawn from enemies hit.
+/// - [`ResourceType::Energy`]
+///     - Starts full and quickly restores itself every turn.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum ResourceType {
+    #[default]
+    Mana,
+    Rage,
+    Blood,
+    Energy,
+}
+
+impl ResourceType {
+    /// Whether a pool of this resource starts full or empty.
+    pub fn starts_full(&self) -> bool {
+        matches!(self, ResourceType::Mana | ResourceType::Energy)
+    }
+
+    /// The amount gained for hitting an enemy.
+    pub fn on_hit(&self) -> u32 {
+        match self {
+            ResourceType::Rage => 15,
+            ResourceType::Blood => 10,
+            _ => 0,
+        }
+    }
+
+    /// The amount gained for getting hit.
+    pub fn on_hurt(&self) -> u32 {
+        match self {
+            ResourceType::Rage => 10,
+            _ => 0,
+        }
+    }
+
+    /// The amount restored every turn.
+    pub fn per_turn(&self) -> u32 {
+        match self {
+            ResourceType::Energy => 20,
+            _ => 0,
+        }
+    }
+}
+
+impl MetaData for ResourceType {
+    fn name(&self) -> &'static str {
+        match self {
+            ResourceType::Mana => "Mana",
+            ResourceType::Rage => "Rage",
+            ResourceType::Blood => "Blood",
+            ResourceType::Energy => "Energy",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ResourceType::Mana => "Arcane power that slowly flows back.",
+            ResourceType::Rage => "Fury that builds up in the heat of battle.",
+            ResourceType::Blood => "Life force stolen from the enemies.",
+            ResourceType::Energy => "Stamina that quickly comes back.",
+        }
+    }
+}
+
+/// Core mana component used to cast abilities.
+///
+/// The pool can hold any [`ResourceType`], Mana being the default.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Mana {
+    current: u32,
+    max: u32,
+    #[serde(default)]
+    resource: ResourceType,
+}
+
+impl Default for Mana {
+    fn default() -> Self {
+        Mana::new(None)
+    }
+}
+
+impl std::fmt::Display for Mana {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({}/{})", self.resource.name(), self.current, self.max)
+    }
+}
+
+impl Mana {
+    /// Creates a new full mana pool.
+    pub fn new(max: Option<u32>) -> Mana {
+        Mana::with_resource(ResourceType::Mana, max)
+    }
+
+    /// Creates a new pool of the given resource, Full or empty depending on the resource.
+    pub fn with_resource(resource: ResourceType, max: Option<u32>) -> Mana {
+        // Default max is 100
+        let max = max.unwrap_or(100);
+        Self {
+            current: if resource.starts_full() { max } else { 0 },
+            max,
+            resource,
+        }
+    }
+
+    /// The resource this pool holds.
+    pub fn resource(&self) -> ResourceType {
+        self.resource
+    }
+
+    /// Returns the current mana.
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    /// Returns the max mana.
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    /// Whether there's enough mana to spend the given amount.
+    pub fn can_spend(&self, amount: u32) -> bool {
+        self.current >= amount
+    }
+
+    /// Spend mana.
+    ///
+    /// ## Returns
+    /// [`u32`] The remaining mana.
+    pub fn spend(&mut self, amount: u32) -> anyhow::Result<u32> {
+        if !self.can_spend(amount) {
+            return Err(anyhow::anyhow!(
+                "Not enough {}, {} needed but only {} left",
+                self.resource.name(),
+                amount,
+                self.current
+            ));
+        }
+        self.current -= amount;
+        Ok(self.current)
+    }
+
+    /// Restore mana up to the max.
+    ///
+    /// ## Returns
+    /// [`u32`] The amount of mana actually restored.
+    pub fn restore(&mut self, amount: u32) -> u32 {
+        let before = self.current;
+        self.current = self.current.saturating_add(amount).min(self.max);
+        self.current - before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spend_and_restore() {
+        let mut mana = Mana::default();
+        assert_eq!(mana.spend(30).unwrap(), 70);
+        assert!(mana.spend(80).is_err());
+        assert_eq!(mana.current(), 70);
+
+        assert_eq!(mana.restore(50), 30);
+        assert_eq!(mana.current(), mana.max());
+    }
+
+    #[test]
+    fn test_resources() {
+        let rage = Mana::with_resource(ResourceType::Rage, Some(50));
+        assert_eq!(rage.current(), 0);
+        assert_eq!(rage.max(), 50);
+        assert!(!rage.can_spend(1));
+
+        let energy = Mana::with_resource(ResourceType::Energy, None);
+        assert_eq!(energy.current(), 100);
+        assert_eq!(energy.to_string(), "Energy(100/100)");
+    }
+}
@@ -0,0 +1,196 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::items::{Item, ItemRarity, ItemType, MetaData};
+use serde::{Deserialize, Serialize};
+
+use rand::{
+    distributions::{Distribution, Standard},
+    random, Rng,
+};
+use std::fmt;
+
+/// How many materials of a type stack.
+pub const MAX_STACK: u32 = 50;
+
+/// ## Crafting material types.
+/// - [`MaterialType::Scrap`]
+///     - Metal salvaged from heavy weapons.
+/// - [`MaterialType::Leather`]
+///     - Hide salvaged from bows and claws.
+/// - [`MaterialType::Crystal`]
+///     - Focus crystals salvaged from rods.
+/// - [`MaterialType::Essence`]
+///     - Magical essence found in rare items and brews.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum MaterialType {
+    Scrap,
+    Leather,
+    Crystal,
+    Essence,
+}
+
+impl Distribution<MaterialType> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> MaterialType {
+        match rng.gen_range(0..=3) {
+            0 => MaterialType::Scrap,
+            1 => MaterialType::Leather,
+            2 => MaterialType::Crystal,
+            _ => MaterialType::Essence,
+        }
+    }
+}
+
+impl MaterialType {
+    /// Every material type.
+    pub fn all() -> &'static [MaterialType] {
+        &[
+            MaterialType::Scrap,
+            MaterialType::Leather,
+            MaterialType::Crystal,
+            MaterialType::Essence,
+        ]
+    }
+
+    /// The gold materials of this type are worth.
+    pub fn value(&self) -> u64 {
+        match self {
+            MaterialType::Scrap | MaterialType::Leather => 5,
+            MaterialType::Essence => 12,
+            MaterialType::Crystal => 15,
+        }
+    }
+}
+
+impl MetaData for MaterialType {
+    fn name(&self) -> &'static str {
+        match self {
+            MaterialType::Scrap => "Scrap",
+            MaterialType::Leather => "Leather",
+            MaterialType::Crystal => "Crystal",
+            MaterialType::Essence => "Essence",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            MaterialType::Scrap => "Bent metal, Still good for forging.",
+            MaterialType::Leather => "Tough hide used for bows and grips.",
+            MaterialType::Crystal => "A focus crystal humming with power.",
+            MaterialType::Essence => "A glowing drop of pure magic.",
+        }
+    }
+}
+
+/// A crafting material item.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Material {
+    material_type: MaterialType,
+    id: u8,
+}
+
+impl fmt::Debug for Material {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Material(name: {}, hash: {}, description: {})",
+            self.name(),
+            self.id,
+            self.material_type.description(),
+        )
+    }
+}
+
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Material(name: {}, hash: {})", self.name(), self.id)
+    }
+}
+
+impl Item for Material {
+    fn rarity(&self) -> ItemRarity {
+        ItemRarity::Rare
+    }
+
+    fn name(&self) -> &'static str {
+        self.material_type.name()
+    }
+
+    fn id(&self) -> u8 {
+        self.id
+    }
+
+    fn item_type(&self) -> ItemType {
+        ItemType::Material
+    }
+
+    fn value(&self) -> u64 {
+        self.material_type.value()
+    }
+
+    fn max_stack(&self) -> u32 {
+        MAX_STACK
+    }
+}
+
+impl Material {
+    pub fn new(material_type: MaterialType) -> Material {
+        Material {
+            material_type,
+            id: random::<u8>(),
+        }
+    }
+
+    /// Generate a material rolling its id from the given random generator.
+    pub fn generate<R: Rng + ?Sized>(material_type: MaterialType, rng: &mut R) -> Material {
+        Material {
+            material_type,
+            id: rng.gen(),
+        }
+    }
+
+    /// Returns this material's type.
+    pub fn material_type(&self) -> MaterialType {
+        self.material_type
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::Generator;
+
+    #[test]
+    fn test_generate_name() {
+        let names = ItemType::Material.generate_name();
+        assert_eq!(names, vec!["Scrap", "Leather", "Crystal", "Essence"]);
+        assert!(names.contains(&ItemType::Material.auto_name()));
+    }
+}
@@ -0,0 +1,373 @@
+//! Borrowing queries over the items of an inventory.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    consumable::Consumable,
+    inventory::Inventory,
+    items::{Item, ItemRarity, ItemType},
+    material::Material,
+    weapon::{Weapon, WeaponType},
+};
+
+/// A borrowed item of an inventory.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ItemRef<'a> {
+    Weapon(&'a Weapon),
+    Consumable(&'a Consumable),
+    Material(&'a Material),
+}
+
+/// An item of an inventory and where it is, Borrowed without cloning.
+#[derive(Clone, Copy, Debug)]
+pub struct Entry<'a> {
+    item: ItemRef<'a>,
+    index: usize,
+    quantity: u32,
+    acquired: u64,
+}
+
+impl<'a> Entry<'a> {
+    pub(crate) fn new(item: ItemRef<'a>, index: usize, quantity: u32, acquired: u64) -> Entry<'a> {
+        Entry {
+            item,
+            index,
+            quantity,
+            acquired,
+        }
+    }
+
+    pub fn item(&self) -> &'a dyn Item {
+        match self.item {
+            ItemRef::Weapon(weapon) => weapon,
+            ItemRef::Consumable(consumable) => consumable,
+            ItemRef::Material(material) => material,
+        }
+    }
+
+    pub fn weapon(&self) -> Option<&'a Weapon> {
+        match self.item {
+            ItemRef::Weapon(weapon) => Some(weapon),
+            _ => None,
+        }
+    }
+
+    pub fn consumable(&self) -> Option<&'a Consumable> {
+        match self.item {
+            ItemRef::Consumable(consumable) => Some(consumable),
+            _ => None,
+        }
+    }
+
+    pub fn material(&self) -> Option<&'a Material> {
+        match self.item {
+            ItemRef::Material(material) => Some(material),
+            _ => None,
+        }
+    }
+
+    pub fn item_type(&self) -> ItemType {
+        self.item().item_type()
+    }
+
+    /// The index of this item among the items of its type, As [`Inventory::take`] expects.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The number of items in this entry's stack.
+    pub fn quantity(&self) -> u32 {
+        self.quantity
+    }
+
+    /// When this item was put in its inventory, See [`crate::Stack::acquired`].
+    pub fn acquired(&self) -> u64 {
+        self.acquired
+    }
+}
+
+/// ## Inventory query filters.
+/// - [`Filter::ItemType`]
+///     - Items of the type.
+/// - [`Filter::Rarity`]
+///     - Items of the rarity.
+/// - [`Filter::WeaponType`]
+///     - Weapons of the type.
+/// - [`Filter::Name`]
+///     - Items with the text in their name, Ignoring case.
+/// - [`Filter::MinDamage`]
+///     - Weapons dealing at least the damage.
+/// - [`Filter::MinValue`]
+///     - Items worth at least the gold each.
+/// - [`Filter::MaxWeight`]
+///     - Items weighing at most the weight each.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Filter {
+    ItemType(ItemType),
+    Rarity(ItemRarity),
+    WeaponType(WeaponType),
+    Name(String),
+    MinDamage(u32),
+    MinValue(u64),
+    MaxWeight(u32),
+}
+
+impl Filter {
+    /// Whether the entry passes this filter.
+    pub fn matches(&self, entry: &Entry<'_>) -> bool {
+        let item = entry.item();
+        match self {
+            Filter::ItemType(item_type) => item.item_type() == *item_type,
+            Filter::Rarity(rarity) => item.rarity() == *rarity,
+            Filter::WeaponType(weapon_type) => entry
+                .weapon()
+                .is_some_and(|w| w.weapon_type() == *weapon_type),
+            Filter::Name(name) => item.name().to_lowercase().contains(&name.to_lowercase()),
+            Filter::MinDamage(damage) => entry.weapon().is_some_and(|w| w.damage() >= *damage),
+            Filter::MinValue(value) => item.value() >= *value,
+            Filter::MaxWeight(weight) => item.weight() <= *weight,
+        }
+    }
+}
+
+/// ## Inventory sort orders, Lowest first.
+/// - [`SortBy::Rarity`]
+/// - [`SortBy::Type`]
+///     - By item type then name.
+/// - [`SortBy::Value`]
+///     - By the value of one item.
+/// - [`SortBy::Acquired`]
+///     - Oldest first.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SortBy {
+    Rarity,
+    Type,
+    Value,
+    Acquired,
+}
+
+/// A query over the items of an inventory.
+///
+/// Every filter must match, Queries borrow the inventory and never clone its items.
+///
+/// ```
+/// use components::{Consumable, ConsumableType, Inventory, ItemType, SortBy};
+///
+/// let mut inventory = Inventory::new();
+/// inventory.put_consumable(Consumable::new(ConsumableType::Elixir)).unwrap();
+/// inventory.put_consumable(Consumable::new(ConsumableType::HealthPotion)).unwrap();
+///
+/// let potions = inventory.query().item_type(ItemType::Consumable).name("potion");
+/// assert_eq!(potions.count(), 1);
+///
+/// let by_value = inventory.query().sorted(SortBy::Value);
+/// assert_eq!(by_value[0].item().name(), "Health Potion");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Query<'a> {
+    inventory: &'a Inventory,
+    filters: Vec<Filter>,
+}
+
+impl<'a> Query<'a> {
+    /// A query matching every item of the inventory.
+    pub fn new(inventory: &'a Inventory) -> Query<'a> {
+        Query {
+            inventory,
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Query<'a> {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn item_type(self, item_type: ItemType) -> Query<'a> {
+        self.filter(Filter::ItemType(item_type))
+    }
+
+    pub fn rarity(self, rarity: ItemRarity) -> Query<'a> {
+        self.filter(Filter::Rarity(rarity))
+    }
+
+    pub fn weapon_type(self, weapon_type: WeaponType) -> Query<'a> {
+        self.filter(Filter::WeaponType(weapon_type))
+    }
+
+    pub fn name(self, name: &str) -> Query<'a> {
+        self.filter(Filter::Name(name.to_owned()))
+    }
+
+    pub fn min_damage(self, damage: u32) -> Query<'a> {
+        self.filter(Filter::MinDamage(damage))
+    }
+
+    pub fn min_value(self, value: u64) -> Query<'a> {
+        self.filter(Filter::MinValue(value))
+    }
+
+    pub fn max_weight(self, weight: u32) -> Query<'a> {
+        self.filter(Filter::MaxWeight(weight))
+    }
+
+    /// Lazily iterate the matching items in the inventory's order, See [`Inventory::entries`].
+    pub fn iter(&self) -> impl Iterator<Item = Entry<'a>> + '_ {
+        self.inventory
+            .entries()
+            .filter(move |entry| self.filters.iter().all(|f| f.matches(entry)))
+    }
+
+    /// The number of matching items, A stack counts as one item.
+    pub fn count(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn first(&self) -> Option<Entry<'a>> {
+        self.iter().next()
+    }
+
+    /// The matching items sorted in the given order, Ties keep the inventory's order.
+    pub fn sorted(&self, by: SortBy) -> Vec<Entry<'a>> {
+        let mut entries: Vec<_> = self.iter().collect();
+        match by {
+            SortBy::Rarity => entries.sort_by_key(|e| e.item().rarity()),
+            SortBy::Type => entries.sort_by_key(|e| (e.item_type(), e.item().name())),
+            SortBy::Value => entries.sort_by_key(|e| e.item().value()),
+            SortBy::Acquired => entries.sort_by_key(|e| e.acquired()),
+        }
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{consumable::ConsumableType, material::MaterialType};
+
+    fn inventory() -> Inventory {
+        let mut inventory = Inventory::new();
+        inventory
+            .put_material(Material::new(MaterialType::Crystal))
+            .unwrap();
+        inventory
+            .put_weapon(Weapon::new(WeaponType::Mace).with_rarity(ItemRarity::Exotic))
+            .unwrap();
+        inventory
+            .put_consumable(Consumable::new(ConsumableType::Antidote))
+            .unwrap();
+        inventory
+            .put_weapon(Weapon::new(WeaponType::Daggers).with_rarity(ItemRarity::Rare))
+            .unwrap();
+        inventory
+            .put_material(Material::new(MaterialType::Crystal))
+            .unwrap();
+        inventory
+    }
+
+    #[test]
+    fn test_filters() {
+        let inventory = inventory();
+        assert_eq!(inventory.query().count(), 4);
+        assert_eq!(inventory.query().item_type(ItemType::Weapon).count(), 2);
+        assert_eq!(inventory.query().rarity(ItemRarity::Exotic).count(), 1);
+
+        let daggers = inventory
+            .query()
+            .weapon_type(WeaponType::Daggers)
+            .first()
+            .unwrap();
+        assert_eq!(daggers.index(), 1);
+        assert!(std::ptr::eq(
+            daggers.weapon().unwrap(),
+            inventory.weapon(1).unwrap()
+        ));
+
+        let crystals = inventory.query().name("CRYST").first().unwrap();
+        assert_eq!(crystals.quantity(), 2);
+        assert_eq!(
+            crystals.material().unwrap().material_type(),
+            MaterialType::Crystal
+        );
+
+        let mace = Weapon::new(WeaponType::Mace).with_rarity(ItemRarity::Exotic);
+        let strong = inventory.query().min_damage(mace.damage());
+        assert_eq!(strong.count(), 1);
+        assert!(inventory.query().max_weight(2).iter().all(|e| e
+            .weapon()
+            .is_none_or(|w| w.weapon_type() == WeaponType::Daggers)));
+        assert_eq!(
+            inventory
+                .query()
+                .item_type(ItemType::Consumable)
+                .min_value(u64::MAX)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_sorting() {
+        let inventory = inventory();
+        let acquired: Vec<_> = inventory
+            .query()
+            .sorted(SortBy::Acquired)
+            .iter()
+            .map(|e| e.item_type())
+            .collect();
+        assert_eq!(
+            acquired,
+            [
+                ItemType::Material,
+                ItemType::Weapon,
+                ItemType::Consumable,
+                ItemType::Weapon
+            ]
+        );
+
+        let rarest = inventory
+            .query()
+            .item_type(ItemType::Weapon)
+            .sorted(SortBy::Rarity);
+        assert_eq!(rarest[1].item().rarity(), ItemRarity::Exotic);
+
+        let values: Vec<_> = inventory
+            .query()
+            .sorted(SortBy::Value)
+            .iter()
+            .map(|e| e.item().value())
+            .collect();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+
+        let types = inventory.query().sorted(SortBy::Type);
+        assert_eq!(types[0].item_type(), ItemType::Weapon);
+        assert_eq!(types[3].item_type(), ItemType::Material);
+    }
+}
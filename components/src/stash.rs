@@ -0,0 +1,259 @@
+//! Account wide item storage shared by characters.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{inventory::Inventory, items::ItemType};
+use serde::{Deserialize, Serialize};
+
+/// A named tab of a stash with its own capacity.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StashTab {
+    name: String,
+    items: Inventory,
+}
+
+impl StashTab {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn items(&self) -> &Inventory {
+        &self.items
+    }
+}
+
+/// Account wide storage any character can deposit to and withdraw from, Split in tabs.
+///
+/// Transfers move whole stacks and are atomic, Nothing moves if the items don't fit.
+///
+/// ```
+/// use components::{Consumable, ConsumableType, Inventory, ItemType, Stash};
+///
+/// let mut stash = Stash::new();
+/// let tab = stash.add_tab("Potions", 10);
+///
+/// let mut inventory = Inventory::new();
+/// inventory.put_consumable(Consumable::new(ConsumableType::Elixir)).unwrap();
+/// stash.deposit(tab, &mut inventory, ItemType::Consumable, 0).unwrap();
+///
+/// assert!(inventory.is_empty());
+/// assert_eq!(stash.tab(tab).unwrap().items().len(), 1);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stash {
+    tabs: Vec<StashTab>,
+}
+
+impl Stash {
+    /// Creates a new stash with no tabs.
+    pub fn new() -> Stash {
+        Self::default()
+    }
+
+    /// Add a tab holding at most the given amount of items.
+    ///
+    /// ## Returns
+    /// [`usize`] The index of the new tab.
+    pub fn add_tab(&mut self, name: &str, capacity: u32) -> usize {
+        self.tabs.push(StashTab {
+            name: name.to_string(),
+            items: Inventory::with_capacity(capacity),
+        });
+        self.tabs.len() - 1
+    }
+
+    /// Remove the tab at the given index, Only empty tabs can be removed.
+    pub fn remove_tab(&mut self, tab: usize) -> anyhow::Result<StashTab> {
+        if !self.tab(tab)?.items.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Stash tab {} isn't empty",
+                self.tabs[tab].name
+            ));
+        }
+        Ok(self.tabs.remove(tab))
+    }
+
+    pub fn rename_tab(&mut self, tab: usize, name: &str) -> anyhow::Result<()> {
+        self.tab_mut(tab)?.name = name.to_string();
+        Ok(())
+    }
+
+    pub fn tab(&self, tab: usize) -> anyhow::Result<&StashTab> {
+        self.tabs
+            .get(tab)
+            .ok_or_else(|| anyhow::anyhow!("No stash tab at index {}", tab))
+    }
+
+    fn tab_mut(&mut self, tab: usize) -> anyhow::Result<&mut StashTab> {
+        self.tabs
+            .get_mut(tab)
+            .ok_or_else(|| anyhow::anyhow!("No stash tab at index {}", tab))
+    }
+
+    pub fn tabs(&self) -> &[StashTab] {
+        &self.tabs
+    }
+
+    /// The number of items in every tab, A stack counts as one item.
+    pub fn len(&self) -> usize {
+        self.tabs.iter().map(|t| t.items.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Move the item of the given type at the given index of the inventory into the tab.
+    ///
+    /// ## Returns
+    /// [`u32`] The amount of items moved.
+    pub fn deposit(
+        &mut self,
+        tab: usize,
+        inventory: &mut Inventory,
+        item_type: ItemType,
+        index: usize,
+    ) -> anyhow::Result<u32> {
+        inventory.transfer(&mut self.tab_mut(tab)?.items, item_type, index)
+    }
+
+    /// Move the item of the given type at the given index of the tab into the inventory.
+    ///
+    /// ## Returns
+    /// [`u32`] The amount of items moved.
+    pub fn withdraw(
+        &mut self,
+        tab: usize,
+        inventory: &mut Inventory,
+        item_type: ItemType,
+        index: usize,
+    ) -> anyhow::Result<u32> {
+        self.tab_mut(tab)?
+            .items
+            .transfer(inventory, item_type, index)
+    }
+
+    /// Move the item of the given type at the given index of one tab into another.
+    ///
+    /// ## Returns
+    /// [`u32`] The amount of items moved.
+    pub fn move_item(
+        &mut self,
+        from: usize,
+        to: usize,
+        item_type: ItemType,
+        index: usize,
+    ) -> anyhow::Result<u32> {
+        self.tab(to)?;
+        if from == to {
+            return Err(anyhow::anyhow!("Can't move items into the same stash tab"));
+        }
+        let mut items = self.tab(from)?.items.clone();
+        let moved = items.transfer(&mut self.tabs[to].items, item_type, index)?;
+        self.tabs[from].items = items;
+        Ok(moved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        consumable::{Consumable, ConsumableType},
+        material::{Material, MaterialType},
+        weapon::{Weapon, WeaponType},
+    };
+
+    #[test]
+    fn test_transfers() {
+        let mut stash = Stash::new();
+        let main = stash.add_tab("Main", 2);
+        let spare = stash.add_tab("Spare", 1);
+
+        let mut vamp = Inventory::new();
+        for _ in 0..3 {
+            vamp.put_material(Material::new(MaterialType::Scrap))
+                .unwrap();
+        }
+        vamp.put_weapon(Weapon::new(WeaponType::Claw)).unwrap();
+        assert_eq!(
+            stash
+                .deposit(main, &mut vamp, ItemType::Material, 0)
+                .unwrap(),
+            3
+        );
+        stash.deposit(main, &mut vamp, ItemType::Weapon, 0).unwrap();
+        assert!(vamp.is_empty());
+        assert_eq!(stash.len(), 2);
+
+        let mut kain = Inventory::new();
+        stash
+            .withdraw(main, &mut kain, ItemType::Material, 0)
+            .unwrap();
+        assert_eq!(
+            kain.count(crate::LootItem::Material(MaterialType::Scrap)),
+            3
+        );
+
+        stash.move_item(main, spare, ItemType::Weapon, 0).unwrap();
+        assert!(stash.tab(main).unwrap().items().is_empty());
+        assert!(stash.move_item(main, spare, ItemType::Weapon, 0).is_err());
+        assert!(stash.remove_tab(spare).is_err());
+        assert!(stash.remove_tab(main).is_ok());
+        assert_eq!(stash.tabs()[0].name(), "Spare");
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut stash = Stash::new();
+        let tab = stash.add_tab("Main", 1);
+
+        let mut inventory = Inventory::with_capacity(1);
+        inventory
+            .put_consumable(Consumable::new(ConsumableType::Antidote))
+            .unwrap();
+        stash
+            .deposit(tab, &mut inventory, ItemType::Consumable, 0)
+            .unwrap();
+
+        inventory.put_weapon(Weapon::new(WeaponType::Bow)).unwrap();
+        let before = (stash.clone(), inventory.clone());
+        assert!(stash
+            .deposit(tab, &mut inventory, ItemType::Weapon, 0)
+            .is_err());
+        assert!(stash
+            .withdraw(tab, &mut inventory, ItemType::Consumable, 0)
+            .is_err());
+        assert!(stash
+            .deposit(tab + 1, &mut inventory, ItemType::Weapon, 0)
+            .is_err());
+        assert_eq!((stash, inventory), before);
+    }
+}
@@ -0,0 +1,253 @@
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::health::Health;
+use crate::items::MetaData;
+use serde::{Deserialize, Serialize};
+
+/// ## Status effects that can be applied to a living object.
+/// - [`StatusKind::Burn`]
+///     - Deals damage every tick.
+/// - [`StatusKind::Bleed`]
+///     - Deals damage every tick.
+/// - [`StatusKind::Toxin`]
+///     - Deals damage every tick.
+/// - [`StatusKind::Freeze`]
+///     - Prevents the object from acting.
+/// - [`StatusKind::Stun`]
+///     - Prevents the object from acting.
+/// - [`StatusKind::Weaken`]
+///     - Reduces the damage the object deals by its potency percentage.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum StatusKind {
+    Burn,
+    Bleed,
+    Toxin,
+    Freeze,
+    Stun,
+    Weaken,
+}
+
+impl StatusKind {
+    /// Whether this status deals damage every tick.
+    pub fn is_damage_over_time(&self) -> bool {
+        matches!(
+            self,
+            StatusKind::Burn | StatusKind::Bleed | StatusKind::Toxin
+        )
+    }
+
+    /// Whether this status prevents the object from acting.
+    pub fn prevents_action(&self) -> bool {
+        matches!(self, StatusKind::Freeze | StatusKind::Stun)
+    }
+}
+
+impl MetaData for StatusKind {
+    fn name(&self) -> &'static str {
+        match self {
+            StatusKind::Burn => "Burn",
+            StatusKind::Bleed => "Bleed",
+            StatusKind::Toxin => "Toxin",
+            StatusKind::Freeze => "Freeze",
+            StatusKind::Stun => "Stun",
+            StatusKind::Weaken => "Weaken",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            StatusKind::Burn => "Burning, Taking fire damage overtime.",
+            StatusKind::Bleed => "Bleeding, Taking damage overtime.",
+            StatusKind::Toxin => "Poisoned by toxins, Taking damage overtime.",
+            StatusKind::Freeze => "Frozen solid and unable to act.",
+            StatusKind::Stun => "Stunned and unable to act.",
+            StatusKind::Weaken => "Weakened by the void, Dealing less damage.",
+        }
+    }
+}
+
+/// A single status effect instance.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// How many ticks this effect lasts.
+    pub duration: u32,
+    /// Damage per tick for damage over time effects, Damage reduction percentage for [`StatusKind::Weaken`].
+    pub potency: u32,
+}
+
+impl StatusEffect {
+    pub const fn new(kind: StatusKind, duration: u32, potency: u32) -> StatusEffect {
+        StatusEffect {
+            kind,
+            duration,
+            potency,
+        }
+    }
+}
+
+impl std::fmt::Display for StatusEffect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "StatusEffect(kind: {}, duration: {}, potency: {})",
+            self.kind.name(),
+            self.duration,
+            self.potency
+        )
+    }
+}
+
+/// Core component for the status effects currently applied to an object.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Creates a new empty status effects object.
+    pub fn new() -> StatusEffects {
+        Self::default()
+    }
+
+    /// Apply a status effect.
+    ///
+    /// Applying an effect of a kind that's already active refreshes it,
+    /// Keeping the longest duration and the highest potency.
+    pub fn apply(&mut self, effect: StatusEffect) {
+        match self.effects.iter_mut().find(|e| e.kind == effect.kind) {
+            Some(active) => {
+                active.duration = active.duration.max(effect.duration);
+                active.potency = active.potency.max(effect.potency);
+            }
+            None => self.effects.push(effect),
+        }
+    }
+
+    /// Returns the active effect of the given kind if any.
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|e| e.kind == kind)
+    }
+
+    /// Whether an effect of the given kind is active.
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    /// Returns the active effect that prevents acting if any.
+    pub fn incapacitated_by(&self) -> Option<StatusKind> {
+        self.effects
+            .iter()
+            .map(|e| e.kind)
+            .find(StatusKind::prevents_action)
+    }
+
+    /// Reduce damage by the active [`StatusKind::Weaken`] effect.
+    pub fn weaken(&self, damage: u32) -> u32 {
+        match self.get(StatusKind::Weaken) {
+            Some(effect) => damage.saturating_mul(100 - effect.potency.min(100)) / 100,
+            None => damage,
+        }
+    }
+
+    /// An iterator over the active effects.
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Remove all active effects.
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    /// Advance all effects by one tick, Applying damage over time to the given health
+    /// and removing expired effects.
+    ///
+    /// ## Returns
+    /// [`Vec<(StatusKind, u32)>`] The health lost to each damage over time effect.
+    pub fn tick(&mut self, health: &mut Health) -> Vec<(StatusKind, u32)> {
+        let mut ticks = Vec::new();
+
+        for effect in self.effects.iter_mut() {
+            if effect.kind.is_damage_over_time() && !health.is_killed() {
+                let before = health.current();
+                ticks.push((effect.kind, before - health.drip(effect.potency)));
+            }
+            effect.duration = effect.duration.saturating_sub(1);
+        }
+        self.effects.retain(|e| e.duration > 0);
+        ticks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick() {
+        let mut health = Health::default();
+        let mut effects = StatusEffects::new();
+        effects.apply(StatusEffect::new(StatusKind::Burn, 2, 5));
+        effects.apply(StatusEffect::new(StatusKind::Stun, 1, 0));
+        assert_eq!(effects.incapacitated_by(), Some(StatusKind::Stun));
+
+        assert_eq!(effects.tick(&mut health), vec![(StatusKind::Burn, 5)]);
+        assert_eq!(health.current(), 95);
+        assert_eq!(effects.incapacitated_by(), None);
+
+        effects.tick(&mut health);
+        assert_eq!(health.current(), 90);
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn test_refresh() {
+        let mut effects = StatusEffects::new();
+        effects.apply(StatusEffect::new(StatusKind::Weaken, 3, 20));
+        effects.apply(StatusEffect::new(StatusKind::Weaken, 1, 50));
+
+        let weaken = effects.get(StatusKind::Weaken).unwrap();
+        assert_eq!((weaken.duration, weaken.potency), (3, 50));
+        assert_eq!(effects.weaken(10), 5);
+    }
+
+    #[test]
+    fn test_weaken_overflow() {
+        let mut effects = StatusEffects::new();
+        effects.apply(StatusEffect::new(StatusKind::Weaken, 1, 50));
+        assert_eq!(effects.weaken(u32::MAX), u32::MAX / 100);
+    }
+}
@@ -0,0 +1,413 @@
+//! Vendors that buy and sell items for gold.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    inventory::Inventory,
+    items::{Item, ItemType},
+    loot::{Loot, LootTable},
+    wallet::Wallet,
+    weapon::Weapon,
+};
+use rand::Rng;
+use std::collections::VecDeque;
+
+/// The most items a vendor keeps for buyback, The oldest sold items are dropped first.
+pub const BUYBACK_SIZE: usize = 10;
+
+/// The percentage of an item's value vendors pay for it.
+pub const SELL_RATE: u64 = 40;
+
+/// The gold a point of durability costs to repair, Multiplied by the weapon's rarity value multiplier.
+pub const REPAIR_RATE: u64 = 2;
+
+/// ## Modifiers of a vendor's prices.
+/// - [`PriceModifier::Discount`]
+///     - Buying and repairing is cheaper by a percentage.
+/// - [`PriceModifier::Markup`]
+///     - Buying and repairing is more expensive by a percentage.
+/// - [`PriceModifier::Bonus`]
+///     - Selling pays more by a percentage, Never more than buying the item back costs.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PriceModifier {
+    Discount(u32),
+    Markup(u32),
+    Bonus(u32),
+}
+
+/// A vendor with a stock of items to sell.
+///
+/// ```
+/// use components::{Inventory, Vendor, Wallet, LootTable};
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let mut rng = StdRng::seed_from_u64(0);
+/// let mut vendor = Vendor::from_loot_table("Hagen", LootTable::get("beast").unwrap(), 10, &mut rng);
+/// let mut wallet = Wallet::new(10_000);
+/// let mut inventory = Inventory::new();
+///
+/// if !vendor.stock().is_empty() {
+///     vendor.buy(0, &mut wallet, &mut inventory).unwrap();
+///     assert_eq!(inventory.len(), 1);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vendor {
+    name: &'static str,
+    stock: Vec<Loot>,
+    buyback: VecDeque<(Loot, u64)>,
+    modifiers: Vec<PriceModifier>,
+}
+
+impl Vendor {
+    /// Creates a new vendor with no stock.
+    pub fn new(name: &'static str) -> Vendor {
+        Vendor {
+            name,
+            stock: Vec::new(),
+            buyback: VecDeque::new(),
+            modifiers: Vec::new(),
+        }
+    }
+
+    /// Creates a new vendor stocked by rolling the loot table the given amount of times.
+    pub fn from_loot_table<R: Rng + ?Sized>(
+        name: &'static str,
+        table: &LootTable,
+        rolls: usize,
+        rng: &mut R,
+    ) -> Vendor {
+        let mut vendor = Vendor::new(name);
+        vendor.restock(table, rolls, rng);
+        vendor
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The items this vendor sells.
+    pub fn stock(&self) -> &[Loot] {
+        &self.stock
+    }
+
+    /// Items sold to this vendor and the gold they were sold for, Oldest first.
+    pub fn buyback(&self) -> impl Iterator<Item = &(Loot, u64)> {
+        self.buyback.iter()
+    }
+
+    pub fn modifiers(&self) -> &[PriceModifier] {
+        &self.modifiers
+    }
+
+    pub fn add_modifier(&mut self, modifier: PriceModifier) {
+        self.modifiers.push(modifier);
+    }
+
+    pub fn remove_modifier(&mut self, modifier: PriceModifier) -> bool {
+        let len = self.modifiers.len();
+        self.modifiers.retain(|m| *m != modifier);
+        len != self.modifiers.len()
+    }
+
+    /// Add items to the stock by rolling the loot table the given amount of times.
+    pub fn restock<R: Rng + ?Sized>(&mut self, table: &LootTable, rolls: usize, rng: &mut R) {
+        for _ in 0..rolls {
+            self.stock.extend(table.roll(rng));
+        }
+    }
+
+    /// Add an item to the stock.
+    pub fn stock_item(&mut self, item: Loot) {
+        self.stock.push(item);
+    }
+
+    /// The gold buying the item from this vendor costs.
+    pub fn buy_price(&self, item: &dyn Item) -> u64 {
+        self.adjusted(item.value())
+    }
+
+    /// The gold this vendor pays for the item.
+    pub fn sell_price(&self, item: &dyn Item) -> u64 {
+        let bonus: u64 = self
+            .modifiers
+            .iter()
+            .map(|modifier| match modifier {
+                PriceModifier::Bonus(bonus) => u64::from(*bonus),
+                _ => 0,
+            })
+            .sum();
+        let price = item
+            .value()
+            .saturating_mul(SELL_RATE)
+            .saturating_mul(bonus.saturating_add(100))
+            / 10_000;
+        price.min(self.buy_price(item))
+    }
+
+    /// The gold fully repairing the weapon costs, `0` if it doesn't need repairs.
+    pub fn repair_price(&self, weapon: &Weapon) -> u64 {
+        let missing = weapon.durability().map_or(0, |d| d.missing());
+        self.adjusted(
+            u64::from(missing)
+                .saturating_mul(REPAIR_RATE)
+                .saturating_mul(weapon.rarity().value_multiplier()),
+        )
+    }
+
+    /// Buy the item at the given stock index, Putting it in the inventory.
+    ///
+    /// Nothing is changed if the wallet can't afford it or the inventory can't hold it.
+    pub fn buy(
+        &mut self,
+        index: usize,
+        wallet: &mut Wallet,
+        inventory: &mut Inventory,
+    ) -> anyhow::Result<Loot> {
+        let item = *self
+            .stock
+            .get(index)
+            .ok_or_else(|| anyhow::anyhow!("{} has no item at index {}", self.name, index))?;
+        self.pay(item, self.buy_price(item.item()), wallet, inventory)?;
+        self.stock.remove(index);
+        Ok(item)
+    }
+
+    /// Sell the item of the given type at the given inventory index to this vendor.
+    ///
+    /// Sold items can be bought back for the same price, See [`Vendor::buy_back`].
+    /// Nothing is changed if the wallet can't hold the gold.
+    ///
+    /// ## Returns
+    /// [`u64`] The gold the item was sold for.
+    pub fn sell(
+        &mut self,
+        item_type: ItemType,
+        index: usize,
+        wallet: &mut Wallet,
+        inventory: &mut Inventory,
+    ) -> anyhow::Result<u64> {
+        let mut sold = inventory.clone();
+        let item = sold
+            .take(item_type, index)
+            .ok_or_else(|| anyhow::anyhow!("No {:?} at inventory index {}", item_type, index))?;
+        let price = self.sell_price(item.item());
+        wallet.deposit(price)?;
+        *inventory = sold;
+
+        if self.buyback.len() == BUYBACK_SIZE {
+            self.buyback.pop_front();
+        }
+        self.buyback.push_back((item, price));
+        Ok(price)
+    }
+
+    /// Buy back the sold item at the given buyback index for the gold it was sold for.
+    ///
+    /// Nothing is changed if the wallet can't afford it or the inventory can't hold it.
+    pub fn buy_back(
+        &mut self,
+        index: usize,
+        wallet: &mut Wallet,
+        inventory: &mut Inventory,
+    ) -> anyhow::Result<Loot> {
+        let (item, price) = *self
+            .buyback
+            .get(index)
+            .ok_or_else(|| anyhow::anyhow!("{} has no sold item at index {}", self.name, index))?;
+        self.pay(item, price, wallet, inventory)?;
+        self.buyback.remove(index);
+        Ok(item)
+    }
+
+    /// Fully repair the weapon for gold.
+    ///
+    /// Nothing is changed if it doesn't need repairs or the wallet can't afford it.
+    ///
+    /// ## Returns
+    /// [`u64`] The gold the repair cost.
+    pub fn repair(&self, weapon: &mut Weapon, wallet: &mut Wallet) -> anyhow::Result<u64> {
+        let price = self.repair_price(weapon);
+        let name = weapon.name();
+        let durability = weapon
+            .durability_mut()
+            .filter(|d| d.missing() > 0)
+            .ok_or_else(|| anyhow::anyhow!("{} doesn't need repairs", name))?;
+        wallet.withdraw(price)?;
+        durability.repair(u32::MAX);
+        Ok(price)
+    }
+
+    fn pay(
+        &self,
+        item: Loot,
+        price: u64,
+        wallet: &mut Wallet,
+        inventory: &mut Inventory,
+    ) -> anyhow::Result<()> {
+        let mut bought = inventory.clone();
+        bought.put_loot(item)?;
+        wallet.withdraw(price)?;
+        *inventory = bought;
+        Ok(())
+    }
+
+    /// Apply the discounts and markups to a price, Discounts never take more than 90% off.
+    fn adjusted(&self, price: u64) -> u64 {
+        let (discount, markup) = self.modifiers.iter().fold(
+            (0u64, 0u64),
+            |(discount, markup), modifier| match modifier {
+                PriceModifier::Discount(d) => (discount.saturating_add(u64::from(*d)), markup),
+                PriceModifier::Markup(m) => (discount, markup.saturating_add(u64::from(*m))),
+                PriceModifier::Bonus(_) => (discount, markup),
+            },
+        );
+        let percentage = markup.saturating_add(100).saturating_sub(discount).max(10);
+        price.saturating_mul(percentage) / 100
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        consumable::{Consumable, ConsumableType},
+        items::ItemRarity,
+        weapon::WeaponType,
+    };
+
+    fn potion() -> Loot {
+        Loot::Consumable(Consumable::new(ConsumableType::HealthPotion))
+    }
+
+    #[test]
+    fn test_buy_and_sell() {
+        let mut vendor = Vendor::new("Hagen");
+        vendor.stock_item(potion());
+        let mut wallet = Wallet::new(10);
+        let mut inventory = Inventory::new();
+
+        assert!(vendor.buy(0, &mut wallet, &mut inventory).is_err());
+        assert_eq!(wallet.gold(), 10);
+
+        wallet.deposit(15).unwrap();
+        vendor.buy(0, &mut wallet, &mut inventory).unwrap();
+        assert_eq!(wallet.gold(), 0);
+        assert!(vendor.stock().is_empty());
+
+        assert_eq!(
+            vendor
+                .sell(ItemType::Consumable, 0, &mut wallet, &mut inventory)
+                .unwrap(),
+            10
+        );
+        assert_eq!(inventory.len(), 0);
+        assert!(vendor
+            .sell(ItemType::Consumable, 0, &mut wallet, &mut inventory)
+            .is_err());
+
+        vendor.buy_back(0, &mut wallet, &mut inventory).unwrap();
+        assert_eq!(wallet.gold(), 0);
+        assert_eq!(inventory.len(), 1);
+        assert_eq!(vendor.buyback().count(), 0);
+    }
+
+    #[test]
+    fn test_modifiers() {
+        let mut vendor = Vendor::new("Hagen");
+        let item = potion();
+        assert_eq!(vendor.buy_price(item.item()), 25);
+
+        vendor.add_modifier(PriceModifier::Discount(20));
+        vendor.add_modifier(PriceModifier::Bonus(500));
+        assert_eq!(vendor.buy_price(item.item()), 20);
+        assert_eq!(vendor.sell_price(item.item()), 20);
+
+        vendor.add_modifier(PriceModifier::Discount(500));
+        assert_eq!(vendor.buy_price(item.item()), 2);
+        assert!(vendor.remove_modifier(PriceModifier::Discount(500)));
+
+        let exotic = Weapon::new(WeaponType::Daggers).with_rarity(ItemRarity::Exotic);
+        vendor.add_modifier(PriceModifier::Markup(u32::MAX));
+        assert!(vendor.buy_price(&exotic) > exotic.value());
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut vendor = Vendor::new("Hagen");
+        let mut wallet = Wallet::new(u64::MAX);
+        let mut inventory = Inventory::new();
+        inventory.put_loot(potion()).unwrap();
+
+        assert!(vendor
+            .sell(ItemType::Consumable, 0, &mut wallet, &mut inventory)
+            .is_err());
+        assert_eq!(inventory.len(), 1);
+    }
+
+    #[test]
+    fn test_buyback_size() {
+        let mut vendor = Vendor::new("Hagen");
+        let mut wallet = Wallet::default();
+        let mut inventory = Inventory::new();
+        for _ in 0..BUYBACK_SIZE + 2 {
+            inventory.put_loot(potion()).unwrap();
+            vendor
+                .sell(ItemType::Consumable, 0, &mut wallet, &mut inventory)
+                .unwrap();
+        }
+        assert_eq!(vendor.buyback().count(), BUYBACK_SIZE);
+    }
+
+    #[test]
+    fn test_repair() {
+        let vendor = Vendor::new("Hagen");
+        let mut wallet = Wallet::new(100);
+        let mut mace = Weapon::new(WeaponType::Mace)
+            .with_rarity(ItemRarity::Legendry)
+            .with_durability(50);
+        assert_eq!(
+            vendor
+                .repair(&mut mace, &mut wallet)
+                .unwrap_err()
+                .to_string(),
+            format!("{} doesn't need repairs", mace.name())
+        );
+
+        mace.wear(20);
+        assert_eq!(vendor.repair_price(&mace), 120);
+        assert!(vendor.repair(&mut mace, &mut wallet).is_err());
+
+        wallet.deposit(20).unwrap();
+        assert_eq!(vendor.repair(&mut mace, &mut wallet).unwrap(), 120);
+        assert_eq!(mace.durability().unwrap().missing(), 0);
+        assert_eq!(wallet.gold(), 0);
+    }
+}
@@ -0,0 +1,114 @@
+//! Currency held by characters.
+// BSD 3-Clause License
+
+// Copyright (c) 2022, nxtlo
+// All rights reserved.
+
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are met:
+
+// 1. Redistributions of source code must retain the above copyright notice, this
+// list of conditions and the following disclaimer.
+
+// 2. Redistributions in binary form must reproduce the above copyright notice,
+// this list of conditions and the following disclaimer in the documentation
+// and/or other materials provided with the distribution.
+
+// 3. Neither the name of the copyright holder nor the names of its
+// contributors may be used to endorse or promote products derived from
+// this software without specific prior written permission.
+
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+// AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+// IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+// FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+// DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+// CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+// OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+// OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Core wallet component, Holds gold.
+///
+/// Every operation is checked, Gold never overflows or goes below zero.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct Wallet {
+    gold: u64,
+}
+
+impl fmt::Display for Wallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Wallet(gold: {})", self.gold)
+    }
+}
+
+impl Wallet {
+    /// Creates a new wallet holding the given gold.
+    pub fn new(gold: u64) -> Wallet {
+        Wallet { gold }
+    }
+
+    pub fn gold(&self) -> u64 {
+        self.gold
+    }
+
+    pub fn can_afford(&self, amount: u64) -> bool {
+        self.gold >= amount
+    }
+
+    /// Put gold in the wallet, Fails if it can't hold that much.
+    pub fn deposit(&mut self, amount: u64) -> anyhow::Result<u64> {
+        self.gold = self
+            .gold
+            .checked_add(amount)
+            .ok_or_else(|| anyhow::anyhow!("Wallet can't hold {} more gold", amount))?;
+        Ok(self.gold)
+    }
+
+    /// Take gold out of the wallet, Fails if there isn't enough.
+    pub fn withdraw(&mut self, amount: u64) -> anyhow::Result<u64> {
+        self.gold = self.gold.checked_sub(amount).ok_or_else(|| {
+            anyhow::anyhow!("Not enough gold, {} of {} needed", self.gold, amount)
+        })?;
+        Ok(self.gold)
+    }
+
+    /// Move gold to another wallet, Nothing is changed if either side fails.
+    pub fn transfer(&mut self, to: &mut Wallet, amount: u64) -> anyhow::Result<()> {
+        if !self.can_afford(amount) {
+            return Err(anyhow::anyhow!(
+                "Not enough gold, {} of {} needed",
+                self.gold,
+                amount
+            ));
+        }
+        to.deposit(amount)?;
+        self.gold -= amount;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overflow() {
+        let mut wallet = Wallet::new(u64::MAX - 5);
+        assert!(wallet.deposit(10).is_err());
+        assert_eq!(wallet.gold(), u64::MAX - 5);
+        assert_eq!(wallet.deposit(5).unwrap(), u64::MAX);
+
+        let mut poor = Wallet::new(3);
+        assert!(poor.withdraw(4).is_err());
+        assert!(poor.transfer(&mut wallet, 1).is_err());
+        assert_eq!(poor.gold(), 3);
+
+        poor.transfer(&mut Wallet::default(), 3).unwrap();
+        assert_eq!(poor.gold(), 0);
+    }
+}
@@ -28,13 +28,20 @@
 // OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 // OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::durability::Durability;
 use crate::items::{Generator, Item, ItemRarity, ItemType, MetaData};
+use crate::status::{StatusEffect, StatusKind};
+
+use serde::{Deserialize, Deserializer, Serialize};
 
 use rand::prelude::SliceRandom;
-use rand::{random, thread_rng};
+use rand::{
+    distributions::{Distribution, Standard},
+    random, thread_rng, Rng,
+};
 use std::fmt;
 
-static UNKNOWN: &str = &"UNKNOWN";
+static UNKNOWN: &str = "UNKNOWN";
 
 /// ## Weapons have ammo, And ammo have a damage type.
 /// These are the available types.
@@ -44,10 +51,10 @@ static UNKNOWN: &str = &"UNKNOWN";
 ///     - A type of weapon ammo that can heal allies.
 /// - [`WeaponAmmoType::Void`]
 ///     - A type of weapon ammo that consumes the enemy's health
-///     damaging them and debuffing for 5 seconds.
+///       damaging them and debuffing for 5 seconds.
 /// - [`WeaponAmmoType::Elemental`]
 ///     - A type of weapon that can deal elemental damage. Either fire, ice, or lightning.
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum WeaponAmmoType {
     Toxic,
     Radiant,
@@ -55,6 +62,49 @@ pub enum WeaponAmmoType {
     Elemental,
 }
 
+impl WeaponAmmoType {
+    /// The value bonus percentage of weapons with this ammo type.
+    pub fn value_bonus(&self) -> u64 {
+        match self {
+            WeaponAmmoType::Toxic => 0,
+            WeaponAmmoType::Elemental => 10,
+            WeaponAmmoType::Radiant => 20,
+            WeaponAmmoType::Void => 25,
+        }
+    }
+}
+
+impl Distribution<WeaponAmmoType> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> WeaponAmmoType {
+        match rng.gen_range(0..=3) {
+            0 => WeaponAmmoType::Toxic,
+            1 => WeaponAmmoType::Radiant,
+            2 => WeaponAmmoType::Void,
+            _ => WeaponAmmoType::Elemental,
+        }
+    }
+}
+
+impl MetaData for WeaponAmmoType {
+    fn name(&self) -> &'static str {
+        match self {
+            WeaponAmmoType::Toxic => "Toxic",
+            WeaponAmmoType::Radiant => "Radiant",
+            WeaponAmmoType::Void => "Void",
+            WeaponAmmoType::Elemental => "Elemental",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            WeaponAmmoType::Toxic => "A toxic weapon that damage enemies overtime.",
+            WeaponAmmoType::Radiant => "A type of weapon ammo that can heal allies.",
+            WeaponAmmoType::Void => "A type of weapon ammo that consumes the enemy's health.",
+            WeaponAmmoType::Elemental => "A type of weapon that can deal elemental damage.",
+        }
+    }
+}
+
 /**
 ## Core weapon types.
 ### Random drop weapon names for each type.
@@ -85,19 +135,104 @@ pub enum WeaponAmmoType {
 - `Serrated Edge`
 - `Soul Eater`
 */
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub enum WeaponType {
     Mace,
+    // Base starter weapon.
+    #[default]
     Bow,
     Rod,
     Claw,
     Daggers,
 }
 
-impl Default for WeaponType {
-    // Base starter weapon.
-    fn default() -> Self {
-        Self::Bow
+/// How many hands a [`WeaponType`] occupies when equipped.
+///
+/// - [`Handedness::OneHanded`]
+///     - Can be held in either hand, Two of them can be dual wielded.
+/// - [`Handedness::TwoHanded`]
+///     - Occupies both hands.
+/// - [`Handedness::Paired`]
+///     - Comes as a pair, One in each hand. Occupies both hands.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Handedness {
+    OneHanded,
+    TwoHanded,
+    Paired,
+}
+
+impl Handedness {
+    /// Whether this weapon occupies both the main hand and the off hand.
+    pub fn is_both_hands(&self) -> bool {
+        !matches!(self, Handedness::OneHanded)
+    }
+}
+
+impl WeaponType {
+    /// Every weapon type.
+    pub fn all() -> &'static [WeaponType] {
+        &[
+            WeaponType::Mace,
+            WeaponType::Bow,
+            WeaponType::Rod,
+            WeaponType::Claw,
+            WeaponType::Daggers,
+        ]
+    }
+    /// Returns how many hands this weapon type occupies.
+    pub fn handedness(&self) -> Handedness {
+        match self {
+            WeaponType::Mace | WeaponType::Claw | WeaponType::Rod => Handedness::OneHanded,
+            WeaponType::Bow => Handedness::TwoHanded,
+            WeaponType::Daggers => Handedness::Paired,
+        }
+    }
+
+    /// Whether this weapon type attacks from range.
+    pub fn is_ranged(&self) -> bool {
+        matches!(self, WeaponType::Bow | WeaponType::Rod)
+    }
+
+    /// The base damage a hit with this weapon type deals before rarity bonuses.
+    pub fn base_damage(&self) -> u32 {
+        match self {
+            WeaponType::Mace => 14,
+            WeaponType::Bow => 18,
+            WeaponType::Rod => 10,
+            WeaponType::Claw => 12,
+            WeaponType::Daggers => 16,
+        }
+    }
+
+    /// How heavy weapons of this type are.
+    pub fn weight(&self) -> u32 {
+        match self {
+            WeaponType::Daggers => 2,
+            WeaponType::Bow => 3,
+            WeaponType::Claw => 4,
+            WeaponType::Rod => 5,
+            WeaponType::Mace => 8,
+        }
+    }
+
+    /// The width and height weapons of this type take in an inventory grid.
+    pub fn size(&self) -> (u32, u32) {
+        match self.handedness() {
+            Handedness::OneHanded => (1, 3),
+            Handedness::Paired => (2, 2),
+            Handedness::TwoHanded => (2, 4),
+        }
+    }
+
+    /// The status effect hits with this weapon type can apply and the chance percentage of applying it.
+    pub fn on_hit_status(&self) -> (StatusEffect, u32) {
+        match self {
+            WeaponType::Bow => (StatusEffect::new(StatusKind::Freeze, 1, 0), 10),
+            WeaponType::Mace => (StatusEffect::new(StatusKind::Burn, 3, 4), 20),
+            WeaponType::Claw => (StatusEffect::new(StatusKind::Bleed, 3, 5), 33),
+            WeaponType::Rod => (StatusEffect::new(StatusKind::Stun, 1, 0), 15),
+            WeaponType::Daggers => (StatusEffect::new(StatusKind::Toxin, 3, 3), 20),
+        }
     }
 }
 
@@ -117,7 +252,7 @@ impl MetaData for WeaponType {
                 )
             }
             WeaponType::Claw => {
-                concat!("An powerful lethal weapon that bleedes enemies every 3 successful hits",)
+                "An powerful lethal weapon that bleedes enemies every 3 successful hits"
             }
             WeaponType::Rod => {
                 "A high velocity, Ranged, Magical weapon that can stun enemies on hits."
@@ -142,21 +277,11 @@ impl MetaData for WeaponType {
 impl Generator for WeaponType {
     fn generate_name(&self) -> Vec<&'static str> {
         match *self {
-            WeaponType::Mace => {
-                return vec!["Threaded Needle", "Jotunn'a Vigor", "Hydras"];
-            }
-            WeaponType::Bow => {
-                return vec!["Scream", "Sorrowbane", "Death's whisper"];
-            }
-            WeaponType::Rod => {
-                return vec!["Underlight Angler", "Bancrofts", "Arondight", "Hope"];
-            }
-            WeaponType::Claw => {
-                return vec!["Thnuderlord", "Throns", "Divine Ruin"];
-            }
-            WeaponType::Daggers => {
-                return vec!["Katana", "Wind Deamon", "Serrated Edge", "Soul Eater"];
-            }
+            WeaponType::Mace => vec!["Threaded Needle", "Jotunn'a Vigor", "Hydras"],
+            WeaponType::Bow => vec!["Scream", "Sorrowbane", "Death's whisper"],
+            WeaponType::Rod => vec!["Underlight Angler", "Bancrofts", "Arondight", "Hope"],
+            WeaponType::Claw => vec!["Thnuderlord", "Throns", "Divine Ruin"],
+            WeaponType::Daggers => vec!["Katana", "Wind Deamon", "Serrated Edge", "Soul Eater"],
         }
     }
 
@@ -167,12 +292,45 @@ impl Generator for WeaponType {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Weapon {
     weapon_type: WeaponType,
+    ammo_type: WeaponAmmoType,
     rarity: ItemRarity,
     name: &'static str,
     id: u8,
+    durability: Option<Durability>,
+}
+
+/// A deserialized weapon, Its name is looked up from the weapon type's names.
+#[derive(Deserialize)]
+struct WeaponRecord {
+    weapon_type: WeaponType,
+    ammo_type: WeaponAmmoType,
+    rarity: ItemRarity,
+    name: String,
+    id: u8,
+    #[serde(default)]
+    durability: Option<Durability>,
+}
+
+impl<'de> Deserialize<'de> for Weapon {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let record = WeaponRecord::deserialize(deserializer)?;
+        Ok(Weapon {
+            name: record
+                .weapon_type
+                .generate_name()
+                .into_iter()
+                .find(|name| *name == record.name)
+                .unwrap_or(UNKNOWN),
+            weapon_type: record.weapon_type,
+            ammo_type: record.ammo_type,
+            rarity: record.rarity,
+            id: record.id,
+            durability: record.durability,
+        })
+    }
 }
 
 impl Default for Weapon {
@@ -180,9 +338,11 @@ impl Default for Weapon {
         let weapon_type = WeaponType::default();
         Weapon {
             weapon_type,
+            ammo_type: random::<WeaponAmmoType>(),
             rarity: random::<ItemRarity>(),
             id: random::<u8>(),
             name: weapon_type.auto_name(),
+            durability: None,
         }
     }
 }
@@ -191,10 +351,11 @@ impl fmt::Debug for Weapon {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Weapon(name: {}, hash: {}, type: {}, description: {})",
+            "Weapon(name: {}, hash: {}, type: {}, ammo: {}, description: {})",
             self.name,
             self.id,
             self.weapon_type.name(),
+            self.ammo_type.name(),
             self.weapon_type.description(),
         )
     }
@@ -204,10 +365,11 @@ impl fmt::Display for Weapon {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Weapon(name: {}, hash: {}, type: {}, description: {})",
+            "Weapon(name: {}, hash: {}, type: {}, ammo: {}, description: {})",
             self.name,
             self.id,
             self.weapon_type.name(),
+            self.ammo_type.name(),
             self.weapon_type.description()
         )
     }
@@ -229,6 +391,26 @@ impl Item for Weapon {
     fn item_type(&self) -> crate::items::ItemType {
         ItemType::Weapon
     }
+
+    fn weight(&self) -> u32 {
+        self.weapon_type.weight()
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.weapon_type.size()
+    }
+
+    /// Worth more for rarer weapons and better ammo, Worn weapons are worth less.
+    fn value(&self) -> u64 {
+        let effectiveness = self.durability.map_or(100, |d| d.effectiveness().max(10));
+        u64::from(self.weapon_type.base_damage())
+            .saturating_mul(10)
+            .saturating_mul(self.rarity.value_multiplier())
+            .saturating_mul(100 + self.ammo_type.value_bonus())
+            / 100
+            * u64::from(effectiveness)
+            / 100
+    }
 }
 
 impl Weapon {
@@ -236,10 +418,94 @@ impl Weapon {
         Weapon {
             name: item_type.auto_name(),
             weapon_type: item_type,
+            ammo_type: random::<WeaponAmmoType>(),
             rarity: random::<ItemRarity>(),
             id: Weapon::default().id(),
+            durability: None,
         }
     }
+
+    /// Generate a weapon rolling its name, ammo, rarity and id from the given random generator.
+    ///
+    /// Unlike [`Weapon::new`] the same seeded generator always generates the same weapon.
+    pub fn generate<R: Rng + ?Sized>(weapon_type: WeaponType, rng: &mut R) -> Weapon {
+        Weapon {
+            name: weapon_type
+                .generate_name()
+                .choose(rng)
+                .copied()
+                .unwrap_or(UNKNOWN),
+            weapon_type,
+            ammo_type: rng.gen(),
+            rarity: rng.gen(),
+            id: rng.gen(),
+            durability: None,
+        }
+    }
+
+    /// Returns this weapon with the given rarity.
+    pub fn with_rarity(mut self, rarity: ItemRarity) -> Weapon {
+        self.rarity = rarity;
+        self
+    }
+
+    /// Returns this weapon with the given ammo type.
+    pub fn with_ammo_type(mut self, ammo_type: WeaponAmmoType) -> Weapon {
+        self.ammo_type = ammo_type;
+        self
+    }
+
+    /// Returns this weapon with a full durability of the given max, Weapons without one never break.
+    pub fn with_durability(mut self, max: u32) -> Weapon {
+        self.durability = Some(Durability::new(max));
+        self
+    }
+
+    /// Returns this weapon's durability if it has one.
+    pub fn durability(&self) -> Option<&Durability> {
+        self.durability.as_ref()
+    }
+
+    /// Returns a mutable reference to this weapon's durability if it has one.
+    pub fn durability_mut(&mut self) -> Option<&mut Durability> {
+        self.durability.as_mut()
+    }
+
+    /// Whether this weapon is broken, Broken weapons can't be equipped.
+    pub fn is_broken(&self) -> bool {
+        self.durability.is_some_and(|d| d.is_broken())
+    }
+
+    /// Wear down this weapon's durability if it has one.
+    ///
+    /// ## Returns
+    /// [`bool`] Whether this broke the weapon.
+    pub fn wear(&mut self, amount: u32) -> bool {
+        self.durability.as_mut().is_some_and(|d| d.wear(amount))
+    }
+
+    /// Returns this weapon's ammo type.
+    pub fn ammo_type(&self) -> WeaponAmmoType {
+        self.ammo_type
+    }
+
+    /// Returns this weapon's type.
+    pub fn weapon_type(&self) -> WeaponType {
+        self.weapon_type
+    }
+
+    /// Returns how many hands this weapon occupies.
+    pub fn handedness(&self) -> Handedness {
+        self.weapon_type.handedness()
+    }
+
+    /// The damage a hit with this weapon deals, Including its rarity bonus.
+    ///
+    /// Weapons with low durability deal half the damage, And broken weapons deal none.
+    pub fn damage(&self) -> u32 {
+        let effectiveness = self.durability.map_or(100, |d| d.effectiveness());
+        self.weapon_type.base_damage() * self.rarity.damage_multiplier() / 100 * effectiveness / 100
+    }
 }
 
 #[test]